@@ -0,0 +1,294 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use thiserror::Error;
+
+use crate::{
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+    site_fs::SiteFs,
+};
+
+pub const ASSET_URL_PREFIX: &str = "/assets/";
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+#[derive(Debug, Error)]
+pub enum AssetError {
+    #[error("Asset {} is not known to the asset manifest", .0)]
+    Unknown(String),
+}
+
+#[derive(Debug, Error)]
+#[error("Asset processor {} failed", .processor)]
+pub struct AssetProcessError {
+    processor: String,
+    #[source]
+    source: Box<dyn Error + Send + Sync>,
+}
+
+impl AssetProcessError {
+    pub fn new(
+        processor: impl Into<String>,
+        source: impl Into<Box<dyn Error + Send + Sync>>,
+    ) -> Self {
+        Self { processor: processor.into(), source: source.into() }
+    }
+}
+
+pub trait AssetProcessor: Send + Sync + 'static {
+    fn name(&self) -> &str;
+
+    fn input_extension(&self) -> &str;
+
+    fn output_extension(&self) -> &str;
+
+    fn emits(&self, _path: &Path) -> bool {
+        true
+    }
+
+    fn process(
+        &self,
+        fs: &dyn SiteFs,
+        path: &Path,
+    ) -> Result<Vec<u8>, AssetProcessError>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScssProcessor;
+
+impl AssetProcessor for ScssProcessor {
+    fn name(&self) -> &str {
+        "scss"
+    }
+
+    fn input_extension(&self) -> &str {
+        "scss"
+    }
+
+    fn output_extension(&self) -> &str {
+        "css"
+    }
+
+    fn emits(&self, path: &Path) -> bool {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_none_or(|name| !name.starts_with('_'))
+    }
+
+    fn process(
+        &self,
+        fs: &dyn SiteFs,
+        path: &Path,
+    ) -> Result<Vec<u8>, AssetProcessError> {
+        let grass_fs = GrassFs(fs);
+        let options = grass::Options::default().fs(&grass_fs);
+        let css = grass::from_path(path, &options).map_err(|error| {
+            AssetProcessError::new(self.name(), error.to_string())
+        })?;
+        Ok(css.into_bytes())
+    }
+}
+
+// Lets imports resolve through the site's file system.
+#[derive(Debug)]
+struct GrassFs<'a>(&'a dyn SiteFs);
+
+impl grass::Fs for GrassFs<'_> {
+    fn is_dir(&self, path: &Path) -> bool {
+        self.0.is_dir(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.0.is_file(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.0.read(path)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct AssetProcessors {
+    processors: Vec<Arc<dyn AssetProcessor>>,
+}
+
+impl AssetProcessors {
+    pub fn register<P>(&mut self, processor: P)
+    where
+        P: AssetProcessor,
+    {
+        self.processors.push(Arc::new(processor));
+    }
+
+    pub fn find(&self, path: &Path) -> Option<&dyn AssetProcessor> {
+        let extension = path.extension()?;
+        self.processors
+            .iter()
+            .rev()
+            .find(|processor| processor.input_extension() == extension)
+            .map(AsRef::as_ref)
+    }
+}
+
+impl fmt::Debug for AssetProcessors {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_list()
+            .entries(self.processors.iter().map(|processor| processor.name()))
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AssetManifest {
+    entries: Arc<RwLock<BTreeMap<String, String>>>,
+}
+
+impl AssetManifest {
+    pub fn insert(&self, logical: String, fingerprinted: String) {
+        self.entries
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(logical, fingerprinted);
+    }
+
+    pub fn get(&self, logical: &str) -> Option<String> {
+        self.entries
+            .read()
+            .unwrap_or_else(|error| error.into_inner())
+            .get(logical)
+            .cloned()
+    }
+
+    pub fn clear(&self) {
+        self.entries.write().unwrap_or_else(|error| error.into_inner()).clear();
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let entries =
+            self.entries.read().unwrap_or_else(|error| error.into_inner());
+        serde_json::to_string_pretty(&*entries)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Fingerprinter {
+    state: u64,
+}
+
+impl Fingerprinter {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    pub fn new() -> Self {
+        Self { state: Self::OFFSET_BASIS }
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= u64::from(byte);
+            self.state = self.state.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    pub fn finish(&self) -> String {
+        format!("{:016x}", self.state)
+    }
+}
+
+impl Default for Fingerprinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn logical_path(suffix: &Path) -> Option<String> {
+    let mut logical = String::new();
+    for component in suffix.components() {
+        if !logical.is_empty() {
+            logical.push('/');
+        }
+        logical.push_str(component.as_os_str().to_str()?);
+    }
+    Some(logical)
+}
+
+pub fn find_source(
+    fs: &dyn SiteFs,
+    asset_dirs: &[PathBuf],
+    logical: &str,
+) -> Option<PathBuf> {
+    asset_dirs
+        .iter()
+        .map(|asset_dir| asset_dir.join(logical))
+        .find(|path| fs.is_file(path))
+}
+
+pub fn fingerprinted_name(file_name: &str, fingerprint: &str) -> String {
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => {
+            format!("{stem}.{fingerprint}.{extension}")
+        },
+        _ => format!("{file_name}.{fingerprint}"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssetArgs<'a> {
+    path: &'a str,
+}
+
+impl<'a> Args<'a> for AssetArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let path = args.retrive_arg("path")?;
+        Ok(Self { path })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AssetFn {
+    fingerprints: bool,
+    manifest: AssetManifest,
+}
+
+impl AssetFn {
+    pub fn new(fingerprints: bool, manifest: AssetManifest) -> Self {
+        Self { fingerprints, manifest }
+    }
+}
+
+impl Function for AssetFn {
+    type Args<'a> = AssetArgs<'a>;
+    type Output = String;
+    type Error = AssetError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let logical = args.path.trim_start_matches('/');
+        if !self.fingerprints {
+            return Ok(format!("{ASSET_URL_PREFIX}{logical}"));
+        }
+        let fingerprinted = self
+            .manifest
+            .get(logical)
+            .ok_or_else(|| AssetError::Unknown(logical.to_owned()))?;
+        Ok(format!("{ASSET_URL_PREFIX}{fingerprinted}"))
+    }
+
+    fn doc(&self) -> String {
+        "{# URL of an asset, fingerprinted if enabled #}
+        asset(
+            {# path relative to the asset directory #}
+            path:string
+        ) -> String "
+            .to_owned()
+    }
+}
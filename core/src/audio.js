@@ -0,0 +1,46 @@
+(function () {
+  "use strict";
+
+  if (window.linSsgAudio) return;
+  window.linSsgAudio = true;
+
+  function attach(figure) {
+    var audio = figure.querySelector("audio");
+    var cues = Array.prototype.slice.call(
+      figure.querySelectorAll(".audio-cue")
+    );
+    if (!audio || cues.length === 0) return;
+
+    function update() {
+      var time = audio.currentTime;
+      for (var i = 0; i < cues.length; i++) {
+        var cue = cues[i];
+        var start = parseFloat(cue.dataset.start);
+        var end = parseFloat(cue.dataset.end);
+        cue.classList.toggle("audio-cue-active", time >= start && time < end);
+      }
+    }
+
+    audio.addEventListener("timeupdate", update);
+    audio.addEventListener("seeked", update);
+    cues.forEach(function (cue) {
+      cue.addEventListener("click", function () {
+        audio.currentTime = parseFloat(cue.dataset.start);
+        audio.play();
+      });
+    });
+  }
+
+  function init() {
+    var figures = document.querySelectorAll("figure.audio");
+    for (var i = 0; i < figures.length; i++) {
+      attach(figures[i]);
+    }
+  }
+
+  if (document.readyState === "loading") {
+    document.addEventListener("DOMContentLoaded", init);
+  } else {
+    init();
+  }
+})();
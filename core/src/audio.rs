@@ -0,0 +1,272 @@
+use std::{
+    fmt::{self, Write as _},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    asset::{self, AssetManifest, ASSET_URL_PREFIX},
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+    site_fs::SiteFs,
+    Config,
+};
+
+pub const SCRIPT_FILE_NAME: &str = "audio.js";
+
+pub const SCRIPT: &str = include_str!("audio.js");
+
+pub const CUE_FIELD_SEPARATOR: char = '|';
+
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("oga", "audio/ogg"),
+    ("opus", "audio/ogg"),
+    ("wav", "audio/wav"),
+    ("flac", "audio/flac"),
+    ("m4a", "audio/mp4"),
+    ("aac", "audio/aac"),
+    ("webm", "audio/webm"),
+];
+
+#[derive(Debug, Error)]
+pub enum AudioError {
+    #[error("Recording {} does not exist", .0.display())]
+    Missing(PathBuf),
+    #[error("Recording {} is not known to the asset manifest", .0)]
+    Unknown(String),
+    #[error("Recording {} is not a supported audio format", .0.display())]
+    UnsupportedFormat(PathBuf),
+    #[error("A transcript and cues cannot be given together")]
+    TranscriptAndCues,
+    #[error(
+        "Cue {} must be a string \"start|end|text\" or an object with start, \
+         end and text, where start is not after end",
+        .0
+    )]
+    BadCue(usize),
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cue {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+impl Cue {
+    fn parse(number: usize, value: &Value) -> Result<Self, AudioError> {
+        let bad = || AudioError::BadCue(number);
+        let cue = match value {
+            Value::String(cue) => {
+                let mut fields = cue.splitn(3, CUE_FIELD_SEPARATOR);
+                let mut time = || {
+                    fields
+                        .next()
+                        .and_then(|field| field.trim().parse().ok())
+                        .ok_or_else(bad)
+                };
+                let start = time()?;
+                let end = time()?;
+                let text = fields.next().ok_or_else(bad)?.trim().to_owned();
+                Self { start, end, text }
+            },
+            Value::Object(fields) => {
+                let time = |name| {
+                    fields.get(name).and_then(Value::as_f64).ok_or_else(bad)
+                };
+                Self {
+                    start: time("start")?,
+                    end: time("end")?,
+                    text: fields
+                        .get("text")
+                        .and_then(Value::as_str)
+                        .ok_or_else(bad)?
+                        .to_owned(),
+                }
+            },
+            _ => Err(bad())?,
+        };
+        if !(0.0 ..= cue.end).contains(&cue.start) {
+            Err(bad())?;
+        }
+        Ok(cue)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioArgs<'a> {
+    path: &'a str,
+    transcript: Option<&'a str>,
+    cues: Option<&'a [Value]>,
+    lang: Option<&'a str>,
+}
+
+impl<'a> Args<'a> for AudioArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let path = args.retrive_arg("path")?;
+        let transcript =
+            args.retrive_arg_with_default("transcript", || None)?;
+        let cues = args.retrive_arg_with_default("cues", || None)?;
+        let lang = args.retrive_arg_with_default("lang", || None)?;
+        Ok(Self { path, transcript, cues, lang })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioFn {
+    fs: Arc<dyn SiteFs>,
+    asset_dirs: Vec<PathBuf>,
+    fingerprints: bool,
+    manifest: AssetManifest,
+    aligned: Arc<AtomicBool>,
+}
+
+impl AudioFn {
+    pub fn new(config: &Config, manifest: AssetManifest) -> Self {
+        Self {
+            fs: config.shared_fs(),
+            asset_dirs: config.asset_dirs(),
+            fingerprints: config.asset_fingerprints(),
+            manifest,
+            aligned: Arc::default(),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.aligned.store(false, Ordering::Relaxed);
+    }
+
+    pub fn needs_script(&self) -> bool {
+        self.aligned.load(Ordering::Relaxed)
+    }
+
+    fn mime_type(path: &Path) -> Option<&'static str> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        MIME_TYPES
+            .iter()
+            .find(|(known, _)| *known == extension)
+            .map(|(_, mime_type)| *mime_type)
+    }
+}
+
+impl Function for AudioFn {
+    type Args<'a> = AudioArgs<'a>;
+    type Output = String;
+    type Error = AudioError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let logical = args.path.trim_start_matches('/');
+        let source_path =
+            asset::find_source(&*self.fs, &self.asset_dirs, logical)
+                .ok_or_else(|| {
+                    AudioError::Missing(self.asset_dirs[0].join(logical))
+                })?;
+        let mime_type = Self::mime_type(&source_path)
+            .ok_or(AudioError::UnsupportedFormat(source_path))?;
+        let src = if self.fingerprints {
+            let fingerprinted = self
+                .manifest
+                .get(logical)
+                .ok_or_else(|| AudioError::Unknown(logical.to_owned()))?;
+            format!("{ASSET_URL_PREFIX}{fingerprinted}")
+        } else {
+            format!("{ASSET_URL_PREFIX}{logical}")
+        };
+        if args.transcript.is_some() && args.cues.is_some() {
+            Err(AudioError::TranscriptAndCues)?;
+        }
+        let cues = args
+            .cues
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(i, cue)| Cue::parse(i + 1, cue))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let src = tera::escape_html(&src);
+        let mut buf = String::new();
+        write!(
+            buf,
+            "<figure class=\"audio\"><audio controls \
+             preload=\"metadata\"><source src=\"{src}\" \
+             type=\"{mime_type}\"/><a href=\"{src}\">{}</a></audio>",
+            tera::escape_html(logical),
+        )?;
+        let lang = args.lang.map_or(String::new(), |lang| {
+            format!(" lang=\"{}\"", tera::escape_html(lang))
+        });
+        if let Some(transcript) = args.transcript {
+            write!(
+                buf,
+                "<figcaption class=\"audio-transcript\"{lang}>{}</figcaption>",
+                tera::escape_html(transcript)
+            )?;
+        } else if !cues.is_empty() {
+            write!(buf, "<figcaption class=\"audio-transcript\"{lang}>")?;
+            for (i, cue) in cues.iter().enumerate() {
+                if i > 0 {
+                    buf.push(' ');
+                }
+                write!(
+                    buf,
+                    "<span class=\"audio-cue\" data-start=\"{}\" \
+                     data-end=\"{}\">{}</span>",
+                    cue.start,
+                    cue.end,
+                    tera::escape_html(&cue.text)
+                )?;
+            }
+            write!(
+                buf,
+                "</figcaption><script src=\"/{SCRIPT_FILE_NAME}\" \
+                 defer></script>"
+            )?;
+            self.aligned.store(true, Ordering::Relaxed);
+        }
+        write!(buf, "</figure>")?;
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        "{# <audio> player for a recording, checked to exist at build time, \
+         optionally with its transcript #}
+        audio(
+            {# path relative to the asset directory #}
+            path:string,
+            {# plain transcript shown below the player #}
+            transcript:string?,
+            {# time-aligned transcript, highlighted during playback and
+               seekable by clicking, each cue either:
+                - a string \"start|end|text\", times in seconds,
+                  e.g. \"0.5|1.2|ka\"
+                - an object with start, end and text
+            #}
+            cues:array?,
+            {# language code of the transcript #}
+            lang:string?
+        ) -> String "
+            .to_owned()
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
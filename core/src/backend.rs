@@ -0,0 +1,245 @@
+use std::{
+    convert::Infallible,
+    error::Error,
+    fmt,
+    ops::Range,
+    path::Path,
+    sync::Arc,
+};
+
+use crate::markdown::{
+    page::{self, CompileError, Page},
+    to_html::ToHtmlOptions,
+    to_latex::html_to_text,
+};
+
+pub trait ContentBackend: Send + Sync + 'static {
+    type Document;
+    type Error: Error + Send + Sync + 'static;
+
+    fn name(&self) -> &str;
+
+    fn is_source(&self, path: &Path) -> bool;
+
+    fn parse(
+        &self,
+        source: &str,
+        options: &ToHtmlOptions,
+    ) -> Result<Self::Document, Self::Error>;
+
+    fn render(
+        &self,
+        document: &Self::Document,
+        buf: &mut String,
+        ctx: &mut RenderCtx,
+    ) -> Result<(), Self::Error>;
+
+    fn word_count(&self, _document: &Self::Document) -> usize {
+        0
+    }
+
+    // Spans are relative to the source given to `parse`.
+    fn error_span(&self, _error: &Self::Error) -> Option<Range<usize>> {
+        None
+    }
+}
+
+// Spans are relative to the source given to `parse`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentWarning {
+    message: String,
+    span: Option<Range<usize>>,
+}
+
+impl ContentWarning {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into(), span: None }
+    }
+
+    pub fn with_span(mut self, span: Option<Range<usize>>) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RenderCtx {
+    options: ToHtmlOptions,
+    summary_pending: bool,
+    wiki_links: Vec<String>,
+    unsupported: Vec<String>,
+    warnings: Vec<ContentWarning>,
+}
+
+impl RenderCtx {
+    pub fn new(options: ToHtmlOptions) -> Self {
+        Self { options, ..Self::default() }
+    }
+
+    pub fn options(&self) -> &ToHtmlOptions {
+        &self.options
+    }
+
+    pub fn mark_summary(&mut self) {
+        self.summary_pending = true;
+    }
+
+    pub fn marks_summary(&self) -> bool {
+        self.summary_pending
+    }
+
+    pub fn wiki_links(&self) -> &[String] {
+        &self.wiki_links
+    }
+
+    pub fn add_wiki_link(&mut self, target: String) {
+        self.wiki_links.push(target);
+    }
+
+    pub fn unsupported(&self) -> &[String] {
+        &self.unsupported
+    }
+
+    pub fn add_unsupported(&mut self, node: String) {
+        self.unsupported.push(node);
+    }
+
+    pub fn warnings(&self) -> &[ContentWarning] {
+        &self.warnings
+    }
+
+    pub fn add_warning(&mut self, warning: ContentWarning) {
+        self.warnings.push(warning);
+    }
+}
+
+// The body is already HTML, possibly with Tera in it, and is handed to Tera
+// as it is written.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlBackend {
+    extensions: Vec<String>,
+}
+
+impl HtmlBackend {
+    pub fn new(extensions: &[String]) -> Self {
+        Self { extensions: extensions.to_vec() }
+    }
+}
+
+impl ContentBackend for HtmlBackend {
+    type Document = String;
+    type Error = Infallible;
+
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn is_source(&self, path: &Path) -> bool {
+        has_extension(path, &self.extensions)
+    }
+
+    fn parse(
+        &self,
+        source: &str,
+        _options: &ToHtmlOptions,
+    ) -> Result<Self::Document, Self::Error> {
+        Ok(source.to_owned())
+    }
+
+    fn render(
+        &self,
+        document: &Self::Document,
+        buf: &mut String,
+        _ctx: &mut RenderCtx,
+    ) -> Result<(), Self::Error> {
+        buf.push_str(document);
+        Ok(())
+    }
+
+    fn word_count(&self, document: &Self::Document) -> usize {
+        html_to_text(document).split_whitespace().count()
+    }
+}
+
+pub(crate) fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    path.extension().is_some_and(|extension| {
+        extensions.iter().any(|known| extension == &known[..])
+    })
+}
+
+// Object-safe view of a backend, so backends with different document types
+// share a registry.
+pub trait PageCompiler: Send + Sync + 'static {
+    fn name(&self) -> &str;
+
+    fn is_source(&self, path: &Path) -> bool;
+
+    fn compile(
+        &self,
+        code: &str,
+        defaults: &toml::Table,
+        options: &ToHtmlOptions,
+    ) -> Result<Page, CompileError>;
+}
+
+impl<B> PageCompiler for B
+where
+    B: ContentBackend,
+{
+    fn name(&self) -> &str {
+        ContentBackend::name(self)
+    }
+
+    fn is_source(&self, path: &Path) -> bool {
+        ContentBackend::is_source(self, path)
+    }
+
+    fn compile(
+        &self,
+        code: &str,
+        defaults: &toml::Table,
+        options: &ToHtmlOptions,
+    ) -> Result<Page, CompileError> {
+        page::compile(code, defaults, self, options)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ContentBackends {
+    backends: Vec<Arc<dyn PageCompiler>>,
+}
+
+impl ContentBackends {
+    pub fn register<B>(&mut self, backend: B)
+    where
+        B: ContentBackend,
+    {
+        self.backends.push(Arc::new(backend));
+    }
+
+    // Later registrations take precedence, so a backend can claim the
+    // extensions of the built-in Markdown one.
+    pub fn find(&self, path: &Path) -> Option<Arc<dyn PageCompiler>> {
+        self.backends
+            .iter()
+            .rev()
+            .find(|backend| backend.is_source(path))
+            .cloned()
+    }
+}
+
+impl fmt::Debug for ContentBackends {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_list()
+            .entries(self.backends.iter().map(|backend| backend.name()))
+            .finish()
+    }
+}
@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+pub const CONTEXT_KEY: &str = "breadcrumbs";
+
+// Levels without a page of their own keep their URL segment as the title and
+// have no URL to link to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Breadcrumb {
+    pub title: String,
+    pub url: Option<String>,
+}
+
+// From the site root down to the page at `url`, which is the last crumb.
+// `titles` maps the URL of every page to its title.
+pub fn trail(url: &str, titles: &HashMap<String, String>) -> Vec<Breadcrumb> {
+    let crumb = |prefix: &str, segment: &str| Breadcrumb {
+        title: titles.get(prefix).map_or(segment, |title| title).to_owned(),
+        url: titles.contains_key(prefix).then(|| prefix.to_owned()),
+    };
+    let mut trail = Vec::new();
+    if titles.contains_key("/") {
+        trail.push(crumb("/", "/"));
+    }
+    let segments: Vec<_> =
+        url.split('/').filter(|segment| !segment.is_empty()).collect();
+    let mut prefix = String::from("/");
+    for (i, segment) in segments.iter().enumerate() {
+        prefix.push_str(segment);
+        if i + 1 < segments.len() || url.ends_with('/') {
+            prefix.push('/');
+        }
+        trail.push(crumb(&prefix, segment));
+    }
+    trail
+}
@@ -0,0 +1,144 @@
+use std::{collections::BTreeMap, path::Path};
+
+use thiserror::Error;
+
+use crate::{
+    asset::{AssetManifest, ASSET_URL_PREFIX},
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+    Config,
+};
+
+pub const BUNDLE_DIR: &str = "bundles";
+
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("Bundle {} is not declared in the config", .0)]
+    Unknown(String),
+    #[error("Bundle {} is neither a .css nor a .js file", .0)]
+    UnsupportedKind(String),
+    #[error("File {} of bundle {} found in no asset directory", .1, .0)]
+    MissingFile(String, String),
+    #[error("Bundle {} is not valid UTF-8", .0)]
+    Utf8(String),
+    #[error("Could not minify bundle {}: {}", .0, .1)]
+    Minify(String, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BundleKind {
+    Css,
+    Js,
+}
+
+impl BundleKind {
+    pub fn of(name: &str) -> Option<Self> {
+        match Path::new(name).extension()?.to_str()? {
+            "css" => Some(Self::Css),
+            "js" => Some(Self::Js),
+            _ => None,
+        }
+    }
+
+    pub fn separator(self) -> &'static [u8] {
+        match self {
+            Self::Css => b"\n",
+            // Guards against files that rely on automatic semicolon
+            // insertion at their end.
+            Self::Js => b";\n",
+        }
+    }
+}
+
+pub fn logical_path(name: &str) -> String {
+    format!("{BUNDLE_DIR}/{name}")
+}
+
+pub fn minify_css(name: &str, css: Vec<u8>) -> Result<Vec<u8>, BundleError> {
+    let css = String::from_utf8(css)
+        .map_err(|_| BundleError::Utf8(name.to_owned()))?;
+    let options =
+        grass::Options::default().style(grass::OutputStyle::Compressed);
+    let minified = grass::from_string(css, &options).map_err(|error| {
+        BundleError::Minify(name.to_owned(), error.to_string())
+    })?;
+    Ok(minified.into_bytes())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BundleArgs<'a> {
+    name: &'a str,
+}
+
+impl<'a> Args<'a> for BundleArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let name = args.retrive_arg("name")?;
+        Ok(Self { name })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BundleFn {
+    kinds: BTreeMap<String, Option<BundleKind>>,
+    fingerprints: bool,
+    manifest: AssetManifest,
+}
+
+impl BundleFn {
+    pub fn new(config: &Config, manifest: AssetManifest) -> Self {
+        let kinds = config
+            .bundles()
+            .iter()
+            .map(|(name, _)| (name.clone(), BundleKind::of(name)))
+            .collect();
+        Self { kinds, fingerprints: config.asset_fingerprints(), manifest }
+    }
+}
+
+impl Function for BundleFn {
+    type Args<'a> = BundleArgs<'a>;
+    type Output = String;
+    type Error = BundleError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let kind = self
+            .kinds
+            .get(args.name)
+            .ok_or_else(|| BundleError::Unknown(args.name.to_owned()))?
+            .ok_or_else(|| {
+                BundleError::UnsupportedKind(args.name.to_owned())
+            })?;
+        let logical = logical_path(args.name);
+        let url = match self.manifest.get(&logical) {
+            Some(fingerprinted) if self.fingerprints => {
+                format!("{ASSET_URL_PREFIX}{fingerprinted}")
+            },
+            _ => format!("{ASSET_URL_PREFIX}{logical}"),
+        };
+        let url = tera::escape_html(&url);
+        Ok(match kind {
+            BundleKind::Css => {
+                format!("<link rel=\"stylesheet\" href=\"{url}\">")
+            },
+            BundleKind::Js => format!("<script src=\"{url}\" defer></script>"),
+        })
+    }
+
+    fn doc(&self) -> String {
+        "{# tag loading a bundle of assets declared in the config: a \
+         stylesheet link for .css bundles, a deferred script for .js ones #}
+        bundle(
+            {# name of the bundle, e.g. \"site.css\" #}
+            name:string
+        ) -> String "
+            .to_owned()
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
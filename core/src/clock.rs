@@ -0,0 +1,74 @@
+use std::{convert::Infallible, env};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::Value;
+
+use crate::{
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+};
+
+pub const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+pub fn source_date_epoch() -> DateTime<Utc> {
+    env::var(SOURCE_DATE_EPOCH)
+        .ok()
+        .and_then(|epoch| epoch.trim().parse().ok())
+        .and_then(|epoch| DateTime::from_timestamp(epoch, 0))
+        .unwrap_or(DateTime::UNIX_EPOCH)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NowArgs {
+    timestamp: bool,
+}
+
+impl<'a> Args<'a> for NowArgs {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let timestamp = args.retrive_arg_with_default("timestamp", || false)?;
+        // Reproducible builds are always in UTC.
+        let _utc: bool = args.retrive_arg_with_default("utc", || false)?;
+        Ok(Self { timestamp })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FixedNowFn {
+    time: DateTime<Utc>,
+}
+
+impl FixedNowFn {
+    pub fn new(time: DateTime<Utc>) -> Self {
+        Self { time }
+    }
+}
+
+impl Function for FixedNowFn {
+    type Args<'a> = NowArgs;
+    type Output = Value;
+    type Error = Infallible;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        Ok(if args.timestamp {
+            Value::from(self.time.timestamp())
+        } else {
+            Value::from(self.time.to_rfc3339_opts(SecondsFormat::Secs, true))
+        })
+    }
+
+    fn doc(&self) -> String {
+        "{# build time, fixed to SOURCE_DATE_EPOCH (or the Unix epoch) in \
+         reproducible builds #}
+        now(
+            {# unix timestamp instead of an RFC 3339 date #}
+            timestamp:bool?,
+            {# accepted for compatibility, always UTC #}
+            utc:bool?
+        ) -> String | Number "
+            .to_owned()
+    }
+}
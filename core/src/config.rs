@@ -1,30 +1,245 @@
-use std::path::{Path, PathBuf};
+use std::{
+    io,
+    path::{self, Path, PathBuf},
+    sync::Arc,
+};
 
-use crate::{ssg::LinSsg, InitError};
+use thiserror::Error;
+
+use crate::{
+    external,
+    site_fs::{self, RealFs, SiteFs},
+    ssg::LinSsg,
+    theme,
+    HtmlClassConfig,
+    InitError,
+    SectionWrapper,
+    UnsupportedPolicy,
+};
+
+pub const TEMPLATE_GLOB: &str = "**/*";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("{} directory {} does not exist", .0, .1.display())]
+    MissingDir(&'static str, PathBuf),
+    #[error("{} file {} does not exist", .0, .1.display())]
+    MissingFile(&'static str, PathBuf),
+    #[error("Template glob {} matches no templates", .0.display())]
+    NoTemplates(PathBuf),
+    #[error("Failed to parse templates")]
+    Template(#[source] tera::Error),
+    #[error(
+        "Output directory {} is inside the {} directory {}",
+        .0.display(),
+        .1,
+        .2.display()
+    )]
+    OutputInside(PathBuf, &'static str, PathBuf),
+    #[error("Could not resolve path {}", .0.display())]
+    Resolve(PathBuf, #[source] io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OutputFormat {
+    name: String,
+    layout: String,
+    extension: String,
+}
+
+impl OutputFormat {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn layout(&self) -> &str {
+        &self.layout
+    }
+
+    pub fn extension(&self) -> &str {
+        &self.extension
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Book {
+    name: String,
+    title: String,
+    pages: Vec<PathBuf>,
+}
+
+impl Book {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn pages(&self) -> &[PathBuf] {
+        &self.pages
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NonPagePolicy {
+    #[default]
+    Ignore,
+    Copy,
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
-    template_dir: String,
+    site_config_path: PathBuf,
+    template_dirs: Vec<(PathBuf, String)>,
     page_dir: PathBuf,
+    page_extensions: Vec<String>,
+    html_page_extensions: Vec<String>,
+    on_non_page: NonPagePolicy,
+    follow_external_symlinks: bool,
+    fs: Arc<dyn SiteFs>,
+    ignore_file: PathBuf,
+    ignore_patterns: Vec<String>,
     asset_dir: PathBuf,
+    theme_dir: Option<PathBuf>,
     output_dir: PathBuf,
+    shortcode_dir: PathBuf,
+    partial_dir: PathBuf,
+    lexicon_path: PathBuf,
+    data_dir: PathBuf,
+    dictionary_dir: PathBuf,
+    dictionary_template: Option<String>,
+    dictionary_index_template: Option<String>,
+    reference_dir: PathBuf,
+    reference_template: Option<String>,
+    linguinput_table: Option<PathBuf>,
+    named_linguinput_tables: Vec<(String, PathBuf)>,
+    asset_fingerprints: bool,
+    bundles: Vec<(String, Vec<String>)>,
+    minify_bundles: bool,
+    minify_html: bool,
+    image_widths: Vec<u32>,
+    languages: Vec<String>,
+    translation_dir: PathBuf,
+    extra_outputs: Vec<(String, PathBuf)>,
+    output_formats: Vec<OutputFormat>,
+    latex_font: Option<String>,
+    books: Vec<Book>,
+    epub_layout: String,
+    epub_fonts: Vec<PathBuf>,
+    search_index: bool,
+    glossary_path: PathBuf,
+    glossary_dir: PathBuf,
+    glossary_template: Option<String>,
+    glossary_autolink: bool,
+    base_url: Option<String>,
+    social_meta: bool,
+    reproducible: bool,
+    preserved_outputs: Vec<PathBuf>,
+    on_unsupported: UnsupportedPolicy,
+    heading_offset: u8,
+    section_wrapper: SectionWrapper,
+    skip_link: bool,
+    html_classes: HtmlClassConfig,
+    smart_typography: bool,
+    external_links: bool,
+    external_link_class: String,
+    external_link_report: Option<PathBuf>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            template_dir: String::from("templates/**/*"),
+            site_config_path: PathBuf::from("lin-ssg.toml"),
+            template_dirs: vec![(
+                PathBuf::from("templates"),
+                String::from(TEMPLATE_GLOB),
+            )],
             page_dir: PathBuf::from("pages"),
+            page_extensions: vec![String::from("md")],
+            html_page_extensions: vec![
+                String::from("html"),
+                String::from("tera"),
+            ],
+            on_non_page: NonPagePolicy::Ignore,
+            follow_external_symlinks: true,
+            fs: Arc::new(RealFs),
+            ignore_file: PathBuf::from(".linssgignore"),
+            ignore_patterns: Vec::new(),
             asset_dir: PathBuf::from("assets"),
+            theme_dir: None,
             output_dir: PathBuf::from("public"),
+            shortcode_dir: PathBuf::from("shortcodes"),
+            partial_dir: PathBuf::from("partials"),
+            lexicon_path: PathBuf::from("data/lexicon.csv"),
+            data_dir: PathBuf::from("data"),
+            dictionary_dir: PathBuf::from("dictionary"),
+            dictionary_template: None,
+            dictionary_index_template: None,
+            reference_dir: PathBuf::from("reference"),
+            reference_template: None,
+            linguinput_table: None,
+            named_linguinput_tables: Vec::new(),
+            asset_fingerprints: false,
+            bundles: Vec::new(),
+            minify_bundles: false,
+            minify_html: false,
+            image_widths: Vec::new(),
+            languages: Vec::new(),
+            translation_dir: PathBuf::from("translations"),
+            extra_outputs: Vec::new(),
+            output_formats: Vec::new(),
+            latex_font: None,
+            books: Vec::new(),
+            epub_layout: String::from(theme::EPUB_LAYOUT.0),
+            epub_fonts: Vec::new(),
+            search_index: false,
+            glossary_path: PathBuf::from("glossary.toml"),
+            glossary_dir: PathBuf::from("glossary"),
+            glossary_template: None,
+            glossary_autolink: false,
+            base_url: None,
+            social_meta: false,
+            reproducible: false,
+            preserved_outputs: vec![
+                PathBuf::from(".git"),
+                PathBuf::from("CNAME"),
+            ],
+            on_unsupported: UnsupportedPolicy::Strict,
+            heading_offset: 0,
+            section_wrapper: SectionWrapper::Div,
+            skip_link: false,
+            html_classes: HtmlClassConfig::default(),
+            smart_typography: false,
+            external_links: false,
+            external_link_class: String::from(external::LINK_CLASS),
+            external_link_report: None,
         }
     }
 }
 
 impl Config {
-    pub fn with_templates(mut self, template_dir: impl Into<String>) -> Self {
-        self.template_dir = template_dir.into();
-        self.template_dir.push_str("/**/*");
+    pub fn with_site_config(
+        mut self,
+        site_config_path: impl Into<PathBuf>,
+    ) -> Self {
+        self.site_config_path = site_config_path.into();
+        self
+    }
+
+    pub fn with_templates(mut self, template_dir: impl Into<PathBuf>) -> Self {
+        self.template_dirs =
+            vec![(template_dir.into(), String::from(TEMPLATE_GLOB))];
+        self
+    }
+
+    pub fn with_template_glob(
+        mut self,
+        template_dir: impl Into<PathBuf>,
+        glob: impl Into<String>,
+    ) -> Self {
+        self.template_dirs.push((template_dir.into(), glob.into()));
         self
     }
 
@@ -33,37 +248,801 @@ impl Config {
         self
     }
 
+    pub fn with_page_extensions<I>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.page_extensions = extension_list(extensions);
+        self
+    }
+
+    // Pages with these extensions skip Markdown and go straight to Tera.
+    pub fn with_html_page_extensions<I>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.html_page_extensions = extension_list(extensions);
+        self
+    }
+
+    pub fn with_on_non_page(mut self, policy: NonPagePolicy) -> Self {
+        self.on_non_page = policy;
+        self
+    }
+
+    pub fn with_follow_external_symlinks(mut self, enabled: bool) -> Self {
+        self.follow_external_symlinks = enabled;
+        self
+    }
+
+    // Every site file, from pages and templates to data, assets and the
+    // output, is read and written through this file system.
+    pub fn with_fs<F>(mut self, fs: F) -> Self
+    where
+        F: SiteFs,
+    {
+        self.fs = Arc::new(fs);
+        self
+    }
+
+    pub fn with_ignore_file(mut self, ignore_file: impl Into<PathBuf>) -> Self {
+        self.ignore_file = ignore_file.into();
+        self
+    }
+
+    pub fn with_ignore<I>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.ignore_patterns.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
     pub fn with_assets(mut self, asset_dir: impl Into<PathBuf>) -> Self {
         self.asset_dir = asset_dir.into();
         self
     }
 
+    pub fn with_theme(mut self, theme_dir: impl Into<PathBuf>) -> Self {
+        self.theme_dir = Some(theme_dir.into());
+        self
+    }
+
     pub fn with_output(mut self, output_dir: impl Into<PathBuf>) -> Self {
         self.output_dir = output_dir.into();
         self
     }
 
+    pub fn with_shortcodes(
+        mut self,
+        shortcode_dir: impl Into<PathBuf>,
+    ) -> Self {
+        self.shortcode_dir = shortcode_dir.into();
+        self
+    }
+
+    pub fn with_partials(mut self, partial_dir: impl Into<PathBuf>) -> Self {
+        self.partial_dir = partial_dir.into();
+        self
+    }
+
+    pub fn with_lexicon(mut self, lexicon_path: impl Into<PathBuf>) -> Self {
+        self.lexicon_path = lexicon_path.into();
+        self
+    }
+
+    pub fn with_data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = data_dir.into();
+        self
+    }
+
+    pub fn with_dictionary_dir(
+        mut self,
+        dictionary_dir: impl Into<PathBuf>,
+    ) -> Self {
+        self.dictionary_dir = dictionary_dir.into();
+        self
+    }
+
+    pub fn with_dictionary_template(
+        mut self,
+        template: impl Into<String>,
+    ) -> Self {
+        self.dictionary_template = Some(template.into());
+        self
+    }
+
+    pub fn with_dictionary_index_template(
+        mut self,
+        template: impl Into<String>,
+    ) -> Self {
+        self.dictionary_index_template = Some(template.into());
+        self
+    }
+
+    pub fn with_reference_dir(
+        mut self,
+        reference_dir: impl Into<PathBuf>,
+    ) -> Self {
+        self.reference_dir = reference_dir.into();
+        self
+    }
+
+    pub fn with_reference_template(
+        mut self,
+        template: impl Into<String>,
+    ) -> Self {
+        self.reference_template = Some(template.into());
+        self
+    }
+
+    pub fn with_linguinput_table(
+        mut self,
+        table_path: impl Into<PathBuf>,
+    ) -> Self {
+        self.linguinput_table = Some(table_path.into());
+        self
+    }
+
+    pub fn with_named_linguinput_table(
+        mut self,
+        name: impl Into<String>,
+        table_path: impl Into<PathBuf>,
+    ) -> Self {
+        self.named_linguinput_tables.push((name.into(), table_path.into()));
+        self
+    }
+
+    pub fn with_asset_fingerprints(mut self, enabled: bool) -> Self {
+        self.asset_fingerprints = enabled;
+        self
+    }
+
+    pub fn with_bundle<I>(mut self, name: impl Into<String>, files: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.bundles
+            .push((name.into(), files.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    pub fn with_minify_bundles(mut self, enabled: bool) -> Self {
+        self.minify_bundles = enabled;
+        self
+    }
+
+    pub fn with_minify_html(mut self, enabled: bool) -> Self {
+        self.minify_html = enabled;
+        self
+    }
+
+    pub fn with_image_widths(
+        mut self,
+        widths: impl IntoIterator<Item = u32>,
+    ) -> Self {
+        self.image_widths = widths.into_iter().collect();
+        self
+    }
+
+    pub fn with_languages<I>(mut self, languages: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.languages = languages.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_translations(
+        mut self,
+        translation_dir: impl Into<PathBuf>,
+    ) -> Self {
+        self.translation_dir = translation_dir.into();
+        self
+    }
+
+    pub fn with_extra_output(
+        mut self,
+        template: impl Into<String>,
+        output: impl Into<PathBuf>,
+    ) -> Self {
+        self.extra_outputs.push((template.into(), output.into()));
+        self
+    }
+
+    pub fn with_extra_outputs<I, T, P>(mut self, outputs: I) -> Self
+    where
+        I: IntoIterator<Item = (T, P)>,
+        T: Into<String>,
+        P: Into<PathBuf>,
+    {
+        self.extra_outputs.extend(
+            outputs
+                .into_iter()
+                .map(|(template, output)| (template.into(), output.into())),
+        );
+        self
+    }
+
+    pub fn with_output_format(
+        mut self,
+        name: impl Into<String>,
+        layout: impl Into<String>,
+        extension: impl Into<String>,
+    ) -> Self {
+        self.output_formats.push(OutputFormat {
+            name: name.into(),
+            layout: layout.into(),
+            extension: extension.into(),
+        });
+        self
+    }
+
+    pub fn with_latex_font(mut self, font: impl Into<String>) -> Self {
+        self.latex_font = Some(font.into());
+        self
+    }
+
+    pub fn with_book<I>(
+        mut self,
+        name: impl Into<String>,
+        title: impl Into<String>,
+        pages: I,
+    ) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<PathBuf>,
+    {
+        self.books.push(Book {
+            name: name.into(),
+            title: title.into(),
+            pages: pages.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
+    pub fn with_epub_layout(mut self, layout: impl Into<String>) -> Self {
+        self.epub_layout = layout.into();
+        self
+    }
+
+    pub fn with_epub_font(mut self, path: impl Into<PathBuf>) -> Self {
+        self.epub_fonts.push(path.into());
+        self
+    }
+
+    pub fn with_search_index(mut self, enabled: bool) -> Self {
+        self.search_index = enabled;
+        self
+    }
+
+    pub fn with_glossary(mut self, glossary_path: impl Into<PathBuf>) -> Self {
+        self.glossary_path = glossary_path.into();
+        self
+    }
+
+    pub fn with_glossary_dir(
+        mut self,
+        glossary_dir: impl Into<PathBuf>,
+    ) -> Self {
+        self.glossary_dir = glossary_dir.into();
+        self
+    }
+
+    pub fn with_glossary_template(
+        mut self,
+        template: impl Into<String>,
+    ) -> Self {
+        self.glossary_template = Some(template.into());
+        self
+    }
+
+    pub fn with_glossary_autolink(mut self, enabled: bool) -> Self {
+        self.glossary_autolink = enabled;
+        self
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    pub fn with_social_meta(mut self, enabled: bool) -> Self {
+        self.social_meta = enabled;
+        self
+    }
+
+    pub fn with_reproducible(mut self, enabled: bool) -> Self {
+        self.reproducible = enabled;
+        self
+    }
+
+    pub fn with_preserved_outputs<I>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<PathBuf>,
+    {
+        self.preserved_outputs = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_on_unsupported(mut self, policy: UnsupportedPolicy) -> Self {
+        self.on_unsupported = policy;
+        self
+    }
+
+    pub fn with_heading_offset(mut self, heading_offset: u8) -> Self {
+        self.heading_offset = heading_offset;
+        self
+    }
+
+    pub fn with_section_wrapper(mut self, wrapper: SectionWrapper) -> Self {
+        self.section_wrapper = wrapper;
+        self
+    }
+
+    pub fn with_skip_link(mut self, skip_link: bool) -> Self {
+        self.skip_link = skip_link;
+        self
+    }
+
+    pub fn with_html_classes(mut self, html_classes: HtmlClassConfig) -> Self {
+        self.html_classes = html_classes;
+        self
+    }
+
+    pub fn with_smart_typography(mut self, enabled: bool) -> Self {
+        self.smart_typography = enabled;
+        self
+    }
+
+    pub fn with_external_links(mut self, enabled: bool) -> Self {
+        self.external_links = enabled;
+        self
+    }
+
+    pub fn with_external_link_class(
+        mut self,
+        class: impl Into<String>,
+    ) -> Self {
+        self.external_link_class = class.into();
+        self
+    }
+
+    pub fn with_external_link_report(
+        mut self,
+        report_path: impl Into<PathBuf>,
+    ) -> Self {
+        self.external_link_report = Some(report_path.into());
+        self
+    }
+
+    pub fn site_config_path(&self) -> &Path {
+        &self.site_config_path
+    }
+
     pub fn template_dir(&self) -> &Path {
-        Path::new(&self.template_dir[.. "/**/*".len()])
+        &self.template_dirs[0].0
     }
 
-    pub(crate) fn template_dir_with_globs(&self) -> &Path {
-        Path::new(&self.template_dir)
+    pub fn template_dirs(&self) -> &[(PathBuf, String)] {
+        &self.template_dirs
     }
 
     pub fn page_dir(&self) -> &Path {
         &self.page_dir
     }
 
+    pub fn page_extensions(&self) -> &[String] {
+        &self.page_extensions
+    }
+
+    pub fn html_page_extensions(&self) -> &[String] {
+        &self.html_page_extensions
+    }
+
+    pub(crate) fn is_page(&self, path: &Path) -> bool {
+        path.extension().and_then(|extension| extension.to_str()).is_some_and(
+            |extension| {
+                self.page_extensions.iter().any(|known| known == extension)
+            },
+        )
+    }
+
+    pub fn on_non_page(&self) -> NonPagePolicy {
+        self.on_non_page
+    }
+
+    pub fn follow_external_symlinks(&self) -> bool {
+        self.follow_external_symlinks
+    }
+
+    pub fn fs(&self) -> &dyn SiteFs {
+        &*self.fs
+    }
+
+    pub fn shared_fs(&self) -> Arc<dyn SiteFs> {
+        self.fs.clone()
+    }
+
+    pub fn ignore_file(&self) -> &Path {
+        &self.ignore_file
+    }
+
+    pub fn ignore_patterns(&self) -> &[String] {
+        &self.ignore_patterns
+    }
+
     pub fn asset_dir(&self) -> &Path {
         &self.asset_dir
     }
 
+    pub fn theme_dir(&self) -> Option<&Path> {
+        self.theme_dir.as_deref()
+    }
+
+    pub fn asset_dirs(&self) -> Vec<PathBuf> {
+        let mut asset_dirs = vec![self.asset_dir.clone()];
+        if let Some(theme_dir) = &self.theme_dir {
+            asset_dirs.push(theme_dir.join(theme::ASSET_DIR));
+        }
+        asset_dirs
+    }
+
     pub fn output_dir(&self) -> &Path {
         &self.output_dir
     }
 
+    pub fn shortcode_dir(&self) -> &Path {
+        &self.shortcode_dir
+    }
+
+    pub fn partial_dir(&self) -> &Path {
+        &self.partial_dir
+    }
+
+    pub fn lexicon_path(&self) -> &Path {
+        &self.lexicon_path
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    pub fn dictionary_dir(&self) -> &Path {
+        &self.dictionary_dir
+    }
+
+    pub fn dictionary_template(&self) -> Option<&str> {
+        self.dictionary_template.as_deref()
+    }
+
+    pub fn dictionary_index_template(&self) -> Option<&str> {
+        self.dictionary_index_template.as_deref()
+    }
+
+    pub fn reference_dir(&self) -> &Path {
+        &self.reference_dir
+    }
+
+    pub fn reference_template(&self) -> Option<&str> {
+        self.reference_template.as_deref()
+    }
+
+    pub fn linguinput_table(&self) -> Option<&Path> {
+        self.linguinput_table.as_deref()
+    }
+
+    pub fn named_linguinput_tables(&self) -> &[(String, PathBuf)] {
+        &self.named_linguinput_tables
+    }
+
+    pub fn asset_fingerprints(&self) -> bool {
+        self.asset_fingerprints
+    }
+
+    pub fn bundles(&self) -> &[(String, Vec<String>)] {
+        &self.bundles
+    }
+
+    pub fn minify_bundles(&self) -> bool {
+        self.minify_bundles
+    }
+
+    pub fn minify_html(&self) -> bool {
+        self.minify_html
+    }
+
+    pub fn image_widths(&self) -> &[u32] {
+        &self.image_widths
+    }
+
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    pub fn default_language(&self) -> Option<&str> {
+        self.languages.first().map(String::as_str)
+    }
+
+    pub fn translation_dir(&self) -> &Path {
+        &self.translation_dir
+    }
+
+    pub fn extra_outputs(&self) -> &[(String, PathBuf)] {
+        &self.extra_outputs
+    }
+
+    pub fn output_formats(&self) -> &[OutputFormat] {
+        &self.output_formats
+    }
+
+    pub fn latex_font(&self) -> Option<&str> {
+        self.latex_font.as_deref()
+    }
+
+    pub fn books(&self) -> &[Book] {
+        &self.books
+    }
+
+    pub fn book(&self, name: &str) -> Option<&Book> {
+        self.books.iter().find(|book| book.name == name)
+    }
+
+    pub fn epub_layout(&self) -> &str {
+        &self.epub_layout
+    }
+
+    pub fn epub_fonts(&self) -> &[PathBuf] {
+        &self.epub_fonts
+    }
+
+    pub fn search_index(&self) -> bool {
+        self.search_index
+    }
+
+    pub fn glossary_path(&self) -> &Path {
+        &self.glossary_path
+    }
+
+    pub fn glossary_dir(&self) -> &Path {
+        &self.glossary_dir
+    }
+
+    pub fn glossary_template(&self) -> Option<&str> {
+        self.glossary_template.as_deref()
+    }
+
+    pub fn glossary_autolink(&self) -> bool {
+        self.glossary_autolink
+    }
+
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+
+    pub fn social_meta(&self) -> bool {
+        self.social_meta
+    }
+
+    pub fn reproducible(&self) -> bool {
+        self.reproducible
+    }
+
+    pub fn preserved_outputs(&self) -> &[PathBuf] {
+        &self.preserved_outputs
+    }
+
+    pub fn on_unsupported(&self) -> UnsupportedPolicy {
+        self.on_unsupported
+    }
+
+    pub fn heading_offset(&self) -> u8 {
+        self.heading_offset
+    }
+
+    pub fn section_wrapper(&self) -> SectionWrapper {
+        self.section_wrapper
+    }
+
+    pub fn skip_link(&self) -> bool {
+        self.skip_link
+    }
+
+    pub fn html_classes(&self) -> &HtmlClassConfig {
+        &self.html_classes
+    }
+
+    pub fn smart_typography(&self) -> bool {
+        self.smart_typography
+    }
+
+    pub fn external_links(&self) -> bool {
+        self.external_links
+    }
+
+    pub fn external_link_class(&self) -> &str {
+        &self.external_link_class
+    }
+
+    pub fn external_link_report(&self) -> Option<&Path> {
+        self.external_link_report.as_deref()
+    }
+
     pub fn finish(self) -> Result<LinSsg, InitError> {
         LinSsg::new(self)
     }
+
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        self.validate_paths(&mut errors);
+        self.validate_templates(&mut errors);
+        self.validate_output(&mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_paths(&self, errors: &mut Vec<ConfigError>) {
+        let defaults = Self::default();
+        if !self.fs.is_dir(&self.page_dir) {
+            errors.push(ConfigError::MissingDir("Page", self.page_dir.clone()));
+        }
+        if let Some(theme_dir) = &self.theme_dir {
+            if !self.fs.is_dir(theme_dir) {
+                errors
+                    .push(ConfigError::MissingDir("Theme", theme_dir.clone()));
+            }
+        }
+        // Directories left at their defaults are optional, but one that was
+        // configured explicitly is most likely a typo when it is missing.
+        let optional_dirs = [
+            ("Template", self.template_dir(), defaults.template_dir()),
+            ("Shortcode", &self.shortcode_dir, &defaults.shortcode_dir),
+            ("Data", &self.data_dir, &defaults.data_dir),
+            ("Translation", &self.translation_dir, &defaults.translation_dir),
+        ];
+        for (kind, directory, default) in optional_dirs {
+            if directory != default && !self.fs.is_dir(directory) {
+                errors
+                    .push(ConfigError::MissingDir(kind, directory.to_owned()));
+            }
+        }
+        let optional_site_dirs = [
+            ("Asset", &self.asset_dir, &defaults.asset_dir),
+            ("Partial", &self.partial_dir, &defaults.partial_dir),
+        ];
+        for (kind, directory, default) in optional_site_dirs {
+            if directory != default && !self.fs.is_dir(directory) {
+                errors
+                    .push(ConfigError::MissingDir(kind, directory.to_owned()));
+            }
+        }
+        for (template_dir, _) in &self.template_dirs[1 ..] {
+            if !self.fs.is_dir(template_dir) {
+                errors.push(ConfigError::MissingDir(
+                    "Template",
+                    template_dir.clone(),
+                ));
+            }
+        }
+        let optional_files = [
+            ("Lexicon", &self.lexicon_path, &defaults.lexicon_path),
+            ("Glossary", &self.glossary_path, &defaults.glossary_path),
+        ];
+        for (kind, path, default) in optional_files {
+            if path != default && !self.fs.is_file(path) {
+                errors.push(ConfigError::MissingFile(kind, path.clone()));
+            }
+        }
+        let tables = self
+            .linguinput_table
+            .iter()
+            .chain(self.named_linguinput_tables.iter().map(|(_, path)| path));
+        for path in tables {
+            if !self.fs.is_file(path) {
+                errors.push(ConfigError::MissingFile(
+                    "Linguinput table",
+                    path.clone(),
+                ));
+            }
+        }
+        let book_pages = self
+            .books
+            .iter()
+            .flat_map(|book| &book.pages)
+            .map(|page| self.page_dir.join(page));
+        for path in book_pages {
+            if !self.fs.is_file(&path) {
+                errors.push(ConfigError::MissingFile("Book page", path));
+            }
+        }
+        for path in &self.epub_fonts {
+            if !self.fs.is_file(path) {
+                errors
+                    .push(ConfigError::MissingFile("EPUB font", path.clone()));
+            }
+        }
+    }
+
+    fn validate_templates(&self, errors: &mut Vec<ConfigError>) {
+        let mut read = true;
+        for (template_dir, glob) in &self.template_dirs {
+            let glob = template_dir.join(glob);
+            match theme::read_glob(self, &glob) {
+                Ok(templates) => {
+                    if self.fs.is_dir(template_dir) && templates.is_empty() {
+                        errors.push(ConfigError::NoTemplates(glob));
+                    }
+                },
+                Err(error) => {
+                    read = false;
+                    errors.push(ConfigError::Template(error));
+                },
+            }
+        }
+        // Loading would only fail again on a glob that could not be read.
+        if read {
+            if let Err(error) = theme::load_templates(self) {
+                errors.push(ConfigError::Template(error));
+            }
+        }
+    }
+
+    fn validate_output(&self, errors: &mut Vec<ConfigError>) {
+        // Resolved lexically, so `..` cannot hide an overlap.
+        let resolve = |path: &Path| {
+            path::absolute(path)
+                .map(|path| site_fs::normalize(&path))
+                .map_err(|error| ConfigError::Resolve(path.to_owned(), error))
+        };
+        let output_dir = match resolve(&self.output_dir) {
+            Ok(output_dir) => output_dir,
+            Err(error) => {
+                errors.push(error);
+                return;
+            },
+        };
+        let asset_dirs = self.asset_dirs();
+        let sources = [("page", &self.page_dir)]
+            .into_iter()
+            .chain(asset_dirs.iter().map(|dir| ("asset", dir)));
+        for (kind, source_dir) in sources {
+            match resolve(source_dir) {
+                Ok(resolved) if output_dir.starts_with(&resolved) => {
+                    errors.push(ConfigError::OutputInside(
+                        self.output_dir.clone(),
+                        kind,
+                        source_dir.clone(),
+                    ));
+                },
+                Ok(_) => (),
+                Err(error) => errors.push(error),
+            }
+        }
+    }
+}
+
+fn extension_list<I>(extensions: I) -> Vec<String>
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    extensions
+        .into_iter()
+        .map(|extension| {
+            let extension = extension.into();
+            match extension.strip_prefix('.') {
+                Some(stripped) => stripped.to_owned(),
+                None => extension,
+            }
+        })
+        .collect()
 }
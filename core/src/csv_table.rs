@@ -0,0 +1,191 @@
+use std::{
+    fmt::{self, Write as _},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use thiserror::Error;
+
+use crate::{
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+    site_fs::SiteFs,
+    Config,
+};
+
+#[derive(Debug, Error)]
+pub enum CsvTableError {
+    #[error(
+        "CSV file {} found in neither the data nor the asset directory",
+        .0
+    )]
+    NotFound(String),
+    #[error("Failed to read CSV file {}", .0.display())]
+    Csv(PathBuf, #[source] csv::Error),
+    #[error("Alignment {} is not one of l, c or r", .0)]
+    BadAlignment(char),
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+impl Alignment {
+    fn parse(code: char) -> Result<Self, CsvTableError> {
+        match code {
+            'l' => Ok(Self::Left),
+            'c' => Ok(Self::Center),
+            'r' => Ok(Self::Right),
+            _ => Err(CsvTableError::BadAlignment(code)),
+        }
+    }
+
+    fn style(self) -> &'static str {
+        match self {
+            Self::Left => "",
+            Self::Center => " style=\"text-align: center\"",
+            Self::Right => " style=\"text-align: right\"",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CsvTableArgs<'a> {
+    path: &'a str,
+    caption: Option<&'a str>,
+    align: &'a str,
+    header: bool,
+}
+
+impl<'a> Args<'a> for CsvTableArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let path = args.retrive_arg("path")?;
+        let caption = args.retrive_arg_with_default("caption", || None)?;
+        let align = args.retrive_arg_with_default("align", || "")?;
+        let header = args.retrive_arg_with_default("header", || true)?;
+        Ok(Self { path, caption, align, header })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvTableFn {
+    fs: Arc<dyn SiteFs>,
+    search_dirs: Vec<PathBuf>,
+}
+
+impl CsvTableFn {
+    pub fn new(config: &Config) -> Self {
+        let mut search_dirs = vec![config.data_dir().to_owned()];
+        search_dirs.extend(config.asset_dirs());
+        Self { fs: config.shared_fs(), search_dirs }
+    }
+}
+
+impl Function for CsvTableFn {
+    type Args<'a> = CsvTableArgs<'a>;
+    type Output = String;
+    type Error = CsvTableError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let logical = args.path.trim_start_matches('/');
+        let path = self
+            .search_dirs
+            .iter()
+            .map(|directory| directory.join(logical))
+            .find(|path| self.fs.is_file(path))
+            .ok_or_else(|| CsvTableError::NotFound(logical.to_owned()))?;
+        let alignments = args
+            .align
+            .chars()
+            .map(Alignment::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        let style = |column: usize| {
+            alignments.get(column).map_or("", |alignment| alignment.style())
+        };
+        let delimiter = if path.extension().is_some_and(|ext| ext == "tsv") {
+            b'\t'
+        } else {
+            b','
+        };
+        let csv_error = |error| CsvTableError::Csv(path.clone(), error);
+        let bytes = self
+            .fs
+            .read(&path)
+            .map_err(|error| csv_error(csv::Error::from(error)))?;
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(args.header)
+            .flexible(true)
+            .from_reader(&bytes[..]);
+
+        let mut buf = String::new();
+        write!(buf, "<table class=\"csv-table\">")?;
+        if let Some(caption) = args.caption {
+            write!(buf, "<caption>{}</caption>", tera::escape_html(caption))?;
+        }
+        if args.header {
+            write!(buf, "<thead><tr>")?;
+            for (column, cell) in
+                reader.headers().map_err(csv_error)?.iter().enumerate()
+            {
+                write!(
+                    buf,
+                    "<th scope=\"col\"{}>{}</th>",
+                    style(column),
+                    tera::escape_html(cell)
+                )?;
+            }
+            write!(buf, "</tr></thead>")?;
+        }
+        write!(buf, "<tbody>")?;
+        for record in reader.records() {
+            write!(buf, "<tr>")?;
+            for (column, cell) in record.map_err(csv_error)?.iter().enumerate()
+            {
+                write!(
+                    buf,
+                    "<td{}>{}</td>",
+                    style(column),
+                    tera::escape_html(cell)
+                )?;
+            }
+            write!(buf, "</tr>")?;
+        }
+        write!(buf, "</tbody></table>")?;
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        "{# HTML table of a CSV (or TSV) file, looked up first in the data \
+         directory and then in the asset directory #}
+        csv_table(
+            {# path relative to the data or asset directory #}
+            path:string,
+            {# caption of the table #}
+            caption:string?,
+            {# column alignments, one letter per column: l (left), c (center)
+               or r (right), e.g. \"lrr\"; default all left #}
+            align:string?,
+            {# whether the first row is a header, default true #}
+            header:bool?
+        ) -> String "
+            .to_owned()
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::site_fs::SiteFs;
+
+pub const CONTEXT_KEY: &str = "data";
+
+#[derive(Debug, Error)]
+pub enum DataError {
+    #[error("Could not read data file {}", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("Invalid TOML in data file {}", .0.display())]
+    Toml(PathBuf, #[source] toml::de::Error),
+    #[error("Invalid JSON in data file {}", .0.display())]
+    Json(PathBuf, #[source] serde_json::Error),
+    #[error("Invalid CSV in data file {}", .0.display())]
+    Csv(PathBuf, #[source] csv::Error),
+    #[error(
+        "Data file {} collides with another file of the same name",
+        .0.display()
+    )]
+    Duplicate(PathBuf),
+    #[error("Data file name {} is not valid UTF-8", .0.display())]
+    NameUtf8(PathBuf),
+}
+
+pub fn load(
+    fs: &dyn SiteFs,
+    data_dir: &Path,
+) -> Result<Map<String, Value>, DataError> {
+    if !fs.is_dir(data_dir) {
+        return Ok(Map::new());
+    }
+    load_dir(fs, data_dir)
+}
+
+fn load_dir(
+    fs: &dyn SiteFs,
+    directory: &Path,
+) -> Result<Map<String, Value>, DataError> {
+    let entries = fs
+        .read_dir(directory)
+        .map_err(|error| DataError::Io(directory.to_owned(), error))?;
+
+    let mut data = Map::new();
+    for path in entries {
+        let (key, value) = if fs.is_dir(&path) {
+            let key = path.file_name().and_then(|name| name.to_str());
+            (key, Value::Object(load_dir(fs, &path)?))
+        } else {
+            let key = path.file_stem().and_then(|name| name.to_str());
+            let Some(value) = load_file(fs, &path)? else { continue };
+            (key, value)
+        };
+        let key = key.ok_or_else(|| DataError::NameUtf8(path.clone()))?;
+        if data.insert(key.to_owned(), value).is_some() {
+            Err(DataError::Duplicate(path))?;
+        }
+    }
+    Ok(data)
+}
+
+fn load_file(fs: &dyn SiteFs, path: &Path) -> Result<Option<Value>, DataError> {
+    let read = || {
+        fs.read_to_string(path)
+            .map_err(|error| DataError::Io(path.to_owned(), error))
+    };
+    let value = match path.extension().and_then(|extension| extension.to_str())
+    {
+        Some("toml") => toml::from_str(&read()?)
+            .map_err(|error| DataError::Toml(path.to_owned(), error))?,
+        Some("json") => serde_json::from_str(&read()?)
+            .map_err(|error| DataError::Json(path.to_owned(), error))?,
+        Some("csv") => load_csv(fs, path)
+            .map_err(|error| DataError::Csv(path.to_owned(), error))?,
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+fn load_csv(fs: &dyn SiteFs, path: &Path) -> Result<Value, csv::Error> {
+    let bytes = fs.read(path)?;
+    let mut reader = csv::Reader::from_reader(&bytes[..]);
+    let headers = reader.headers()?.clone();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let row: Map<_, _> = headers
+            .iter()
+            .zip(record?.iter())
+            .map(|(column, cell)| (column.to_owned(), Value::from(cell)))
+            .collect();
+        rows.push(Value::Object(row));
+    }
+    Ok(Value::Array(rows))
+}
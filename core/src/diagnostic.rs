@@ -0,0 +1,187 @@
+use std::{
+    fmt,
+    io::Write,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+// Offsets are in bytes, lines and columns count from 1 and columns count
+// characters. The end is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct SourceSpan {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl SourceSpan {
+    pub(crate) fn locate(code: &str, span: Range<usize>) -> Option<Self> {
+        let start = span.start.min(code.len());
+        let end = span.end.clamp(start, code.len());
+        let (line, column) = position(code, start)?;
+        let (end_line, end_column) = position(code, end)?;
+        Some(Self { start, end, line, column, end_line, end_column })
+    }
+}
+
+fn position(code: &str, offset: usize) -> Option<(usize, usize)> {
+    let before = code.get(.. offset)?;
+    let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+    let line = before.matches('\n').count() + 1;
+    Some((line, before[line_start ..].chars().count() + 1))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    severity: Severity,
+    path: PathBuf,
+    message: String,
+    span: Option<SourceSpan>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        path: impl Into<PathBuf>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            path: path.into(),
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn with_span(mut self, span: Option<SourceSpan>) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> Option<SourceSpan> {
+        self.span
+    }
+}
+
+pub trait DiagnosticsSink: Send + Sync + 'static {
+    fn report(&self, diagnostic: &Diagnostic);
+}
+
+impl<F> DiagnosticsSink for F
+where
+    F: Fn(&Diagnostic) + Send + Sync + 'static,
+{
+    fn report(&self, diagnostic: &Diagnostic) {
+        self(diagnostic)
+    }
+}
+
+// Writes one JSON object per line. Write errors are dropped, since there is
+// nowhere left to report them.
+#[derive(Debug)]
+pub struct JsonDiagnostics<W> {
+    writer: Mutex<W>,
+}
+
+impl<W> JsonDiagnostics<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer: Mutex::new(writer) }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl<W> DiagnosticsSink for JsonDiagnostics<W>
+where
+    W: Write + Send + 'static,
+{
+    fn report(&self, diagnostic: &Diagnostic) {
+        let mut writer =
+            self.writer.lock().unwrap_or_else(|error| error.into_inner());
+        if serde_json::to_writer(&mut *writer, diagnostic).is_ok() {
+            let _ = writeln!(writer);
+            let _ = writer.flush();
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct DiagnosticsSinks {
+    sinks: Vec<Arc<dyn DiagnosticsSink>>,
+}
+
+impl DiagnosticsSinks {
+    pub fn register<S>(&mut self, sink: S)
+    where
+        S: DiagnosticsSink,
+    {
+        self.sinks.push(Arc::new(sink));
+    }
+
+    pub fn report(&self, diagnostic: &Diagnostic) {
+        for sink in &self.sinks {
+            sink.report(diagnostic);
+        }
+    }
+}
+
+impl fmt::Debug for DiagnosticsSinks {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("DiagnosticsSinks")
+            .field("len", &self.sinks.len())
+            .finish()
+    }
+}
+
+pub fn annotate(path: &Path, code: &str, span: Range<usize>) -> String {
+    let start = span.start.min(code.len());
+    if !code.is_char_boundary(start) {
+        return String::new();
+    }
+    let line_start = code[.. start].rfind('\n').map_or(0, |pos| pos + 1);
+    let line_end =
+        code[start ..].find('\n').map_or(code.len(), |pos| start + pos);
+    let end = span.end.clamp(start, line_end);
+    let line_number = code[.. start].matches('\n').count() + 1;
+    let column = code[line_start .. start].chars().count() + 1;
+    let marked =
+        code.get(start .. end).map_or(0, |marked| marked.chars().count());
+    let line = code[line_start .. line_end].trim_end_matches('\r');
+
+    let width = line_number.to_string().len();
+    let gutter = " ".repeat(width);
+    format!(
+        "\n{gutter}--> {}:{line_number}:{column}\n{gutter} |\n{line_number} | \
+         {line}\n{gutter} | {}{}",
+        path.display(),
+        " ".repeat(column - 1),
+        "^".repeat(marked.max(1)),
+    )
+}
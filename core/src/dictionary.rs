@@ -0,0 +1,102 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::lexicon::Lexicon;
+
+pub const ENTRY_DIR: &str = "entry";
+pub const OTHER_LETTER: &str = "other";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DictionaryEntry<'a> {
+    pub key: &'a str,
+    pub slug: String,
+    pub url: String,
+    pub entry: &'a Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DictionaryLetter<'a> {
+    pub letter: String,
+    pub url: String,
+    pub entries: Vec<DictionaryEntry<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dictionary<'a> {
+    base_url: String,
+    letters: Vec<DictionaryLetter<'a>>,
+}
+
+impl<'a> Dictionary<'a> {
+    pub fn new(lexicon: &'a Lexicon, base_url: &str) -> Self {
+        let base_url = format!("/{}", base_url.trim_matches('/'));
+        let mut used_slugs = HashSet::new();
+        let mut grouped = BTreeMap::<String, Vec<_>>::new();
+
+        for entry in lexicon.entries() {
+            let key = lexicon.entry_key(entry);
+            let base_slug = slugify(key);
+            let mut slug = base_slug.clone();
+            let mut suffix = 1;
+            while !used_slugs.insert(slug.clone()) {
+                suffix += 1;
+                slug = format!("{base_slug}-{suffix}");
+            }
+            let url = format!("{base_url}/{ENTRY_DIR}/{slug}/");
+            grouped
+                .entry(index_letter(key))
+                .or_default()
+                .push(DictionaryEntry { key, slug, url, entry });
+        }
+
+        let letters = grouped
+            .into_iter()
+            .map(|(letter, mut entries)| {
+                entries.sort_by_cached_key(|entry| entry.key.to_lowercase());
+                let url = format!("{base_url}/{letter}/");
+                DictionaryLetter { letter, url, entries }
+            })
+            .collect();
+
+        Self { base_url, letters }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn letters(&self) -> &[DictionaryLetter<'a>] {
+        &self.letters
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &DictionaryEntry<'a>> {
+        self.letters.iter().flat_map(|letter| &letter.entries)
+    }
+}
+
+pub fn index_letter(key: &str) -> String {
+    match key.chars().find(|ch| ch.is_alphanumeric()) {
+        Some(ch) if ch.is_alphabetic() => ch.to_lowercase().collect(),
+        _ => OTHER_LETTER.to_owned(),
+    }
+}
+
+pub fn slugify(key: &str) -> String {
+    let mut slug = String::with_capacity(key.len());
+    for ch in key.chars() {
+        if ch.is_alphanumeric() {
+            slug.extend(ch.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    if slug.is_empty() {
+        slug.push_str("entry");
+    }
+    slug
+}
@@ -0,0 +1,87 @@
+use std::{
+    env,
+    fmt,
+    io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serde::Serialize;
+
+use crate::{output, site_fs::SiteFs};
+
+static STAGING_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Creates a staging directory no other dry run, in this process or another
+// one, is using.
+pub fn staging_dir(fs: &dyn SiteFs) -> io::Result<PathBuf> {
+    loop {
+        let count = STAGING_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir()
+            .join(format!("lin-ssg-dry-run-{}-{count}", process::id()));
+        match fs.kind(&path) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                fs.create_dir_all(&path)?;
+                return Ok(path);
+            },
+            Err(error) => return Err(error),
+            Ok(_) => continue,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BuildDiff {
+    pub created: Vec<PathBuf>,
+    pub changed: Vec<PathBuf>,
+    pub deleted: Vec<PathBuf>,
+}
+
+impl BuildDiff {
+    pub fn compare(
+        fs: &dyn SiteFs,
+        current: &Path,
+        planned: &Path,
+        preserved: &[PathBuf],
+    ) -> io::Result<Self> {
+        let existing = output::list_files(fs, current)?;
+        let mut current_files =
+            output::generated_files(fs, current, preserved)?;
+        let mut diff = Self::default();
+        for path_rel in output::list_files(fs, planned)? {
+            if path_rel == Path::new(output::MANIFEST_FILE_NAME) {
+                continue;
+            }
+            current_files.remove(&path_rel);
+            if !existing.contains(&path_rel) {
+                diff.created.push(path_rel);
+            } else if fs.read(&current.join(&path_rel))?
+                != fs.read(&planned.join(&path_rel))?
+            {
+                diff.changed.push(path_rel);
+            }
+        }
+        diff.deleted.extend(current_files);
+        Ok(diff)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty()
+            && self.changed.is_empty()
+            && self.deleted.is_empty()
+    }
+}
+
+impl fmt::Display for BuildDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (sign, paths) in
+            [('+', &self.created), ('~', &self.changed), ('-', &self.deleted)]
+        {
+            for path in paths {
+                writeln!(f, "{sign} {}", path.display())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use tera::escape_html;
+use thiserror::Error;
+
+pub const MIMETYPE: &str = "application/epub+zip";
+pub const CONTENT_DIR: &str = "OEBPS";
+pub const STYLESHEET: &str = "style.css";
+pub const NAV_FILE: &str = "nav.xhtml";
+pub const FONT_DIR: &str = "fonts";
+
+const CONTAINER: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n",
+    "<container version=\"1.0\" ",
+    "xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">",
+    "<rootfiles><rootfile full-path=\"OEBPS/content.opf\" ",
+    "media-type=\"application/oebps-package+xml\"/></rootfiles>",
+    "</container>\n",
+);
+
+#[derive(Debug, Error)]
+pub enum EpubError {
+    #[error("Font {} is neither TTF, OTF, WOFF nor WOFF2", .0.display())]
+    UnsupportedFont(PathBuf),
+}
+
+pub fn chapter_file_name(index: usize) -> String {
+    format!("chapter-{:03}.xhtml", index + 1)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpubChapter {
+    pub title: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpubFont {
+    file_name: String,
+    family: String,
+    media_type: &'static str,
+    data: Vec<u8>,
+}
+
+impl EpubFont {
+    pub fn new(path: &Path, data: Vec<u8>) -> Result<Self, EpubError> {
+        let unsupported = || EpubError::UnsupportedFont(path.to_owned());
+        let media_type = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ttf") => "font/ttf",
+            Some("otf") => "font/otf",
+            Some("woff") => "font/woff",
+            Some("woff2") => "font/woff2",
+            _ => Err(unsupported())?,
+        };
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(unsupported)?
+            .to_owned();
+        let family = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(unsupported)?
+            .to_owned();
+        Ok(Self { file_name, family, media_type, data })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Epub {
+    pub identifier: String,
+    pub title: String,
+    pub lang: String,
+    pub modified: DateTime<Utc>,
+    pub chapters: Vec<EpubChapter>,
+    pub fonts: Vec<EpubFont>,
+}
+
+impl Epub {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut zip = StoredZip::default();
+        // Readers identify the file by this entry, so it comes first.
+        zip.add("mimetype", MIMETYPE.as_bytes());
+        zip.add("META-INF/container.xml", CONTAINER.as_bytes());
+        zip.add(
+            &format!("{CONTENT_DIR}/content.opf"),
+            self.package().as_bytes(),
+        );
+        zip.add(&format!("{CONTENT_DIR}/{NAV_FILE}"), self.nav().as_bytes());
+        zip.add(
+            &format!("{CONTENT_DIR}/{STYLESHEET}"),
+            self.stylesheet().as_bytes(),
+        );
+        for (i, chapter) in self.chapters.iter().enumerate() {
+            zip.add(
+                &format!("{CONTENT_DIR}/{}", chapter_file_name(i)),
+                chapter.content.as_bytes(),
+            );
+        }
+        for font in &self.fonts {
+            zip.add(
+                &format!("{CONTENT_DIR}/{FONT_DIR}/{}", font.file_name),
+                &font.data,
+            );
+        }
+        zip.finish()
+    }
+
+    fn package(&self) -> String {
+        let mut buf = String::new();
+        let lang = escape_html(&self.lang);
+        let _ = writeln!(buf, "<?xml version=\"1.0\" encoding=\"utf-8\"?>");
+        let _ = writeln!(
+            buf,
+            "<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" \
+             unique-identifier=\"book-id\" xml:lang=\"{lang}\">"
+        );
+        let _ = writeln!(
+            buf,
+            "<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">"
+        );
+        let _ = writeln!(
+            buf,
+            "<dc:identifier id=\"book-id\">{}</dc:identifier>",
+            escape_html(&self.identifier)
+        );
+        let _ =
+            writeln!(buf, "<dc:title>{}</dc:title>", escape_html(&self.title));
+        let _ = writeln!(buf, "<dc:language>{lang}</dc:language>");
+        let _ = writeln!(
+            buf,
+            "<meta property=\"dcterms:modified\">{}</meta>",
+            self.modified.to_rfc3339_opts(SecondsFormat::Secs, true)
+        );
+        let _ = writeln!(buf, "</metadata>");
+        let _ = writeln!(buf, "<manifest>");
+        let _ = writeln!(
+            buf,
+            "<item id=\"nav\" href=\"{NAV_FILE}\" \
+             media-type=\"application/xhtml+xml\" properties=\"nav\"/>"
+        );
+        let _ = writeln!(
+            buf,
+            "<item id=\"style\" href=\"{STYLESHEET}\" \
+             media-type=\"text/css\"/>"
+        );
+        for i in 0 .. self.chapters.len() {
+            let _ = writeln!(
+                buf,
+                "<item id=\"chapter-{i}\" href=\"{}\" \
+                 media-type=\"application/xhtml+xml\"/>",
+                chapter_file_name(i)
+            );
+        }
+        for (i, font) in self.fonts.iter().enumerate() {
+            let _ = writeln!(
+                buf,
+                "<item id=\"font-{i}\" href=\"{FONT_DIR}/{}\" \
+                 media-type=\"{}\"/>",
+                escape_html(&font.file_name),
+                font.media_type
+            );
+        }
+        let _ = writeln!(buf, "</manifest>");
+        let _ = writeln!(buf, "<spine>");
+        for i in 0 .. self.chapters.len() {
+            let _ = writeln!(buf, "<itemref idref=\"chapter-{i}\"/>");
+        }
+        let _ = writeln!(buf, "</spine>");
+        let _ = writeln!(buf, "</package>");
+        buf
+    }
+
+    fn nav(&self) -> String {
+        let mut buf = String::new();
+        let lang = escape_html(&self.lang);
+        let title = escape_html(&self.title);
+        let _ = writeln!(buf, "<?xml version=\"1.0\" encoding=\"utf-8\"?>");
+        let _ = writeln!(buf, "<!DOCTYPE html>");
+        let _ = writeln!(
+            buf,
+            "<html xmlns=\"http://www.w3.org/1999/xhtml\" \
+             xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"{lang}\" \
+             xml:lang=\"{lang}\">"
+        );
+        let _ = writeln!(buf, "<head><title>{title}</title></head>");
+        let _ = writeln!(buf, "<body><nav epub:type=\"toc\" id=\"toc\">");
+        let _ = writeln!(buf, "<h1>{title}</h1><ol>");
+        for (i, chapter) in self.chapters.iter().enumerate() {
+            let _ = writeln!(
+                buf,
+                "<li><a href=\"{}\">{}</a></li>",
+                chapter_file_name(i),
+                escape_html(&chapter.title)
+            );
+        }
+        let _ = writeln!(buf, "</ol></nav></body></html>");
+        buf
+    }
+
+    // The embedded fonts come first in the body's font stack, so that
+    // readers without IPA coverage in their own fonts still show it.
+    fn stylesheet(&self) -> String {
+        let mut buf = String::new();
+        for font in &self.fonts {
+            let _ = writeln!(
+                buf,
+                "@font-face {{ font-family: \"{}\"; src: \
+                 url(\"{FONT_DIR}/{}\"); }}",
+                font.family, font.file_name
+            );
+        }
+        let families = self
+            .fonts
+            .iter()
+            .map(|font| format!("\"{}\", ", font.family))
+            .collect::<String>();
+        let _ = writeln!(buf, "body {{ font-family: {families}serif; }}");
+        buf
+    }
+}
+
+// Entries are stored uncompressed, which EPUB requires of the mimetype entry
+// and allows for every other one.
+#[derive(Debug, Default)]
+struct StoredZip {
+    buf: Vec<u8>,
+    central: Vec<u8>,
+    entries: u16,
+}
+
+impl StoredZip {
+    const VERSION: u16 = 20;
+    const UTF8_NAMES: u16 = 0x0800;
+    // 1980-01-01, the earliest date the format represents.
+    const DATE: u16 = 0x0021;
+
+    fn add(&mut self, name: &str, data: &[u8]) {
+        let offset = self.buf.len() as u32;
+        let fields =
+            Self::entry_fields(crc32fast::hash(data), data.len() as u32, name);
+
+        put32(&mut self.buf, 0x0403_4b50);
+        put16(&mut self.buf, Self::VERSION);
+        self.buf.extend_from_slice(&fields);
+        self.buf.extend_from_slice(name.as_bytes());
+        self.buf.extend_from_slice(data);
+
+        put32(&mut self.central, 0x0201_4b50);
+        put16(&mut self.central, Self::VERSION);
+        put16(&mut self.central, Self::VERSION);
+        self.central.extend_from_slice(&fields);
+        // No comment, a single disk and no file attributes.
+        put16(&mut self.central, 0);
+        put16(&mut self.central, 0);
+        put16(&mut self.central, 0);
+        put32(&mut self.central, 0);
+        put32(&mut self.central, offset);
+        self.central.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+    }
+
+    // Fields shared by the local and the central header of an entry.
+    fn entry_fields(crc: u32, size: u32, name: &str) -> Vec<u8> {
+        let mut fields = Vec::new();
+        put16(&mut fields, Self::UTF8_NAMES);
+        put16(&mut fields, 0);
+        put16(&mut fields, 0);
+        put16(&mut fields, Self::DATE);
+        put32(&mut fields, crc);
+        put32(&mut fields, size);
+        put32(&mut fields, size);
+        put16(&mut fields, name.len() as u16);
+        put16(&mut fields, 0);
+        fields
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let central_offset = self.buf.len() as u32;
+        let central_size = self.central.len() as u32;
+        self.buf.append(&mut self.central);
+        put32(&mut self.buf, 0x0605_4b50);
+        put16(&mut self.buf, 0);
+        put16(&mut self.buf, 0);
+        put16(&mut self.buf, self.entries);
+        put16(&mut self.buf, self.entries);
+        put32(&mut self.buf, central_size);
+        put32(&mut self.buf, central_offset);
+        put16(&mut self.buf, 0);
+        self.buf
+    }
+}
+
+fn put16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
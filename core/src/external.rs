@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::{
+    minify,
+    search,
+    transform::{Transform, TransformError},
+};
+
+pub const LINK_CLASS: &str = "external-link";
+
+pub const LINK_REL: &str = "noopener noreferrer";
+
+pub fn is_external(href: &str, base_url: Option<&str>) -> bool {
+    let absolute = href.starts_with("http://")
+        || href.starts_with("https://")
+        || href.starts_with("//");
+    absolute
+        && base_url.is_none_or(|base_url| {
+            !href.starts_with(base_url.trim_end_matches('/'))
+        })
+}
+
+pub fn external_urls(html: &str, base_url: Option<&str>) -> Vec<String> {
+    link_tags(html)
+        .filter_map(|(start, len)| {
+            search::attribute(&html[start .. start + len], "href")
+        })
+        .filter(|href| is_external(href, base_url))
+        .collect()
+}
+
+fn link_tags(html: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    html.match_indices("<a").filter_map(|(start, _)| {
+        let next = html[start + 2 ..].chars().next()?;
+        (next.is_whitespace() || next == '>')
+            .then(|| (start, minify::tag_len(&html[start ..])))
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct ExternalLinks {
+    base_url: Option<String>,
+    class: String,
+}
+
+impl ExternalLinks {
+    pub fn new(base_url: Option<String>, class: impl Into<String>) -> Self {
+        Self { base_url, class: class.into() }
+    }
+
+    fn decorate(&self, tag: &str) -> String {
+        let (open, close) = match tag.strip_suffix("/>") {
+            Some(open) => (open.trim_end(), "/>"),
+            None => (tag.trim_end_matches('>'), ">"),
+        };
+        let mut decorated = open.to_owned();
+        match decorated.find(" class=\"") {
+            Some(start) if !self.class.is_empty() => {
+                let insert_at = start + " class=\"".len();
+                decorated.insert_str(insert_at, &format!("{} ", self.class));
+            },
+            Some(_) => (),
+            None if !self.class.is_empty() => {
+                decorated.push_str(&format!(" class=\"{}\"", self.class));
+            },
+            None => (),
+        }
+        if !decorated.contains(" target=") {
+            decorated.push_str(" target=\"_blank\"");
+        }
+        if !decorated.contains(" rel=") {
+            decorated.push_str(&format!(" rel=\"{LINK_REL}\""));
+        }
+        decorated.push_str(close);
+        decorated
+    }
+}
+
+impl Transform for ExternalLinks {
+    fn name(&self) -> &str {
+        "external_links"
+    }
+
+    fn transform(&self, html: &str) -> Result<String, TransformError> {
+        let mut output = String::with_capacity(html.len());
+        let mut copied = 0;
+        for (start, len) in link_tags(html) {
+            let tag = &html[start .. start + len];
+            let external = search::attribute(tag, "href").is_some_and(|href| {
+                is_external(&href, self.base_url.as_deref())
+            });
+            if external {
+                output.push_str(&html[copied .. start]);
+                output.push_str(&self.decorate(tag));
+                copied = start + len;
+            }
+        }
+        output.push_str(&html[copied ..]);
+        Ok(output)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct LinkReport {
+    links: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl LinkReport {
+    pub fn record(&mut self, page_url: &str, urls: Vec<String>) {
+        for url in urls {
+            self.links.entry(url).or_default().insert(page_url.to_owned());
+        }
+    }
+}
@@ -1,23 +1,270 @@
 use std::{
     collections::{HashMap, HashSet},
     error::Error,
+    fmt::Write as _,
+    sync::Arc,
 };
 
 use serde_json::Value;
 use thiserror::Error;
 
+use crate::page_ctx::{CurrentPage, PageCtx};
+
+pub const POSITIONAL_PREFIX: &str = "_";
+pub const INPUT_ARG: &str = "input";
+
+pub fn positional_key(index: usize) -> String {
+    format!("{POSITIONAL_PREFIX}{index}")
+}
+
 pub fn invoke_fn<F>(
     fn_name: &str,
     fun: &F,
     args: &HashMap<String, serde_json::Value>,
+    ctx: &PageCtx,
 ) -> Result<F::Output, InvokeError<F::Error>>
 where
     F: Function,
 {
-    let mut arg_parser = ArgParser::new(fn_name, args);
+    let mut arg_parser = ArgParser::new(fn_name, args)
+        .with_positional(<F::Args<'_> as Args>::POSITIONAL);
     let parsed_args = Args::parse(&mut arg_parser).map_err(InvokeError::Arg)?;
     arg_parser.finish().map_err(InvokeError::Arg)?;
-    fun.call(parsed_args).map_err(InvokeError::Execution)
+    fun.call(parsed_args, ctx).map_err(InvokeError::Execution)
+}
+
+#[derive(Debug)]
+pub struct TeraFunction<F> {
+    name: String,
+    fun: Arc<F>,
+    page: CurrentPage,
+}
+
+impl<F> Clone for TeraFunction<F> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            fun: self.fun.clone(),
+            page: self.page.clone(),
+        }
+    }
+}
+
+impl<F> TeraFunction<F> {
+    pub fn new(name: String, fun: F, page: CurrentPage) -> Self {
+        Self { name, fun: Arc::new(fun), page }
+    }
+}
+
+impl<F> TeraFunction<F>
+where
+    F: Function,
+{
+    pub fn takes_positional(&self) -> bool {
+        !<F::Args<'_> as Args>::POSITIONAL.is_empty()
+    }
+
+    fn invoke(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        match invoke_fn(&self.name, &*self.fun, args, &self.page.get()) {
+            Ok(output) => Ok(output.into()),
+            Err(error) => Err(call_error(&self.name, args, &error)),
+        }
+    }
+}
+
+fn call_error(
+    name: &str,
+    args: &HashMap<String, Value>,
+    error: &dyn Error,
+) -> tera::Error {
+    let mut buf = format!("error in {}(", name);
+    for (i, (key, value)) in args.iter().enumerate() {
+        if i > 0 {
+            let _ = write!(buf, ", ");
+        }
+        let _ = write!(buf, "{}={}", key, value);
+    }
+    let _ = writeln!(buf, "):");
+    let mut next_source = Some(error);
+    while let Some(source) = next_source {
+        let _ = writeln!(buf, "- caused by: {}", source);
+        next_source = source.source();
+    }
+    tera::Error::msg(buf)
+}
+
+impl<F> tera::Function for TeraFunction<F>
+where
+    F: Function,
+{
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        self.invoke(args)
+    }
+
+    fn is_safe(&self) -> bool {
+        self.fun.is_safe()
+    }
+}
+
+impl<F> tera::Filter for TeraFunction<F>
+where
+    F: Function,
+{
+    fn filter(
+        &self,
+        value: &Value,
+        args: &HashMap<String, Value>,
+    ) -> tera::Result<Value> {
+        let mut args = args.clone();
+        args.insert(positional_key(0), value.clone());
+        self.invoke(&args)
+    }
+
+    fn is_safe(&self) -> bool {
+        self.fun.is_safe()
+    }
+}
+
+pub fn invoke_filter<F>(
+    filter_name: &str,
+    filter: &F,
+    input: &Value,
+    args: &HashMap<String, Value>,
+    ctx: &PageCtx,
+) -> Result<F::Output, InvokeError<F::Error>>
+where
+    F: Filter,
+{
+    let input = parse_input(input).map_err(InvokeError::Arg)?;
+    let mut arg_parser = ArgParser::new(filter_name, args)
+        .with_positional(<F::Args<'_> as Args>::POSITIONAL);
+    let parsed_args = Args::parse(&mut arg_parser).map_err(InvokeError::Arg)?;
+    arg_parser.finish().map_err(InvokeError::Arg)?;
+    filter.filter(input, parsed_args, ctx).map_err(InvokeError::Execution)
+}
+
+pub fn invoke_tester<T>(
+    tester_name: &str,
+    tester: &T,
+    input: &Value,
+    args: &HashMap<String, Value>,
+    ctx: &PageCtx,
+) -> Result<bool, InvokeError<T::Error>>
+where
+    T: Tester,
+{
+    let input = parse_input(input).map_err(InvokeError::Arg)?;
+    let mut arg_parser = ArgParser::new(tester_name, args)
+        .with_positional(<T::Args<'_> as Args>::POSITIONAL);
+    let parsed_args = Args::parse(&mut arg_parser).map_err(InvokeError::Arg)?;
+    arg_parser.finish().map_err(InvokeError::Arg)?;
+    tester.test(input, parsed_args, ctx).map_err(InvokeError::Execution)
+}
+
+fn parse_input<'a, A>(input: &'a Value) -> Result<A, ArgError>
+where
+    A: Arg<'a>,
+{
+    A::from_json_ref(input).ok_or_else(|| ArgError::MismatchedTypes {
+        arg: INPUT_ARG.to_owned(),
+        ty: A::json_type(),
+    })
+}
+
+#[derive(Debug)]
+pub struct TeraFilter<F> {
+    name: String,
+    filter: Arc<F>,
+    page: CurrentPage,
+}
+
+impl<F> Clone for TeraFilter<F> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            filter: self.filter.clone(),
+            page: self.page.clone(),
+        }
+    }
+}
+
+impl<F> TeraFilter<F> {
+    pub fn new(name: String, filter: F, page: CurrentPage) -> Self {
+        Self { name, filter: Arc::new(filter), page }
+    }
+}
+
+impl<F> tera::Filter for TeraFilter<F>
+where
+    F: Filter,
+{
+    fn filter(
+        &self,
+        value: &Value,
+        args: &HashMap<String, Value>,
+    ) -> tera::Result<Value> {
+        let ctx = self.page.get();
+        match invoke_filter(&self.name, &*self.filter, value, args, &ctx) {
+            Ok(output) => Ok(output.into()),
+            Err(error) => {
+                let mut args = args.clone();
+                args.insert(INPUT_ARG.to_owned(), value.clone());
+                Err(call_error(&self.name, &args, &error))
+            },
+        }
+    }
+
+    fn is_safe(&self) -> bool {
+        self.filter.is_safe()
+    }
+}
+
+#[derive(Debug)]
+pub struct TeraTester<T> {
+    name: String,
+    tester: Arc<T>,
+    page: CurrentPage,
+}
+
+impl<T> Clone for TeraTester<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            tester: self.tester.clone(),
+            page: self.page.clone(),
+        }
+    }
+}
+
+impl<T> TeraTester<T> {
+    pub fn new(name: String, tester: T, page: CurrentPage) -> Self {
+        Self { name, tester: Arc::new(tester), page }
+    }
+}
+
+impl<T> tera::Test for TeraTester<T>
+where
+    T: Tester,
+{
+    fn test(
+        &self,
+        value: Option<&Value>,
+        args: &[Value],
+    ) -> tera::Result<bool> {
+        let mut args: HashMap<_, _> = args
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (positional_key(i), value.clone()))
+            .collect();
+        let input = value.unwrap_or(&Value::Null);
+        let ctx = self.page.get();
+        invoke_tester(&self.name, &*self.tester, input, &args, &ctx).map_err(
+            |error| {
+                args.insert(INPUT_ARG.to_owned(), input.clone());
+                call_error(&self.name, &args, &error)
+            },
+        )
+    }
 }
 
 #[derive(Debug, Error)]
@@ -36,6 +283,8 @@ pub enum ArgError {
     MismatchedTypes { arg: String, ty: String },
     #[error("Argument {} is unknown", .0)]
     UnknownArguments(String),
+    #[error("Argument {} is given both by name and by position", .0)]
+    DuplicatedArgument(String),
 }
 
 pub trait Function: Send + Sync + 'static {
@@ -46,13 +295,215 @@ pub trait Function: Send + Sync + 'static {
     fn call<'a>(
         &self,
         args: Self::Args<'a>,
+        ctx: &PageCtx,
     ) -> Result<Self::Output, Self::Error>;
 
     fn doc(&self) -> String;
+
+    fn is_safe(&self) -> bool {
+        false
+    }
+}
+
+pub trait Filter: Send + Sync + 'static {
+    type Input<'a>: Arg<'a>;
+    type Args<'a>: Args<'a>;
+    type Output: Into<serde_json::Value>;
+    type Error: Error;
+
+    fn filter<'a>(
+        &self,
+        input: Self::Input<'a>,
+        args: Self::Args<'a>,
+        ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error>;
+
+    fn doc(&self) -> String;
+
+    fn is_safe(&self) -> bool {
+        false
+    }
+}
+
+pub trait Tester: Send + Sync + 'static {
+    type Input<'a>: Arg<'a>;
+    type Args<'a>: Args<'a>;
+    type Error: Error;
+
+    fn test<'a>(
+        &self,
+        input: Self::Input<'a>,
+        args: Self::Args<'a>,
+        ctx: &PageCtx,
+    ) -> Result<bool, Self::Error>;
+
+    fn doc(&self) -> String;
 }
 
 pub trait Args<'a>: Sized {
+    const POSITIONAL: &'static [&'static str] = &[];
+
     fn parse(arg_parser: &mut ArgParser<'a>) -> Result<Self, ArgError>;
+
+    fn doc() -> String {
+        String::new()
+    }
+}
+
+#[macro_export]
+macro_rules! args {
+    (
+        positional [$($positional:literal),* $(,)?];
+        $($rest:tt)*
+    ) => {
+        $crate::args!(@struct [$($positional),*] $($rest)*);
+    };
+
+    (
+        @struct [$($positional:literal),*]
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident<$lt:lifetime> {
+            $(
+                $(#[doc = $doc:literal])*
+                $field_vis:vis $field:ident: $field_ty:ty
+                $(=> $arg:literal)?
+                $(= $default:expr)?
+            ),*
+            $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name<$lt> {
+            $($(#[doc = $doc])* $field_vis $field: $field_ty,)*
+        }
+
+        $crate::args!(
+            @impl [$lt] [$name<$lt>] [$($positional),*]
+            $(
+                $(#[doc = $doc])*
+                $field_vis $field: $field_ty
+                $(=> $arg)?
+                $(= $default)?
+            ),*
+        );
+    };
+
+    (
+        @struct [$($positional:literal),*]
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[doc = $doc:literal])*
+                $field_vis:vis $field:ident: $field_ty:ty
+                $(=> $arg:literal)?
+                $(= $default:expr)?
+            ),*
+            $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $($(#[doc = $doc])* $field_vis $field: $field_ty,)*
+        }
+
+        $crate::args!(
+            @impl ['a] [$name] [$($positional),*]
+            $(
+                $(#[doc = $doc])*
+                $field_vis $field: $field_ty
+                $(=> $arg)?
+                $(= $default)?
+            ),*
+        );
+    };
+
+    (
+        @impl [$lt:lifetime] [$self_ty:ty] [$($positional:literal),*]
+        $(
+            $(#[doc = $doc:literal])*
+            $field_vis:vis $field:ident: $field_ty:ty
+            $(=> $arg:literal)?
+            $(= $default:expr)?
+        ),*
+        $(,)?
+    ) => {
+        impl<$lt> $crate::Args<$lt> for $self_ty {
+            const POSITIONAL: &'static [&'static str] = &[$($positional),*];
+
+            fn parse(
+                arg_parser: &mut $crate::ArgParser<$lt>,
+            ) -> Result<Self, $crate::ArgError> {
+                Ok(Self {
+                    $(
+                        $field: $crate::args!(
+                            @retrive arg_parser,
+                            $crate::args!(@name $field $($arg)?),
+                            $($default)?
+                        ),
+                    )*
+                })
+            }
+
+            fn doc() -> String {
+                let params: Vec<String> = vec![$(
+                    {
+                        let lines: &[&str] = &[$($doc),*];
+                        let comment = lines
+                            .iter()
+                            .map(|line| line.trim())
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        let optional = $crate::args!(@optional $($default)?);
+                        let ty = <$field_ty as $crate::Arg<$lt>>::json_type();
+                        let ty = match ty.strip_prefix("optional ") {
+                            Some(inner) if optional => inner,
+                            _ => &ty,
+                        };
+                        format!(
+                            "{}{}:{}{}",
+                            if comment.is_empty() {
+                                String::new()
+                            } else {
+                                format!("{{# {} #}}\n", comment)
+                            },
+                            $crate::args!(@name $field $($arg)?),
+                            ty,
+                            if optional { "?" } else { "" },
+                        )
+                    }
+                ),*];
+                params.join(",\n")
+            }
+        }
+    };
+
+    (@name $field:ident) => {
+        stringify!($field)
+    };
+
+    (@name $field:ident $arg:literal) => {
+        $arg
+    };
+
+    (@retrive $parser:ident, $name:expr,) => {
+        $parser.retrive_arg($name)?
+    };
+
+    (@retrive $parser:ident, $name:expr, $default:expr) => {
+        $parser.retrive_arg_with_default($name, || $default)?
+    };
+
+    (@optional) => {
+        false
+    };
+
+    (@optional $default:expr) => {
+        true
+    };
+
+    ($($rest:tt)*) => {
+        $crate::args!(@struct [] $($rest)*);
+    };
 }
 
 pub trait Arg<'a>: Sized {
@@ -61,6 +512,12 @@ pub trait Arg<'a>: Sized {
     fn json_type() -> String;
 }
 
+impl<'a> Args<'a> for () {
+    fn parse(_arg_parser: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        Ok(())
+    }
+}
+
 impl<'a> Arg<'a> for bool {
     fn from_json_ref(json: &'a Value) -> Option<Self> {
         json.as_bool()
@@ -111,6 +568,55 @@ impl<'a> Arg<'a> for &'a str {
     }
 }
 
+impl<'a> Arg<'a> for &'a [Value] {
+    fn from_json_ref(json: &'a Value) -> Option<Self> {
+        json.as_array().map(Vec::as_slice)
+    }
+
+    fn json_type() -> String {
+        "array".to_owned()
+    }
+}
+
+impl<'a> Arg<'a> for &'a Value {
+    fn from_json_ref(json: &'a Value) -> Option<Self> {
+        Some(json)
+    }
+
+    fn json_type() -> String {
+        "any".to_owned()
+    }
+}
+
+impl<'a, A> Arg<'a> for Vec<A>
+where
+    A: Arg<'a>,
+{
+    fn from_json_ref(json: &'a Value) -> Option<Self> {
+        json.as_array()?.iter().map(A::from_json_ref).collect()
+    }
+
+    fn json_type() -> String {
+        format!("array of {}", A::json_type())
+    }
+}
+
+impl<'a, A> Arg<'a> for HashMap<String, A>
+where
+    A: Arg<'a>,
+{
+    fn from_json_ref(json: &'a Value) -> Option<Self> {
+        json.as_object()?
+            .iter()
+            .map(|(key, value)| Some((key.clone(), A::from_json_ref(value)?)))
+            .collect()
+    }
+
+    fn json_type() -> String {
+        format!("object of {}", A::json_type())
+    }
+}
+
 impl<'a, A> Arg<'a> for Option<A>
 where
     A: Arg<'a>,
@@ -132,6 +638,7 @@ where
 pub struct ArgParser<'a> {
     fn_name: &'a str,
     args: &'a HashMap<String, Value>,
+    positional: &'static [&'static str],
     unknown: HashSet<&'a str>,
 }
 
@@ -140,10 +647,40 @@ impl<'a> ArgParser<'a> {
         Self {
             fn_name,
             args,
+            positional: &[],
             unknown: args.keys().map(String::as_ref).collect(),
         }
     }
 
+    pub fn with_positional(
+        mut self,
+        positional: &'static [&'static str],
+    ) -> Self {
+        self.positional = positional;
+        self
+    }
+
+    fn lookup(
+        &self,
+        name: &str,
+    ) -> Result<Option<(&'a str, &'a Value)>, ArgError> {
+        let named = self.args.get_key_value(name);
+        let positional = self
+            .positional
+            .iter()
+            .position(|positional| *positional == name)
+            .and_then(|index| self.args.get_key_value(&positional_key(index)));
+        match (named, positional) {
+            (Some(_), Some(_)) => {
+                Err(ArgError::DuplicatedArgument(name.to_owned()))
+            },
+            (Some((key, json)), None) | (None, Some((key, json))) => {
+                Ok(Some((key.as_str(), json)))
+            },
+            (None, None) => Ok(None),
+        }
+    }
+
     #[allow(unused)]
     pub fn fn_name(&self) -> &'a str {
         self.fn_name
@@ -153,9 +690,8 @@ impl<'a> ArgParser<'a> {
     where
         A: Arg<'a>,
     {
-        let json = self
-            .args
-            .get(name)
+        let (key, json) = self
+            .lookup(name)?
             .ok_or_else(|| ArgError::MissingArgument(name.to_owned()))?;
         let arg = A::from_json_ref(json).ok_or_else(|| {
             ArgError::MismatchedTypes {
@@ -163,7 +699,7 @@ impl<'a> ArgParser<'a> {
                 ty: A::json_type(),
             }
         })?;
-        self.unknown.remove(name);
+        self.unknown.remove(key);
         Ok(arg)
     }
 
@@ -176,19 +712,29 @@ impl<'a> ArgParser<'a> {
         A: Arg<'a>,
         F: FnOnce() -> A,
     {
-        let arg = match self.args.get(name) {
-            Some(json) => A::from_json_ref(json).ok_or_else(|| {
-                ArgError::MismatchedTypes {
-                    arg: name.to_owned(),
-                    ty: A::json_type(),
-                }
-            })?,
+        let arg = match self.lookup(name)? {
+            Some((key, json)) => {
+                self.unknown.remove(key);
+                A::from_json_ref(json).ok_or_else(|| {
+                    ArgError::MismatchedTypes {
+                        arg: name.to_owned(),
+                        ty: A::json_type(),
+                    }
+                })?
+            },
             None => default(),
         };
-        self.unknown.remove(name);
         Ok(arg)
     }
 
+    pub fn retrive_rest(&mut self) -> Vec<(&'a str, &'a Value)> {
+        let args = self.args;
+        self.unknown
+            .drain()
+            .filter_map(|name| Some((name, args.get(name)?)))
+            .collect()
+    }
+
     fn finish(self) -> Result<(), ArgError> {
         if self.unknown.is_empty() {
             Ok(())
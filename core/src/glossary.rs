@@ -0,0 +1,198 @@
+use std::{collections::BTreeMap, io, path::Path, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use tera::escape_html;
+use thiserror::Error;
+
+use crate::{
+    dictionary::slugify,
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+    site_fs::SiteFs,
+};
+
+pub const ANCHOR_PREFIX: &str = "term-";
+
+#[derive(Debug, Error)]
+pub enum GlossaryError {
+    #[error("Failed to read glossary {}", .0)]
+    Io(String, #[source] io::Error),
+    #[error("Failed to parse glossary {}", .0)]
+    Toml(String, #[source] toml::de::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum TermError {
+    #[error("Glossary term {} not found", .0)]
+    NotFound(String),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RawTerm {
+    definition: String,
+    #[serde(default)]
+    expansion: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GlossaryTerm {
+    pub name: String,
+    pub slug: String,
+    pub url: Option<String>,
+    pub definition: String,
+    pub expansion: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Glossary {
+    terms: Vec<GlossaryTerm>,
+}
+
+impl Glossary {
+    pub fn load(
+        fs: &dyn SiteFs,
+        path: &Path,
+        base_url: Option<&str>,
+    ) -> Result<Self, GlossaryError> {
+        let display = path.display().to_string();
+        if !fs.is_file(path) {
+            return Ok(Self::default());
+        }
+        let code = fs
+            .read_to_string(path)
+            .map_err(|error| GlossaryError::Io(display.clone(), error))?;
+        let raw: BTreeMap<String, RawTerm> = toml::from_str(&code)
+            .map_err(|error| GlossaryError::Toml(display, error))?;
+        let mut terms: Vec<_> = raw
+            .into_iter()
+            .map(|(name, raw)| {
+                let slug = slugify(&name);
+                let url = base_url.map(|base_url| {
+                    format!(
+                        "/{}/#{ANCHOR_PREFIX}{slug}",
+                        base_url.trim_matches('/')
+                    )
+                });
+                GlossaryTerm {
+                    name,
+                    slug,
+                    url,
+                    definition: raw.definition,
+                    expansion: raw.expansion,
+                    aliases: raw.aliases,
+                }
+            })
+            .collect();
+        terms.sort_by_cached_key(|term| term.name.to_lowercase());
+        Ok(Self { terms })
+    }
+
+    pub fn terms(&self) -> &[GlossaryTerm] {
+        &self.terms
+    }
+
+    pub fn get(&self, name: &str) -> Option<&GlossaryTerm> {
+        self.terms.iter().find(|term| {
+            term.name == name || term.aliases.iter().any(|alias| alias == name)
+        })
+    }
+
+    pub fn surface_forms(&self) -> Vec<(String, String)> {
+        let mut forms: Vec<_> = self
+            .terms
+            .iter()
+            .flat_map(|term| {
+                [&term.name]
+                    .into_iter()
+                    .chain(&term.aliases)
+                    .map(|form| (form.clone(), term.name.clone()))
+            })
+            .collect();
+        forms.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then(a.cmp(b)));
+        forms
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TermArgs<'a> {
+    name: &'a str,
+    text: Option<&'a str>,
+}
+
+impl<'a> Args<'a> for TermArgs<'a> {
+    const POSITIONAL: &'static [&'static str] = &["name"];
+
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let name = args.retrive_arg("name")?;
+        let text = args.retrive_arg_with_default("text", || None)?;
+        Ok(Self { name, text })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TermFn {
+    glossary: Arc<Glossary>,
+}
+
+impl TermFn {
+    pub fn new(glossary: Arc<Glossary>) -> Self {
+        Self { glossary }
+    }
+}
+
+impl Function for TermFn {
+    type Args<'a> = TermArgs<'a>;
+    type Output = String;
+    type Error = TermError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let term = self
+            .glossary
+            .get(args.name)
+            .ok_or_else(|| TermError::NotFound(args.name.to_owned()))?;
+        let text = escape_html(args.text.unwrap_or(args.name));
+        let definition = escape_html(&term.definition);
+        let (inner, title) = match &term.expansion {
+            Some(expansion) => (
+                format!(
+                    "<abbr title=\"{}\">{text}</abbr>",
+                    escape_html(expansion)
+                ),
+                None,
+            ),
+            None => (text, Some(definition)),
+        };
+        let title = title
+            .map(|title| format!(" title=\"{title}\""))
+            .unwrap_or_default();
+        Ok(match &term.url {
+            Some(url) => format!(
+                "<a class=\"term\" href=\"{}\"{title}>{inner}</a>",
+                escape_html(url)
+            ),
+            None => format!("<span class=\"term\"{title}>{inner}</span>"),
+        })
+    }
+
+    fn doc(&self) -> String {
+        "{# glossary term, linked to its definition on the glossary page #}
+        term(
+            {# term name or alias, as in the glossary file #}
+            name:string,
+            {# text shown instead of the name #}
+            text:string?
+        ) -> String "
+            .to_owned()
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,109 @@
+use std::{error::Error, fmt, path::Path, sync::Arc};
+
+use serde_json::{Map, Value};
+use tera::Context;
+use thiserror::Error;
+
+use crate::Config;
+
+#[derive(Debug, Error)]
+#[error("Build hook {} failed", .hook)]
+pub struct HookError {
+    hook: String,
+    #[source]
+    source: Box<dyn Error + Send + Sync>,
+}
+
+impl HookError {
+    pub fn new(
+        hook: impl Into<String>,
+        source: impl Into<Box<dyn Error + Send + Sync>>,
+    ) -> Self {
+        Self { hook: hook.into(), source: source.into() }
+    }
+}
+
+pub trait BuildHook: Send + Sync + 'static {
+    fn name(&self) -> &str;
+
+    fn before_build(&self, _config: &Config) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    fn after_page_compile(
+        &self,
+        _source: &Path,
+        _context: &mut Map<String, Value>,
+    ) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    fn after_render(
+        &self,
+        _output: &Path,
+        _html: &mut String,
+    ) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    fn after_build(&self, _config: &Config) -> Result<(), HookError> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct BuildHooks {
+    hooks: Vec<Arc<dyn BuildHook>>,
+}
+
+impl BuildHooks {
+    pub fn register<H>(&mut self, hook: H)
+    where
+        H: BuildHook,
+    {
+        self.hooks.push(Arc::new(hook));
+    }
+
+    pub fn before_build(&self, config: &Config) -> Result<(), HookError> {
+        self.hooks.iter().try_for_each(|hook| hook.before_build(config))
+    }
+
+    pub fn after_page_compile(
+        &self,
+        source: &Path,
+        context: &mut Context,
+    ) -> Result<(), HookError> {
+        if self.hooks.is_empty() {
+            return Ok(());
+        }
+        let Value::Object(mut values) = context.clone().into_json() else {
+            return Ok(());
+        };
+        for hook in &self.hooks {
+            hook.after_page_compile(source, &mut values)?;
+        }
+        *context = Context::from_value(Value::Object(values))
+            .map_err(|error| HookError::new("after_page_compile", error))?;
+        Ok(())
+    }
+
+    pub fn after_render(
+        &self,
+        output: &Path,
+        html: &mut String,
+    ) -> Result<(), HookError> {
+        self.hooks.iter().try_for_each(|hook| hook.after_render(output, html))
+    }
+
+    pub fn after_build(&self, config: &Config) -> Result<(), HookError> {
+        self.hooks.iter().try_for_each(|hook| hook.after_build(config))
+    }
+}
+
+impl fmt::Debug for BuildHooks {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_list()
+            .entries(self.hooks.iter().map(|hook| hook.name()))
+            .finish()
+    }
+}
@@ -0,0 +1,142 @@
+use std::{collections::HashMap, io, path::Path, sync::Arc};
+
+use thiserror::Error;
+
+use crate::{
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+    site_fs::SiteFs,
+};
+
+#[derive(Debug, Error)]
+pub enum TranslationsError {
+    #[error("Failed to read translations {}", .0)]
+    Io(String, #[source] io::Error),
+    #[error("Failed to parse translations {}", .0)]
+    Toml(String, #[source] toml::de::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum TranslateError {
+    #[error("No language given and the site has no default language")]
+    NoLanguage,
+    #[error("Translation {} not found for language {}", .key, .lang)]
+    NotFound { lang: String, key: String },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    strings: HashMap<String, HashMap<String, String>>,
+}
+
+impl Translations {
+    pub fn load(
+        fs: &dyn SiteFs,
+        dir: &Path,
+        languages: &[String],
+    ) -> Result<Self, TranslationsError> {
+        let mut strings = HashMap::new();
+        for lang in languages {
+            let path = dir.join(format!("{lang}.toml"));
+            if !fs.is_file(&path) {
+                continue;
+            }
+            let display = path.display().to_string();
+            let code = fs.read_to_string(&path).map_err(|error| {
+                TranslationsError::Io(display.clone(), error)
+            })?;
+            let table: toml::Table = toml::from_str(&code)
+                .map_err(|error| TranslationsError::Toml(display, error))?;
+            let mut lang_strings = HashMap::new();
+            flatten(&mut lang_strings, "", table);
+            strings.insert(lang.clone(), lang_strings);
+        }
+        Ok(Self { strings })
+    }
+
+    pub fn get(&self, lang: &str, key: &str) -> Option<&str> {
+        self.strings.get(lang)?.get(key).map(String::as_str)
+    }
+}
+
+fn flatten(
+    strings: &mut HashMap<String, String>,
+    prefix: &str,
+    table: toml::Table,
+) {
+    for (key, value) in table {
+        let key =
+            if prefix.is_empty() { key } else { format!("{prefix}.{key}") };
+        match value {
+            toml::Value::Table(table) => flatten(strings, &key, table),
+            toml::Value::String(string) => {
+                strings.insert(key, string);
+            },
+            value => {
+                strings.insert(key, value.to_string());
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TranslateArgs<'a> {
+    key: &'a str,
+    lang: Option<&'a str>,
+}
+
+impl<'a> Args<'a> for TranslateArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let key = args.retrive_arg("key")?;
+        let lang = args.retrive_arg_with_default("lang", || None)?;
+        Ok(Self { key, lang })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TranslateFn {
+    translations: Arc<Translations>,
+}
+
+impl TranslateFn {
+    pub fn new(translations: Arc<Translations>) -> Self {
+        Self { translations }
+    }
+}
+
+impl Function for TranslateFn {
+    type Args<'a> = TranslateArgs<'a>;
+    type Output = String;
+    type Error = TranslateError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let lang = args
+            .lang
+            .or_else(|| ctx.metadata().get("lang")?.as_str())
+            .or_else(|| ctx.config()?.default_language())
+            .ok_or(TranslateError::NoLanguage)?;
+        let string =
+            self.translations.get(lang, args.key).ok_or_else(|| {
+                TranslateError::NotFound {
+                    lang: lang.to_owned(),
+                    key: args.key.to_owned(),
+                }
+            })?;
+        Ok(string.to_owned())
+    }
+
+    fn doc(&self) -> String {
+        "{# string from translations/<lang>.toml #}
+        t(
+            {# dotted key, e.g. \"nav.home\" #}
+            key:string,
+            {# language, defaults to the page's language #}
+            lang:string?
+        ) -> String "
+            .to_owned()
+    }
+}
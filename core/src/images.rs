@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use image::{imageops::FilterType, GenericImageView, ImageFormat};
+use thiserror::Error;
+
+use crate::{
+    asset::{self, AssetManifest, Fingerprinter, ASSET_URL_PREFIX},
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+    site_fs::SiteFs,
+    Config,
+};
+
+pub const DEFAULT_SIZES: &str = "100vw";
+
+#[derive(Debug, Error)]
+pub enum ImageError {
+    #[error("Image {} could not be accessed", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("Image {} could not be processed", .0.display())]
+    Image(PathBuf, #[source] image::ImageError),
+    #[error("Image {} is not a supported format", .0.display())]
+    UnsupportedFormat(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageVariant {
+    pub url: String,
+    pub width: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponsiveImage {
+    pub src: String,
+    pub width: u32,
+    pub height: u32,
+    pub variants: Vec<ImageVariant>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImagePipeline {
+    fs: Arc<dyn SiteFs>,
+    widths: Vec<u32>,
+    asset_dirs: Vec<PathBuf>,
+    output_dir: Arc<RwLock<PathBuf>>,
+    fingerprints: bool,
+    manifest: AssetManifest,
+    cache: Arc<RwLock<HashMap<String, ResponsiveImage>>>,
+}
+
+impl ImagePipeline {
+    pub fn new(config: &Config, manifest: AssetManifest) -> Self {
+        let mut widths = config.image_widths().to_vec();
+        widths.sort_unstable();
+        widths.dedup();
+        Self {
+            fs: config.shared_fs(),
+            widths,
+            asset_dirs: config.asset_dirs(),
+            output_dir: Arc::new(RwLock::new(
+                config.output_dir().join("assets"),
+            )),
+            fingerprints: config.asset_fingerprints(),
+            manifest,
+            cache: Arc::default(),
+        }
+    }
+
+    pub fn set_output_dir(&self, output_dir: &Path) {
+        *self.output_dir.write().unwrap_or_else(|error| error.into_inner()) =
+            output_dir.join("assets");
+    }
+
+    pub fn clear(&self) {
+        self.cache.write().unwrap_or_else(|error| error.into_inner()).clear();
+    }
+
+    pub fn process(
+        &self,
+        logical: &str,
+    ) -> Result<ResponsiveImage, ImageError> {
+        let logical = logical.trim_start_matches('/');
+        if let Some(cached) = self
+            .cache
+            .read()
+            .unwrap_or_else(|error| error.into_inner())
+            .get(logical)
+        {
+            return Ok(cached.clone());
+        }
+
+        let source_path =
+            asset::find_source(&*self.fs, &self.asset_dirs, logical)
+                .unwrap_or_else(|| self.asset_dirs[0].join(logical));
+        let format = ImageFormat::from_path(&source_path)
+            .map_err(|_| ImageError::UnsupportedFormat(source_path.clone()))?;
+        let bytes = self
+            .fs
+            .read(&source_path)
+            .map_err(|error| ImageError::Io(source_path.clone(), error))?;
+        let decoded = image::load_from_memory_with_format(&bytes, format)
+            .map_err(|error| ImageError::Image(source_path.clone(), error))?;
+        let (width, height) = decoded.dimensions();
+
+        let src = match self.manifest.get(logical) {
+            Some(fingerprinted) => format!("{ASSET_URL_PREFIX}{fingerprinted}"),
+            None => format!("{ASSET_URL_PREFIX}{logical}"),
+        };
+        let mut variants = Vec::new();
+        for &variant_width in self.widths.iter().filter(|&&w| w < width) {
+            let resized =
+                decoded.resize(variant_width, u32::MAX, FilterType::Triangle);
+            let mut encoded = Cursor::new(Vec::new());
+            resized.write_to(&mut encoded, format).map_err(|error| {
+                ImageError::Image(source_path.clone(), error)
+            })?;
+            let encoded = encoded.into_inner();
+            let variant_logical =
+                self.variant_logical(logical, variant_width, &encoded);
+            let variant_path = self
+                .output_dir
+                .read()
+                .unwrap_or_else(|error| error.into_inner())
+                .join(&variant_logical);
+            if let Some(parent) = variant_path.parent() {
+                self.fs.create_dir_all(parent).map_err(|error| {
+                    ImageError::Io(parent.to_owned(), error)
+                })?;
+            }
+            self.fs
+                .write(&variant_path, &encoded)
+                .map_err(|error| ImageError::Io(variant_path, error))?;
+            variants.push(ImageVariant {
+                url: format!("{ASSET_URL_PREFIX}{variant_logical}"),
+                width: variant_width,
+            });
+        }
+        variants.push(ImageVariant { url: src.clone(), width });
+
+        let processed = ResponsiveImage { src, width, height, variants };
+        self.cache
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(logical.to_owned(), processed.clone());
+        Ok(processed)
+    }
+
+    fn variant_logical(
+        &self,
+        logical: &str,
+        width: u32,
+        encoded: &[u8],
+    ) -> String {
+        let (directory, file_name) = match logical.rsplit_once('/') {
+            Some((directory, file_name)) => (Some(directory), file_name),
+            None => (None, logical),
+        };
+        let mut variant_name =
+            asset::fingerprinted_name(file_name, &format!("{width}w"));
+        if self.fingerprints {
+            let mut fingerprinter = Fingerprinter::new();
+            fingerprinter.write(encoded);
+            variant_name = asset::fingerprinted_name(
+                &variant_name,
+                &fingerprinter.finish(),
+            );
+        }
+        match directory {
+            Some(directory) => format!("{directory}/{variant_name}"),
+            None => variant_name,
+        }
+    }
+}
+
+pub fn render_img(
+    image: &ResponsiveImage,
+    alt: &str,
+    sizes: &str,
+) -> Result<String, std::fmt::Error> {
+    let mut buf = String::new();
+    write!(buf, "<img src=\"{}\"", tera::escape_html(&image.src))?;
+    if image.variants.len() > 1 {
+        write!(buf, " srcset=\"")?;
+        for (i, variant) in image.variants.iter().enumerate() {
+            if i > 0 {
+                write!(buf, ", ")?;
+            }
+            write!(
+                buf,
+                "{} {}w",
+                tera::escape_html(&variant.url),
+                variant.width
+            )?;
+        }
+        write!(buf, "\" sizes=\"{}\"", tera::escape_html(sizes))?;
+    }
+    write!(
+        buf,
+        " width=\"{}\" height=\"{}\" alt=\"{}\"/>",
+        image.width,
+        image.height,
+        tera::escape_html(alt),
+    )?;
+    Ok(buf)
+}
+
+pub fn asset_logical_path(url: &str) -> Option<&str> {
+    url.strip_prefix(ASSET_URL_PREFIX)
+        .filter(|logical| ImageFormat::from_path(Path::new(logical)).is_ok())
+}
+
+#[derive(Debug, Error)]
+pub enum ImageFnError {
+    #[error(transparent)]
+    Image(#[from] ImageError),
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        std::fmt::Error,
+    ),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ImageArgs<'a> {
+    path: &'a str,
+    alt: &'a str,
+    sizes: &'a str,
+}
+
+impl<'a> Args<'a> for ImageArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let path = args.retrive_arg("path")?;
+        let alt = args.retrive_arg_with_default("alt", || "")?;
+        let sizes = args.retrive_arg_with_default("sizes", || DEFAULT_SIZES)?;
+        Ok(Self { path, alt, sizes })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageFn {
+    pipeline: ImagePipeline,
+}
+
+impl ImageFn {
+    pub fn new(pipeline: ImagePipeline) -> Self {
+        Self { pipeline }
+    }
+}
+
+impl Function for ImageFn {
+    type Args<'a> = ImageArgs<'a>;
+    type Output = String;
+    type Error = ImageFnError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let image = self.pipeline.process(args.path)?;
+        Ok(render_img(&image, args.alt, args.sizes)?)
+    }
+
+    fn doc(&self) -> String {
+        "{# responsive <img> markup with resized variants #}
+        image(
+            {# path relative to the asset directory #}
+            path:string,
+            {# alternative text #}
+            alt:string?,
+            {# sizes attribute, default 100vw #}
+            sizes:string?
+        ) -> String "
+            .to_owned()
+    }
+}
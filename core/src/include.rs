@@ -0,0 +1,109 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::site_fs::SiteFs;
+
+pub const DIRECTIVE_NAME: &str = "include_md";
+
+#[derive(Debug, Error)]
+pub enum IncludeError {
+    #[error("Could not read included file {}", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("Include cycle: {}", .0)]
+    Cycle(String),
+    #[error("Malformed {}(path=\"...\") call: {}", DIRECTIVE_NAME, .0)]
+    Malformed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Directive<'a> {
+    path: &'a str,
+    len: usize,
+}
+
+pub fn expand(
+    fs: &dyn SiteFs,
+    code: &str,
+    partial_dir: &Path,
+) -> Result<String, IncludeError> {
+    let mut stack = Vec::new();
+    expand_nested(fs, code, partial_dir, &mut stack)
+}
+
+fn expand_nested(
+    fs: &dyn SiteFs,
+    code: &str,
+    partial_dir: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<String, IncludeError> {
+    let mut output = String::with_capacity(code.len());
+    let mut rest = code;
+    while let Some(start) = rest.find("{{") {
+        let Some(directive) = parse_directive(&rest[start ..])? else {
+            output.push_str(&rest[.. start + "{{".len()]);
+            rest = &rest[start + "{{".len() ..];
+            continue;
+        };
+        output.push_str(&rest[.. start]);
+        rest = &rest[start + directive.len ..];
+
+        let path = partial_dir.join(directive.path.trim_start_matches('/'));
+        if stack.contains(&path) {
+            let cycle = stack
+                .iter()
+                .chain([&path])
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            Err(IncludeError::Cycle(cycle))?;
+        }
+        let included = fs
+            .read_to_string(&path)
+            .map_err(|error| IncludeError::Io(path.clone(), error))?;
+        stack.push(path);
+        output.push_str(&expand_nested(fs, &included, partial_dir, stack)?);
+        stack.pop();
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn parse_directive(code: &str) -> Result<Option<Directive<'_>>, IncludeError> {
+    let inner = code["{{".len() ..].trim_start();
+    let Some(after_name) =
+        inner.strip_prefix(DIRECTIVE_NAME).filter(|after_name| {
+            !after_name
+                .starts_with(|ch: char| ch.is_alphanumeric() || ch == '_')
+        })
+    else {
+        return Ok(None);
+    };
+    let malformed = || {
+        IncludeError::Malformed(code.lines().next().unwrap_or_default().into())
+    };
+    let args = after_name
+        .trim_start()
+        .strip_prefix('(')
+        .and_then(|args| args.trim_start().strip_prefix("path"))
+        .and_then(|args| args.trim_start().strip_prefix('='))
+        .map(str::trim_start)
+        .ok_or_else(malformed)?;
+    let quote = args
+        .chars()
+        .next()
+        .filter(|&ch| ch == '"' || ch == '\'')
+        .ok_or_else(malformed)?;
+    let (path, after_path) =
+        args[1 ..].split_once(quote).ok_or_else(malformed)?;
+    let after_call = after_path
+        .trim_start()
+        .strip_prefix(')')
+        .map(str::trim_start)
+        .and_then(|after_call| after_call.strip_prefix("}}"))
+        .ok_or_else(malformed)?;
+    Ok(Some(Directive { path, len: code.len() - after_call.len() }))
+}
@@ -0,0 +1,86 @@
+use std::{collections::HashMap, fmt::Write as _};
+
+use serde_json::Value;
+
+use crate::markdown::to_latex::{escape, html_to_text};
+
+pub const DOCUMENT_CLASS: &str = "book";
+
+pub const PACKAGES: &[&str] = &["fontspec", "graphicx", "expex", "hyperref"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    pub title: String,
+    pub body: String,
+}
+
+// The preamble relies on fontspec, so the output is meant for XeLaTeX or
+// LuaLaTeX, which take IPA and other scripts straight from the sources.
+pub fn document(font: Option<&str>, chapters: &[Chapter]) -> String {
+    let mut buf = String::new();
+    let _ = writeln!(buf, "\\documentclass{{{DOCUMENT_CLASS}}}");
+    for package in PACKAGES {
+        let _ = writeln!(buf, "\\usepackage{{{package}}}");
+    }
+    if let Some(font) = font {
+        let _ = writeln!(buf, "\\setmainfont{{{font}}}");
+    }
+    let _ = writeln!(buf, "\\begin{{document}}");
+    let _ = writeln!(buf, "\\tableofcontents");
+    for chapter in chapters {
+        let _ = write!(
+            buf,
+            "\n\\chapter{{{}}}\n\n{}",
+            escape(&chapter.title),
+            chapter.body.trim_end()
+        );
+        buf.push('\n');
+    }
+    let _ = writeln!(buf, "\\end{{document}}");
+    buf
+}
+
+// Stands in for functions and filters without a LaTeX rendering of their
+// own, keeping the text of their output. HTML output loses its markup.
+pub struct LatexFallback<T> {
+    inner: T,
+    html: bool,
+}
+
+impl<T> LatexFallback<T> {
+    pub fn new(inner: T, html: bool) -> Self {
+        Self { inner, html }
+    }
+
+    fn convert(&self, value: Value) -> Value {
+        match value {
+            Value::String(html) if self.html => {
+                escape(&html_to_text(&html)).into()
+            },
+            Value::String(text) => escape(&text).into(),
+            value => value,
+        }
+    }
+}
+
+impl<T> tera::Function for LatexFallback<T>
+where
+    T: tera::Function,
+{
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        self.inner.call(args).map(|value| self.convert(value))
+    }
+}
+
+impl<T> tera::Filter for LatexFallback<T>
+where
+    T: tera::Filter,
+{
+    fn filter(
+        &self,
+        value: &Value,
+        args: &HashMap<String, Value>,
+    ) -> tera::Result<Value> {
+        self.inner.filter(value, args).map(|value| self.convert(value))
+    }
+}
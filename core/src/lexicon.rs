@@ -0,0 +1,207 @@
+use std::{path::Path, sync::Arc};
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::{
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+    site_fs::SiteFs,
+};
+
+#[derive(Debug, Error)]
+pub enum LexiconError {
+    #[error("Failed to read lexicon {}", .0)]
+    Csv(String, #[source] csv::Error),
+    #[error("Lexicon {} has no header row", .0)]
+    MissingHeader(String),
+}
+
+#[derive(Debug, Error)]
+pub enum LexError {
+    #[error("Lexical entry {} not found", .0)]
+    NotFound(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Lexicon {
+    key_column: String,
+    entries: Vec<Map<String, Value>>,
+}
+
+impl Lexicon {
+    pub fn load(fs: &dyn SiteFs, path: &Path) -> Result<Self, LexiconError> {
+        let display = path.display().to_string();
+        if !fs.is_file(path) {
+            return Ok(Self::default());
+        }
+        let bytes = fs.read(path).map_err(|error| {
+            LexiconError::Csv(display.clone(), csv::Error::from(error))
+        })?;
+        let delimiter = if path.extension().is_some_and(|ext| ext == "tsv") {
+            b'\t'
+        } else {
+            b','
+        };
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .from_reader(&bytes[..]);
+        let headers = reader
+            .headers()
+            .map_err(|error| LexiconError::Csv(display.clone(), error))?
+            .clone();
+        let Some(key_column) = headers.get(0).map(ToOwned::to_owned) else {
+            Err(LexiconError::MissingHeader(display))?
+        };
+
+        let mut entries = Vec::new();
+        for result in reader.records() {
+            let record = result
+                .map_err(|error| LexiconError::Csv(display.clone(), error))?;
+            let entry = headers
+                .iter()
+                .zip(record.iter())
+                .map(|(column, cell)| {
+                    (column.to_owned(), Value::from(cell.to_owned()))
+                })
+                .collect();
+            entries.push(entry);
+        }
+
+        Ok(Self { key_column, entries })
+    }
+
+    pub fn key_column(&self) -> &str {
+        &self.key_column
+    }
+
+    pub fn entries(&self) -> &[Map<String, Value>] {
+        &self.entries
+    }
+
+    pub fn entry_key<'a>(&self, entry: &'a Map<String, Value>) -> &'a str {
+        entry.get(&self.key_column).and_then(Value::as_str).unwrap_or_default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Map<String, Value>> {
+        self.entries.iter().find(|entry| self.entry_key(entry) == key)
+    }
+
+    pub fn filter<'s>(
+        &'s self,
+        filters: &'s [(&str, &Value)],
+    ) -> impl Iterator<Item = &'s Map<String, Value>> + 's {
+        self.entries.iter().filter(move |entry| {
+            filters.iter().all(|(column, expected)| {
+                entry.get(*column).and_then(Value::as_str).is_some_and(
+                    |actual| match expected.as_str() {
+                        Some(expected) => actual == expected,
+                        None => serde_json::from_str::<Value>(actual)
+                            .is_ok_and(|actual| actual == **expected),
+                    },
+                )
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LexArgs<'a> {
+    entry: &'a str,
+}
+
+impl<'a> Args<'a> for LexArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let entry = args.retrive_arg("entry")?;
+        Ok(Self { entry })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LexFn {
+    lexicon: Arc<Lexicon>,
+}
+
+impl LexFn {
+    pub fn new(lexicon: Arc<Lexicon>) -> Self {
+        Self { lexicon }
+    }
+}
+
+impl Function for LexFn {
+    type Args<'a> = LexArgs<'a>;
+    type Output = Value;
+    type Error = LexError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let entry = self
+            .lexicon
+            .get(args.entry)
+            .ok_or_else(|| LexError::NotFound(args.entry.to_owned()))?;
+        Ok(Value::Object(entry.clone()))
+    }
+
+    fn doc(&self) -> String {
+        "{# lexicon row whose first column matches entry #}
+        lex(
+            {# key of the entry #}
+            entry:string
+        ) -> Object "
+            .to_owned()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexFilterArgs<'a> {
+    filters: Vec<(&'a str, &'a Value)>,
+}
+
+impl<'a> Args<'a> for LexFilterArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let mut filters = args.retrive_rest();
+        filters.sort_by_key(|(column, _)| *column);
+        Ok(Self { filters })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LexFilterFn {
+    lexicon: Arc<Lexicon>,
+}
+
+impl LexFilterFn {
+    pub fn new(lexicon: Arc<Lexicon>) -> Self {
+        Self { lexicon }
+    }
+}
+
+impl Function for LexFilterFn {
+    type Args<'a> = LexFilterArgs<'a>;
+    type Output = Value;
+    type Error = LexError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        Ok(self
+            .lexicon
+            .filter(&args.filters)
+            .map(|entry| Value::Object(entry.clone()))
+            .collect())
+    }
+
+    fn doc(&self) -> String {
+        "{# lexicon rows whose columns match every given argument #}
+        lex_filter(
+            {# any column name, e.g. pos=\"noun\" #}
+            column:any*
+        ) -> Array "
+            .to_owned()
+    }
+}
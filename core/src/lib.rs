@@ -1,8 +1,112 @@
-pub use config::Config;
-pub use function::{Arg, ArgError, ArgParser, Args, Function};
-pub use ssg::{InitError, LinSsg,BuildError};
+pub use asset::{AssetError, AssetProcessError, AssetProcessor, ScssProcessor};
+pub use audio::AudioError;
+pub use backend::{ContentBackend, ContentWarning, HtmlBackend, RenderCtx};
+pub use breadcrumb::Breadcrumb;
+pub use bundle::BundleError;
+pub use config::{Book, Config, ConfigError, NonPagePolicy, OutputFormat};
+pub use csv_table::CsvTableError;
+pub use data::DataError;
+pub use diagnostic::{
+    Diagnostic,
+    DiagnosticsSink,
+    JsonDiagnostics,
+    Severity,
+    SourceSpan,
+};
+pub use dry_run::BuildDiff;
+pub use function::{
+    invoke_filter,
+    invoke_fn,
+    invoke_tester,
+    positional_key,
+    Arg,
+    ArgError,
+    ArgParser,
+    Args,
+    Filter,
+    Function,
+    InvokeError,
+    Tester,
+    INPUT_ARG,
+    POSITIONAL_PREFIX,
+};
+pub use glossary::{Glossary, GlossaryError, GlossaryTerm, TermError};
+pub use hook::{BuildHook, HookError};
+pub use i18n::{TranslateError, Translations, TranslationsError};
+pub use images::{ImageError, ImageFnError};
+pub use include::IncludeError;
+pub use lexicon::{LexError, Lexicon, LexiconError};
+pub use markdown::{
+    backend::{MarkdownBackend, MarkdownDocument, MarkdownError},
+    to_html::{
+        HtmlClassConfig,
+        SectionWrapper,
+        ToHtmlOptions,
+        UnsupportedPolicy,
+    },
+    to_latex::{escape as escape_latex, ToLatexError},
+};
+pub use menu::{MenuError, MenuItem};
+pub use observer::{BuildEvent, BuildObserver, BuildPhase};
+pub use pack::{Pack, PackConfig, PackError, PackRegistry};
+pub use page_ctx::{CurrentPage, PageCtx};
+pub use reference::{ItemKind, Reference, ReferenceItem};
+pub use route::{PageRoute, RouteError, TranslationLink};
+pub use search::{SearchEntry, SearchHeading};
+pub use site::SiteConfigError;
+pub use site_fs::{FileKind, MemoryFs, RealFs, SiteFs};
+pub use ssg::{BuildError, BuildErrorKind, BuildWarning, InitError, LinSsg};
+pub use tera::escape_html;
+pub use theme::scaffold;
+pub use transform::{Transform, TransformError};
+pub use typography::{strip_verbatim, verbatim, VERBATIM_END, VERBATIM_START};
+pub use wiki::{Backlink, WikiError, WikiIndex};
 
+mod asset;
+mod audio;
+mod backend;
+mod breadcrumb;
+mod bundle;
+mod clock;
+mod csv_table;
+mod data;
+mod diagnostic;
+mod dictionary;
+mod dry_run;
+mod epub;
+mod external;
 mod function;
+mod glossary;
+mod hook;
+mod i18n;
+mod images;
+mod include;
+mod latex;
+mod lexicon;
 mod markdown;
+mod menu;
+mod minify;
+mod observer;
+mod output;
+mod pack;
+mod page_ctx;
+mod reference;
+mod route;
 mod config;
+mod search;
+mod shortcode;
+mod site;
+mod site_fs;
+mod skip_link;
+mod social;
+mod summary;
+mod template_check;
+mod theme;
+mod transform;
+mod typography;
 mod ssg;
+mod walk;
+mod wiki;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,192 @@
+use std::{ops::Range, path::Path, rc::Rc};
+
+use markdown::{mdast, message::Place};
+use thiserror::Error;
+
+use super::{
+    shortcode::{self, ParsedShortcode, ShortcodeError},
+    to_html::{ToHtml, ToHtmlCtx, ToHtmlError, ToHtmlOptions},
+};
+use crate::backend::{has_extension, ContentBackend, RenderCtx};
+
+#[derive(Debug, Error)]
+#[error("{}", .message)]
+pub struct MdParseError {
+    message: markdown::message::Message,
+    span: Option<Range<usize>>,
+}
+
+impl MdParseError {
+    fn locate(mut self, source_map: &SourceMap) -> Self {
+        self.span = self.message.place.as_deref().map(|place| match place {
+            Place::Position(position) => source_map
+                .map_range(position.start.offset .. position.end.offset),
+            Place::Point(point) => {
+                source_map.map_range(point.offset .. point.offset)
+            },
+        });
+        self
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MarkdownError {
+    #[error(transparent)]
+    Parse(#[from] MdParseError),
+    #[error(transparent)]
+    Shortcode(#[from] ShortcodeError),
+    #[error(transparent)]
+    ToHtml(#[from] ToHtmlError),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    replacements: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl SourceMap {
+    fn new(content: &str, calls: &[shortcode::ShortcodeCall]) -> Self {
+        let mut replacements: Vec<_> = calls
+            .iter()
+            .enumerate()
+            .filter_map(|(index, call)| {
+                let placeholder = shortcode::placeholder(index);
+                let start = content.find(&placeholder)?;
+                Some((start .. start + placeholder.len(), call.source.clone()))
+            })
+            .collect();
+        replacements.sort_by_key(|(output, _)| output.start);
+        Self { replacements }
+    }
+
+    fn map(&self, offset: usize, is_end: bool) -> usize {
+        self.replacements
+            .iter()
+            .rev()
+            .find(|(output, _)| output.start <= offset)
+            .map_or(offset, |(output, source)| {
+                if offset >= output.end {
+                    source.end + (offset - output.end)
+                } else if is_end && offset > output.start {
+                    source.end
+                } else {
+                    source.start
+                }
+            })
+    }
+
+    fn map_range(&self, range: Range<usize>) -> Range<usize> {
+        let start = self.map(range.start, false);
+        start .. self.map(range.end, true).max(start)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MarkdownDocument {
+    pub ast: mdast::Node,
+    pub shortcodes: Rc<[ParsedShortcode]>,
+    source_map: SourceMap,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownBackend {
+    extensions: Vec<String>,
+}
+
+impl MarkdownBackend {
+    pub fn new(extensions: &[String]) -> Self {
+        Self { extensions: extensions.to_vec() }
+    }
+}
+
+impl ContentBackend for MarkdownBackend {
+    type Document = MarkdownDocument;
+    type Error = MarkdownError;
+
+    fn name(&self) -> &str {
+        "markdown"
+    }
+
+    fn is_source(&self, path: &Path) -> bool {
+        has_extension(path, &self.extensions)
+    }
+
+    fn parse(
+        &self,
+        source: &str,
+        options: &ToHtmlOptions,
+    ) -> Result<Self::Document, Self::Error> {
+        let mut calls = Vec::new();
+        let content =
+            shortcode::extract(source, &options.shortcodes, &mut calls)?;
+        let source_map = SourceMap::new(&content, &calls);
+        let ast = parse_markdown(&content)
+            .map_err(|error| error.locate(&source_map))?;
+        let shortcodes = calls
+            .into_iter()
+            .map(|call| {
+                let ast = parse_markdown(&call.body)?;
+                Ok(ParsedShortcode { call, ast })
+            })
+            .collect::<Result<_, MdParseError>>()?;
+        Ok(MarkdownDocument { ast, shortcodes, source_map })
+    }
+
+    fn render(
+        &self,
+        document: &Self::Document,
+        buf: &mut String,
+        ctx: &mut RenderCtx,
+    ) -> Result<(), Self::Error> {
+        let mut to_html_ctx =
+            ToHtmlCtx::new(ctx.options().clone(), document.shortcodes.clone());
+        if ctx.marks_summary() {
+            to_html_ctx.mark_summary();
+        }
+        document.ast.to_html(buf, &mut to_html_ctx).map_err(|error| {
+            error.relocate(|span| document.source_map.map_range(span))
+        })?;
+        for wiki_link in to_html_ctx.wiki_links() {
+            ctx.add_wiki_link(wiki_link.clone());
+        }
+        for node in to_html_ctx.unsupported() {
+            ctx.add_unsupported(node.clone());
+        }
+        for warning in to_html_ctx.warnings() {
+            let span =
+                warning.span().map(|span| document.source_map.map_range(span));
+            ctx.add_warning(warning.clone().with_span(span));
+        }
+        Ok(())
+    }
+
+    fn word_count(&self, document: &Self::Document) -> usize {
+        count_words(&document.ast)
+    }
+
+    fn error_span(&self, error: &Self::Error) -> Option<Range<usize>> {
+        match error {
+            MarkdownError::Parse(error) => error.span.clone(),
+            MarkdownError::ToHtml(error) => error.span(),
+            MarkdownError::Shortcode(_) => None,
+        }
+    }
+}
+
+fn count_words(node: &mdast::Node) -> usize {
+    match node {
+        mdast::Node::Text(text) => text.value.split_whitespace().count(),
+        mdast::Node::InlineCode(code) => code.value.split_whitespace().count(),
+        mdast::Node::Code(code) => code.value.split_whitespace().count(),
+        _ => node
+            .children()
+            .map_or(0, |children| children.iter().map(count_words).sum()),
+    }
+}
+
+fn parse_markdown(content: &str) -> Result<mdast::Node, MdParseError> {
+    let options = markdown::ParseOptions::default();
+    let ast = markdown::to_mdast(content, &options)
+        .map_err(|message| MdParseError { message, span: None })?;
+    Ok(ast)
+}
@@ -1,3 +1,6 @@
+pub mod backend;
 pub mod to_html;
+pub mod to_latex;
 pub mod slugify;
 pub mod page;
+pub mod shortcode;
@@ -1,21 +1,65 @@
-use super::to_html::{ToHtml, ToHtmlCtx, ToHtmlError};
-use markdown::mdast;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Write as _},
+    ops::Range,
+};
+
+use super::{
+    backend::MarkdownBackend,
+    to_html::{SectionWrapper, ToHtmlOptions},
+};
+
+use crate::{
+    backend::{ContentBackend, ContentWarning, RenderCtx},
+    search,
+    skip_link,
+    social,
+};
 use thiserror::Error;
 
 pub const METADATA_TERMINATOR: &str = "+++";
 
+pub const DEFAULTS_FILE_NAME: &str = "_defaults.toml";
+
+pub const RESERVED_BLOCKS: &[&str] = &["title", "content"];
+
+pub const WORDS_PER_MINUTE: usize = 200;
+
 #[derive(Debug, Error)]
-#[error("{}", .message)]
-pub struct MdParseError {
-    message: markdown::message::Message,
+#[error("{}", .error)]
+pub struct BackendError {
+    error: Box<dyn Error + Send + Sync>,
+    span: Option<Range<usize>>,
+}
+
+impl BackendError {
+    fn new<B>(backend: &B, error: B::Error, offset: usize) -> Self
+    where
+        B: ContentBackend,
+    {
+        let span = backend
+            .error_span(&error)
+            .map(|span| span.start + offset .. span.end + offset);
+        Self { error: error.into(), span }
+    }
+
+    fn unlocated<E>(error: E) -> Self
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        Self { error: error.into(), span: None }
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum ParseError {
     #[error(transparent)]
-    Md(#[from] MdParseError),
+    Backend(#[from] BackendError),
     #[error(transparent)]
     Toml(#[from] toml::de::Error),
+    #[error("Invalid page block name {:?}", .0)]
+    InvalidBlockName(String),
 }
 
 #[derive(Debug, Error)]
@@ -27,63 +71,199 @@ pub enum SplitError {
 #[derive(Debug, Error)]
 pub enum ExpandError {
     #[error(transparent)]
-    ToHtml(#[from] ToHtmlError),
+    Backend(#[from] BackendError),
     #[error(transparent)]
     Json(#[from] serde_json::Error),
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
 }
 
 #[derive(Debug, Error)]
 pub enum CompileError {
     #[error(transparent)]
-    SplitError(#[from] SplitError),
+    Split(#[from] SplitError),
     #[error(transparent)]
-    ParseError(#[from] ParseError),
+    Parse(#[from] ParseError),
     #[error(transparent)]
-    ExpandError(#[from] ExpandError),
+    Expand(#[from] ExpandError),
+}
+
+impl CompileError {
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Self::Parse(ParseError::Toml(error)) => error.span(),
+            Self::Parse(ParseError::Backend(error))
+            | Self::Expand(ExpandError::Backend(error)) => error.span.clone(),
+            _ => None,
+        }
+    }
 }
 
-pub fn compile(code: &str) -> Result<Page, CompileError> {
-    let raw_parts = RawPageParts::split(&code)?;
-    let parts = raw_parts.parse()?;
-    let expanded = parts.expand()?;
+pub fn compile<B>(
+    code: &str,
+    defaults: &toml::Table,
+    backend: &B,
+    options: &ToHtmlOptions,
+) -> Result<Page, CompileError>
+where
+    B: ContentBackend,
+{
+    let raw_parts = RawPageParts::split(code)?;
+    let parts = raw_parts.parse(defaults, backend, options)?;
+    let expanded = parts.expand(backend, options)?;
     Ok(expanded)
 }
 
 #[derive(Debug, Clone)]
 pub struct Page {
     pub template: String,
+    pub body: String,
+    pub formats: Option<Vec<String>>,
     pub base_context: tera::Context,
+    pub permalink: Option<String>,
+    pub slug: Option<String>,
+    pub lang: Option<String>,
+    pub translation_key: Option<String>,
+    pub aliases: Vec<String>,
+    pub wiki_links: Vec<String>,
+    pub unsupported: Vec<String>,
+    pub warnings: Vec<ContentWarning>,
+}
+
+pub fn extend_layout(
+    layout: &str,
+    body: &str,
+) -> Result<String, serde_json::Error> {
+    Ok(format!("{{% extends {} %}}{body}", tera::to_value(layout)?))
 }
 
 #[derive(Debug, Clone)]
-pub struct PageParts {
+pub struct PageParts<D> {
     pub metadata: Metadata,
-    pub ast: mdast::Node,
+    pub document: D,
+    pub content_offset: usize,
+    pub blocks: BTreeMap<String, ParsedBlock>,
 }
 
-impl PageParts {
-    pub fn expand(&self) -> Result<Page, ExpandError> {
+#[derive(Debug, Clone)]
+pub enum ParsedBlock {
+    Html(String),
+    Markdown(<MarkdownBackend as ContentBackend>::Document),
+}
+
+impl<D> PageParts<D> {
+    pub fn expand<B>(
+        &self,
+        backend: &B,
+        options: &ToHtmlOptions,
+    ) -> Result<Page, ExpandError>
+    where
+        B: ContentBackend<Document = D>,
+    {
         let mut content = String::new();
-        let mut to_html_ctx = ToHtmlCtx::default();
-        self.ast.to_html(&mut content, &mut to_html_ctx)?;
+        let mut page_options = options.clone();
+        if let Some(heading_offset) = self.metadata.heading_offset {
+            page_options.heading_offset = heading_offset;
+        }
+        if let Some(sections) = self.metadata.sections {
+            page_options.section_wrapper = sections;
+        }
+        let mut render_ctx = RenderCtx::new(page_options.clone());
+        if self.metadata.summary.is_none() {
+            render_ctx.mark_summary();
+        }
+        if options.skip_link {
+            content.push_str(&skip_link::target());
+        }
+        if options.search_markers {
+            content.push_str(search::CONTENT_START);
+        }
+        backend.render(&self.document, &mut content, &mut render_ctx).map_err(
+            |error| BackendError::new(backend, error, self.content_offset),
+        )?;
+        let mut warnings: Vec<_> = render_ctx
+            .warnings()
+            .iter()
+            .map(|warning| {
+                let span = warning.span().map(|span| {
+                    span.start + self.content_offset
+                        .. span.end + self.content_offset
+                });
+                warning.clone().with_span(span)
+            })
+            .collect();
+        if options.search_markers {
+            content.push_str(search::CONTENT_END);
+        }
         let mut context = tera::Context::new();
         context.insert("layout", &self.metadata.layout);
         context.insert("title", &self.metadata.title);
-        let template = format!(
-            concat!(
-                "{layout_start}{layout}{layout_end}",
-                "{title}",
-                "{content_start}{content}{content_end}",
-            ),
-            layout_start = "{% extends ",
-            layout = tera::to_value(&self.metadata.layout)?,
-            layout_end = " %}",
+        context.insert("description", &self.metadata.description);
+        context.insert("image", &self.metadata.image);
+        context.insert("summary", &self.metadata.summary);
+        context.insert("tags", &self.metadata.tags);
+        let word_count = backend.word_count(&self.document);
+        context.insert("word_count", &word_count);
+        context.insert("reading_time", &word_count.div_ceil(WORDS_PER_MINUTE));
+        let mut body = format!(
+            concat!("{title}", "{content_start}{content}{content_end}"),
             title = "{% block title %}{{ title }}{% endblock title %}",
             content_start = "{% block content %}",
             content = content,
             content_end = "{% endblock content %}",
         );
-        Ok(Page { template, base_context: context })
+        for (name, block) in &self.blocks {
+            write!(body, "{{% block {name} %}}")?;
+            match block {
+                ParsedBlock::Html(html) => body.push_str(html),
+                ParsedBlock::Markdown(document) => {
+                    let mut block_ctx = RenderCtx::new(page_options.clone());
+                    MarkdownBackend::default()
+                        .render(document, &mut body, &mut block_ctx)
+                        .map_err(BackendError::unlocated)?;
+                    for wiki_link in block_ctx.wiki_links() {
+                        render_ctx.add_wiki_link(wiki_link.clone());
+                    }
+                    for node in block_ctx.unsupported() {
+                        render_ctx.add_unsupported(node.clone());
+                    }
+                    warnings.extend(
+                        block_ctx.warnings().iter().map(|warning| {
+                            ContentWarning::new(warning.message())
+                        }),
+                    );
+                },
+            }
+            write!(body, "{{% endblock {name} %}}")?;
+        }
+        if options.social_meta
+            && !self.blocks.contains_key(social::HEAD_META_BLOCK)
+        {
+            write!(
+                body,
+                "{{% block {name} %}}{{{{ super() }}}}{{{{ social_meta() \
+                 }}}}{{% endblock {name} %}}",
+                name = social::HEAD_META_BLOCK,
+            )?;
+        }
+        Ok(Page {
+            template: extend_layout(&self.metadata.layout, &body)?,
+            body,
+            formats: self.metadata.formats.clone(),
+            base_context: context,
+            permalink: self.metadata.permalink.clone(),
+            slug: self.metadata.slug.clone(),
+            lang: self.metadata.lang.clone(),
+            translation_key: self.metadata.translation_key.clone(),
+            aliases: self.metadata.aliases.clone(),
+            wiki_links: render_ctx.wiki_links().to_vec(),
+            unsupported: render_ctx.unsupported().to_vec(),
+            warnings,
+        })
     }
 }
 
@@ -91,6 +271,7 @@ impl PageParts {
 pub struct RawPageParts<'a> {
     pub metadata: &'a str,
     pub content: &'a str,
+    pub content_offset: usize,
 }
 
 impl<'a> RawPageParts<'a> {
@@ -109,31 +290,123 @@ impl<'a> RawPageParts<'a> {
             if line.trim() == METADATA_TERMINATOR {
                 let metadata = &code[.. start];
                 let content = &code[end ..];
-                break Ok(Self { metadata, content });
+                break Ok(Self { metadata, content, content_offset: end });
             }
 
             start = end;
         }
     }
 
-    pub fn parse(self) -> Result<PageParts, ParseError> {
-        let metadata = toml::from_str(self.metadata)?;
-        let options = markdown::ParseOptions::default();
-        let ast = markdown::to_mdast(self.content, &options)
-            .map_err(|message| MdParseError { message })?;
-        Ok(PageParts { metadata, ast })
+    pub fn parse<B>(
+        self,
+        defaults: &toml::Table,
+        backend: &B,
+        options: &ToHtmlOptions,
+    ) -> Result<PageParts<B::Document>, ParseError>
+    where
+        B: ContentBackend,
+    {
+        let mut metadata = defaults.clone();
+        merge_metadata(&mut metadata, toml::from_str(self.metadata)?);
+        let metadata: Metadata = metadata.try_into()?;
+        let document =
+            backend.parse(self.content, options).map_err(|error| {
+                BackendError::new(backend, error, self.content_offset)
+            })?;
+        let mut blocks = BTreeMap::new();
+        for (name, block) in &metadata.blocks {
+            if !is_block_name(name) {
+                Err(ParseError::InvalidBlockName(name.clone()))?;
+            }
+            let parsed = match block {
+                Block::Html(html) => ParsedBlock::Html(html.clone()),
+                Block::Markdown(markdown) => ParsedBlock::Markdown(
+                    MarkdownBackend::default()
+                        .parse(markdown, options)
+                        .map_err(BackendError::unlocated)?,
+                ),
+            };
+            blocks.insert(name.clone(), parsed);
+        }
+        Ok(PageParts {
+            metadata,
+            document,
+            content_offset: self.content_offset,
+            blocks,
+        })
     }
 }
 
+pub fn merge_metadata(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge_metadata(base, overrides)
+            },
+            (_, value) => {
+                base.insert(key, value);
+            },
+        }
+    }
+}
+
+fn is_block_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        && !RESERVED_BLOCKS.contains(&name)
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Metadata {
     #[serde(default = "Metadata::default_layout")]
     layout: String,
     title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    permalink: Option<String>,
+    #[serde(default)]
+    slug: Option<String>,
+    #[serde(default)]
+    lang: Option<String>,
+    #[serde(default)]
+    translation_key: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    formats: Option<Vec<String>>,
+    #[serde(default)]
+    heading_offset: Option<u8>,
+    #[serde(default)]
+    sections: Option<SectionWrapper>,
+    #[serde(default)]
+    blocks: BTreeMap<String, Block>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Block {
+    Html(String),
+    Markdown(String),
 }
 
 impl Metadata {
     fn default_layout() -> String {
         String::from("default.html")
     }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn heading_offset(&self) -> Option<u8> {
+        self.heading_offset
+    }
 }
@@ -0,0 +1,182 @@
+use std::{collections::HashSet, ops::Range};
+
+use markdown::mdast;
+use thiserror::Error;
+
+pub const PLACEHOLDER_START: &str = "<!--lin-ssg-shortcode:";
+pub const PLACEHOLDER_END: &str = "-->";
+
+#[derive(Debug, Error)]
+pub enum ShortcodeError {
+    #[error("Shortcode {} opened but never closed", .0)]
+    Unclosed(String),
+    #[error("Shortcode {} has unbalanced arguments", .0)]
+    UnbalancedArgs(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortcodeCall {
+    pub name: String,
+    pub args: String,
+    pub body: String,
+    pub source: Range<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedShortcode {
+    pub call: ShortcodeCall,
+    pub ast: mdast::Node,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OpeningTag<'a> {
+    name: &'a str,
+    args: &'a str,
+    len: usize,
+}
+
+pub fn placeholder(index: usize) -> String {
+    format!("\n\n{PLACEHOLDER_START}{index}{PLACEHOLDER_END}\n\n")
+}
+
+pub fn placeholder_index(html: &str) -> Option<usize> {
+    html.trim()
+        .strip_prefix(PLACEHOLDER_START)?
+        .strip_suffix(PLACEHOLDER_END)?
+        .parse()
+        .ok()
+}
+
+pub fn extract(
+    content: &str,
+    names: &HashSet<String>,
+    calls: &mut Vec<ShortcodeCall>,
+) -> Result<String, ShortcodeError> {
+    if names.is_empty() {
+        return Ok(content.to_owned());
+    }
+
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{%") {
+        let Some(opening) = parse_opening(&rest[start ..], names)? else {
+            output.push_str(&rest[.. start + "{%".len()]);
+            rest = &rest[start + "{%".len() ..];
+            continue;
+        };
+        output.push_str(&rest[.. start]);
+        let after_opening = &rest[start + opening.len ..];
+        let (body_len, closing_len) =
+            find_closing(after_opening, opening.name, names)?;
+        let body = &after_opening[.. body_len];
+        let source_start = content.len() - rest.len() + start;
+
+        let index = calls.len();
+        calls.push(ShortcodeCall {
+            name: opening.name.to_owned(),
+            args: opening.args.trim().to_owned(),
+            body: String::new(),
+            source: source_start .. source_start,
+        });
+        calls[index].body = extract(body, names, calls)?;
+
+        output.push_str(&placeholder(index));
+        rest = &after_opening[body_len + closing_len ..];
+        calls[index].source.end = content.len() - rest.len();
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn parse_opening<'a>(
+    code: &'a str,
+    names: &HashSet<String>,
+) -> Result<Option<OpeningTag<'a>>, ShortcodeError> {
+    let inner = &code["{%".len() ..];
+    let trimmed = inner.trim_start();
+    let name_len = trimmed
+        .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+        .unwrap_or(trimmed.len());
+    let name = &trimmed[.. name_len];
+    if !names.contains(name) {
+        return Ok(None);
+    }
+
+    let mut offset = code.len() - trimmed.len() + name_len;
+    let mut args = "";
+    let after_name = &code[offset ..];
+    if after_name.starts_with('(') {
+        let args_len = balanced_parens_len(after_name)
+            .ok_or_else(|| ShortcodeError::UnbalancedArgs(name.to_owned()))?;
+        args = &after_name[1 .. args_len - 1];
+        offset += args_len;
+    }
+
+    let after_args = &code[offset ..];
+    let trimmed_after = after_args.trim_start();
+    if !trimmed_after.starts_with("%}") {
+        return Ok(None);
+    }
+    offset += after_args.len() - trimmed_after.len() + "%}".len();
+    Ok(Some(OpeningTag { name, args, len: offset }))
+}
+
+fn find_closing(
+    code: &str,
+    name: &str,
+    names: &HashSet<String>,
+) -> Result<(usize, usize), ShortcodeError> {
+    let mut depth = 0usize;
+    let mut position = 0;
+
+    while let Some(found) = code[position ..].find("{%") {
+        let start = position + found;
+        if let Some(opening) = parse_opening(&code[start ..], names)? {
+            if opening.name == name {
+                depth += 1;
+            }
+            position = start + opening.len;
+        } else if let Some(closing_len) = closing_len(&code[start ..], name) {
+            if depth == 0 {
+                return Ok((start, closing_len));
+            }
+            depth -= 1;
+            position = start + closing_len;
+        } else {
+            position = start + "{%".len();
+        }
+    }
+
+    Err(ShortcodeError::Unclosed(name.to_owned()))
+}
+
+fn closing_len(code: &str, name: &str) -> Option<usize> {
+    let inner = code["{%".len() ..].trim_start();
+    let after_name = inner.strip_prefix("end")?.strip_prefix(name)?;
+    let trimmed = after_name.trim_start();
+    trimmed.strip_prefix("%}")?;
+    Some(code.len() - trimmed.len() + "%}".len())
+}
+
+fn balanced_parens_len(code: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (i, ch) in code.char_indices() {
+        match quote {
+            Some(open) if ch == open => quote = None,
+            Some(_) => (),
+            None if ch == '"' || ch == '\'' || ch == '`' => quote = Some(ch),
+            None if ch == '(' => depth += 1,
+            None if ch == ')' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            },
+            None => (),
+        }
+    }
+    None
+}
@@ -1,15 +1,27 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::{self, Write as _},
+    mem,
+    ops::Range,
+    rc::Rc,
 };
 
-use markdown::mdast;
+use markdown::{mdast, unist::Position};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use super::slugify::{Slugify, SlugifyError};
+use super::{
+    shortcode::{self, ParsedShortcode},
+    slugify::{Slugify, SlugifyError},
+};
+use crate::{backend::ContentWarning, images, summary, wiki, Config};
 
 pub const TEMPLATE_BLOCK_START: &str = "{{";
 pub const TEMPLATE_BLOCK_END: &str = "}}";
+pub const TEMPLATE_STATEMENT_START: &str = "{%";
+pub const TEMPLATE_STATEMENT_END: &str = "%}";
+pub const TEMPLATE_COMMENT_START: &str = "{#";
+pub const TEMPLATE_COMMENT_END: &str = "#}";
 
 #[derive(Debug, Error)]
 pub enum ToHtmlError {
@@ -25,17 +37,262 @@ pub enum ToHtmlError {
     Unsupported(String),
     #[error("HTML/Markdown template block not closed, near {}", .0)]
     UnclosedBlock(String),
+    #[error("Invalid wiki link {}", .0)]
+    InvalidWikiLink(String),
+    #[error("{error}")]
+    Located { span: Range<usize>, error: Box<ToHtmlError> },
+}
+
+impl ToHtmlError {
+    fn at(self, position: Option<&Position>) -> Self {
+        match (self, position) {
+            (error @ Self::Located { .. }, _) | (error, None) => error,
+            (error, Some(position)) => Self::Located {
+                span: position.start.offset .. position.end.offset,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    pub fn unlocated(self) -> Self {
+        match self {
+            Self::Located { error, .. } => *error,
+            error => error,
+        }
+    }
+
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Self::Located { span, .. } => Some(span.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn relocate(
+        self,
+        map: impl FnOnce(Range<usize>) -> Range<usize>,
+    ) -> Self {
+        match self {
+            Self::Located { span, error } => {
+                Self::Located { span: map(span), error }
+            },
+            error => error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UnsupportedPolicy {
+    #[default]
+    Strict,
+    Warn,
+    Skip,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SectionWrapper {
+    #[default]
+    Div,
+    Section,
+    None,
+}
+
+impl SectionWrapper {
+    fn tag(self) -> Option<&'static str> {
+        match self {
+            Self::Div => Some("div"),
+            Self::Section => Some("section"),
+            Self::None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlClassConfig {
+    ordered_lists: Vec<String>,
+    unordered_lists: Vec<String>,
+    image_wrapper: String,
+    image_legend: String,
+    section_body: String,
+    wiki_link: String,
+}
+
+impl Default for HtmlClassConfig {
+    fn default() -> Self {
+        Self {
+            ordered_lists: ["list-arabic", "list-latin", "list-roman"]
+                .map(String::from)
+                .to_vec(),
+            unordered_lists: ["list-disc", "list-square", "list-circle"]
+                .map(String::from)
+                .to_vec(),
+            image_wrapper: String::from("img-wrapper"),
+            image_legend: String::from("img-legend"),
+            section_body: String::from("section-body"),
+            wiki_link: String::from("wiki-link"),
+        }
+    }
+}
+
+impl HtmlClassConfig {
+    pub fn with_ordered_lists<I>(mut self, classes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.ordered_lists = classes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_unordered_lists<I>(mut self, classes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.unordered_lists = classes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_image_wrapper(mut self, class: impl Into<String>) -> Self {
+        self.image_wrapper = class.into();
+        self
+    }
+
+    pub fn with_image_legend(mut self, class: impl Into<String>) -> Self {
+        self.image_legend = class.into();
+        self
+    }
+
+    pub fn with_section_body(mut self, class: impl Into<String>) -> Self {
+        self.section_body = class.into();
+        self
+    }
+
+    pub fn with_wiki_link(mut self, class: impl Into<String>) -> Self {
+        self.wiki_link = class.into();
+        self
+    }
+
+    pub fn ordered_lists(&self) -> &[String] {
+        &self.ordered_lists
+    }
+
+    pub fn unordered_lists(&self) -> &[String] {
+        &self.unordered_lists
+    }
+
+    pub fn image_wrapper(&self) -> &str {
+        &self.image_wrapper
+    }
+
+    pub fn image_legend(&self) -> &str {
+        &self.image_legend
+    }
+
+    pub fn section_body(&self) -> &str {
+        &self.section_body
+    }
+
+    pub fn wiki_link(&self) -> &str {
+        &self.wiki_link
+    }
+
+    // Lists cycle through their classes as they nest.
+    fn list(classes: &[String], depth: usize) -> &str {
+        if classes.is_empty() {
+            ""
+        } else {
+            &classes[depth % classes.len()]
+        }
+    }
+}
+
+fn class_attr(class: &str) -> String {
+    if class.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"{}\"", tera::escape_html(class))
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+pub struct ToHtmlOptions {
+    pub responsive_images: bool,
+    pub search_markers: bool,
+    pub shortcodes: HashSet<String>,
+    pub glossary_terms: Vec<(String, String)>,
+    pub social_meta: bool,
+    pub on_unsupported: UnsupportedPolicy,
+    pub heading_offset: u8,
+    pub section_wrapper: SectionWrapper,
+    pub skip_link: bool,
+    pub classes: HtmlClassConfig,
+}
+
+impl ToHtmlOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            responsive_images: !config.image_widths().is_empty(),
+            search_markers: config.search_index(),
+            shortcodes: HashSet::new(),
+            glossary_terms: Vec::new(),
+            social_meta: config.social_meta(),
+            on_unsupported: config.on_unsupported(),
+            heading_offset: config.heading_offset(),
+            section_wrapper: config.section_wrapper(),
+            skip_link: config.skip_link(),
+            classes: config.html_classes().clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct ToHtmlCtx {
+    options: ToHtmlOptions,
+    shortcodes: Rc<[ParsedShortcode]>,
     slugs: HashMap<String, usize>,
-    sections: Vec<String>,
+    sections: Vec<(u8, String)>,
     ord_list_depth: usize,
     unord_list_depth: usize,
+    wiki_links: Vec<String>,
+    linked_terms: HashSet<String>,
+    autolink_suspended: bool,
+    summary_pending: bool,
+    unsupported: Vec<String>,
+    warnings: Vec<ContentWarning>,
 }
 
 impl ToHtmlCtx {
+    pub fn new(
+        options: ToHtmlOptions,
+        shortcodes: Rc<[ParsedShortcode]>,
+    ) -> Self {
+        Self { options, shortcodes, ..Self::default() }
+    }
+
+    pub fn mark_summary(&mut self) {
+        self.summary_pending = true;
+    }
+
+    pub fn unsupported(&self) -> &[String] {
+        &self.unsupported
+    }
+
+    pub fn wiki_links(&self) -> &[String] {
+        &self.wiki_links
+    }
+
+    pub fn warnings(&self) -> &[ContentWarning] {
+        &self.warnings
+    }
+
+    pub fn options(&self) -> &ToHtmlOptions {
+        &self.options
+    }
+
     #[expect(dead_code)]
     pub fn ord_list_depth(&self) -> usize {
         self.ord_list_depth
@@ -43,7 +300,7 @@ impl ToHtmlCtx {
 
     #[expect(dead_code)]
     pub fn unord_list_depth(&self) -> usize {
-        self.ord_list_depth
+        self.unord_list_depth
     }
 
     #[expect(dead_code)]
@@ -67,11 +324,21 @@ impl ToHtmlCtx {
         &mut self,
         depth: u8,
         title_slug: String,
+        position: Option<&Position>,
         buf: &mut String,
     ) -> Result<String, ToHtmlError> {
+        if let Some((previous, _)) = self.sections.last() {
+            if depth > previous + 1 {
+                let message = format!(
+                    "heading level {depth} follows level {previous}, skipping \
+                     a level"
+                );
+                self.warn(message, position);
+            }
+        }
         self.prepare_section_level(depth, buf)?;
-        self.sections.push(title_slug);
-        Ok(self.make_slug())
+        self.sections.push((depth, title_slug));
+        Ok(self.make_slug(position))
     }
 
     pub fn leave_ord_list(&mut self) {
@@ -91,43 +358,57 @@ impl ToHtmlCtx {
         Ok(())
     }
 
-    fn make_slug(&mut self) -> String {
-        let base_slug = self.sections.join("-").to_ascii_lowercase();
+    fn make_slug(&mut self, position: Option<&Position>) -> String {
+        let base_slug = self
+            .sections
+            .iter()
+            .map(|(_, slug)| &slug[..])
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_ascii_lowercase();
         let count = self.slugs.entry(base_slug.clone()).or_insert(0);
         *count += 1;
         if *count > 1 {
-            format!("{}-{}", base_slug, *count)
+            let slug = format!("{}-{}", base_slug, *count);
+            let message = format!(
+                "duplicate heading slug {base_slug:?}, renamed to {slug:?}"
+            );
+            self.warn(message, position);
+            slug
         } else {
             base_slug
         }
     }
 
+    fn warn(&mut self, message: String, position: Option<&Position>) {
+        let span = position
+            .map(|position| position.start.offset .. position.end.offset);
+        self.warnings.push(ContentWarning::new(message).with_span(span));
+    }
+
     fn prepare_section_level(
         &mut self,
         new_depth: u8,
         buf: &mut String,
     ) -> Result<(), ToHtmlError> {
-        if let Some(close_count) =
-            self.sections.len().checked_sub(usize::from(new_depth))
+        // Sections are closed by depth rather than by count, so documents
+        // starting at H2 or skipping levels still nest correctly.
+        while self.sections.last().is_some_and(|(depth, _)| *depth >= new_depth)
         {
-            for _ in 0 ..= close_count {
-                self.sections.pop();
-                write!(buf, "</div>")?;
+            self.sections.pop();
+            if let Some(tag) = self.options.section_wrapper.tag() {
+                write!(buf, "</{tag}>")?;
             }
         }
         Ok(())
     }
 }
 
-impl Default for ToHtmlCtx {
-    fn default() -> Self {
-        Self {
-            slugs: HashMap::new(),
-            sections: Vec::new(),
-            ord_list_depth: 0,
-            unord_list_depth: 0,
-        }
-    }
+fn tera_string_literal(value: &str) -> Option<String> {
+    ['"', '\'', '`']
+        .into_iter()
+        .find(|quote| !value.contains(*quote))
+        .map(|quote| format!("{quote}{value}{quote}"))
 }
 
 pub trait ToHtml {
@@ -144,7 +425,8 @@ impl ToHtml for mdast::Node {
         buf: &mut String,
         context: &mut ToHtmlCtx,
     ) -> Result<(), ToHtmlError> {
-        match self {
+        let start = buf.len();
+        let result = match self {
             Self::Root(node) => node.to_html(buf, context),
             Self::Blockquote(node) => node.to_html(buf, context),
             Self::FootnoteDefinition(node) => node.to_html(buf, context),
@@ -179,6 +461,22 @@ impl ToHtml for mdast::Node {
             Self::ListItem(node) => node.to_html(buf, context),
             Self::Definition(node) => node.to_html(buf, context),
             Self::Paragraph(node) => node.to_html(buf, context),
+        };
+        match result {
+            Err(ToHtmlError::Unsupported(node))
+                if context.options.on_unsupported
+                    != UnsupportedPolicy::Strict =>
+            {
+                buf.truncate(start);
+                if context.options.on_unsupported == UnsupportedPolicy::Warn {
+                    write!(buf, "{}", tera::escape_html(&self.to_string()))?;
+                }
+                if !context.unsupported.contains(&node) {
+                    context.unsupported.push(node);
+                }
+                Ok(())
+            },
+            result => result.map_err(|error| error.at(self.position())),
         }
     }
 }
@@ -205,7 +503,34 @@ impl ToHtml for mdast::Root {
         buf: &mut String,
         context: &mut ToHtmlCtx,
     ) -> Result<(), ToHtmlError> {
-        self.children.to_html(buf, context)?;
+        if !mem::take(&mut context.summary_pending) {
+            self.children.to_html(buf, context)?;
+            context.leave_section(1, buf)?;
+            return Ok(());
+        }
+        let more = self.children.iter().position(|child| {
+            matches!(child, mdast::Node::Html(html) if summary::is_more_marker(&html.value))
+        });
+        let mut first_paragraph = true;
+        for (i, child) in self.children.iter().enumerate() {
+            let in_summary = match more {
+                Some(more) => {
+                    i < more && !matches!(child, mdast::Node::Heading(_))
+                },
+                None => {
+                    first_paragraph
+                        && matches!(child, mdast::Node::Paragraph(_))
+                },
+            };
+            if in_summary {
+                buf.push_str(summary::SUMMARY_START);
+            }
+            child.to_html(buf, context)?;
+            if in_summary {
+                buf.push_str(summary::SUMMARY_END);
+                first_paragraph = false;
+            }
+        }
         context.leave_section(1, buf)?;
         Ok(())
     }
@@ -249,9 +574,11 @@ impl ToHtml for mdast::List {
     ) -> Result<(), ToHtmlError> {
         if self.ordered {
             let depth = context.enter_ord_list();
-            let classes = ["arabic", "latin", "roman"];
-            let class = classes[depth % classes.len()];
-            write!(buf, "<ol class=\"list-{class}\"")?;
+            let class = HtmlClassConfig::list(
+                &context.options.classes.ordered_lists,
+                depth,
+            );
+            write!(buf, "<ol{}", class_attr(class))?;
             if let Some(start) = self.start {
                 write!(buf, " start=\"{start}\"")?;
             }
@@ -261,9 +588,11 @@ impl ToHtml for mdast::List {
             context.leave_ord_list();
         } else {
             let depth = context.enter_unord_list();
-            let classes = ["disc", "square", "circle"];
-            let class = classes[depth % classes.len()];
-            write!(buf, "<ul class=\"list-{class}\">")?;
+            let class = HtmlClassConfig::list(
+                &context.options.classes.unordered_lists,
+                depth,
+            );
+            write!(buf, "<ul{}>", class_attr(class))?;
             self.children.to_html(buf, context)?;
             write!(buf, "</ul>")?;
             context.leave_unord_list();
@@ -376,9 +705,31 @@ impl ToHtml for mdast::Html {
     fn to_html(
         &self,
         buf: &mut String,
-        _context: &mut ToHtmlCtx,
+        context: &mut ToHtmlCtx,
     ) -> Result<(), ToHtmlError> {
-        write!(buf, "{}", self.value)?;
+        let shortcodes = context.shortcodes.clone();
+        let Some(shortcode) = shortcode::placeholder_index(&self.value)
+            .and_then(|index| shortcodes.get(index))
+        else {
+            write!(buf, "{}", self.value)?;
+            return Ok(());
+        };
+        write!(buf, "{{% filter shortcode(name=\"{}\"", shortcode.call.name)?;
+        if !shortcode.call.args.is_empty() {
+            write!(buf, ", {}", shortcode.call.args)?;
+        }
+        write!(buf, ") %}}")?;
+        // Positions in the body are relative to the body, not the page.
+        let warned = context.warnings.len();
+        match &shortcode.ast {
+            mdast::Node::Root(root) => root.children.to_html(buf, context),
+            node => node.to_html(buf, context),
+        }
+        .map_err(ToHtmlError::unlocated)?;
+        for warning in &mut context.warnings[warned ..] {
+            *warning = ContentWarning::new(warning.message());
+        }
+        write!(buf, "{{% endfilter %}}")?;
         Ok(())
     }
 }
@@ -387,14 +738,41 @@ impl ToHtml for mdast::Image {
     fn to_html(
         &self,
         buf: &mut String,
-        _context: &mut ToHtmlCtx,
+        context: &mut ToHtmlCtx,
     ) -> Result<(), ToHtmlError> {
+        if self.alt.trim().is_empty() {
+            let message = format!("image {:?} has no alt text", self.url);
+            context.warn(message, self.position.as_ref());
+        }
         let escaped_src = tera::escape_html(&self.url);
         let escaped_alt = tera::escape_html(&self.alt);
+        let responsive = context
+            .options()
+            .responsive_images
+            .then(|| images::asset_logical_path(&self.url))
+            .flatten()
+            .and_then(|logical| {
+                Some((
+                    tera_string_literal(logical)?,
+                    tera_string_literal(&self.alt)?,
+                ))
+            });
+        let classes = &context.options.classes;
+        let wrapper = class_attr(&classes.image_wrapper);
+        let legend = class_attr(&classes.image_legend);
+        if let Some((path, alt)) = responsive {
+            write!(
+                buf,
+                "<div{wrapper}>{{{{ image(path={}, alt={}) | safe \
+                 }}}}<div{legend}>{}</div></div>",
+                path, alt, escaped_alt,
+            )?;
+            return Ok(());
+        }
         write!(
             buf,
-            "<div class=\"img-wrapper\"><img src=\"{}\" alt=\"{}\"/><div \
-             class=\"img-legend\">{}</div></div>",
+            "<div{wrapper}><img src=\"{}\" \
+             alt=\"{}\"/><div{legend}>{}</div></div>",
             escaped_src, escaped_alt, escaped_alt,
         )?;
         Ok(())
@@ -427,12 +805,24 @@ impl ToHtml for mdast::Link {
         buf: &mut String,
         context: &mut ToHtmlCtx,
     ) -> Result<(), ToHtmlError> {
+        if self.url.trim().is_empty() {
+            context
+                .warn("link has no target".to_owned(), self.position.as_ref());
+        }
+        if self.children.iter().all(|child| {
+            matches!(child, mdast::Node::Text(text) if text.value.trim().is_empty())
+        }) {
+            let message = format!("link to {:?} has no text", self.url);
+            context.warn(message, self.position.as_ref());
+        }
         write!(buf, "<a href=\"{}\"", self.url)?;
         if let Some(title) = &self.title {
             write!(buf, " title=\"{title}\"")?;
         }
         write!(buf, ">")?;
+        let suspended = mem::replace(&mut context.autolink_suspended, true);
         self.children.to_html(buf, context)?;
+        context.autolink_suspended = suspended;
         write!(buf, "</a>")?;
         Ok(())
     }
@@ -462,61 +852,204 @@ impl ToHtml for mdast::Text {
     fn to_html(
         &self,
         buf: &mut String,
-        _context: &mut ToHtmlCtx,
+        context: &mut ToHtmlCtx,
     ) -> Result<(), ToHtmlError> {
+        let mut value = &self.value[..];
+
+        while let Some((start, delimiter)) = TemplateDelimiter::find(value) {
+            write_text(buf, &value[.. start], context)?;
+            let block = &value[start ..];
+            let len = delimiter
+                .block_len(block)
+                .ok_or_else(|| ToHtmlError::UnclosedBlock(value.to_owned()))?;
+            write!(buf, "{}", &block[.. len])?;
+            value = &block[len ..];
+        }
+        write_text(buf, value, context)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TemplateDelimiter {
+    Expression,
+    Statement,
+    Comment,
+}
+
+impl TemplateDelimiter {
+    const ALL: [Self; 3] = [Self::Expression, Self::Statement, Self::Comment];
+
+    fn start(self) -> &'static str {
+        match self {
+            Self::Expression => TEMPLATE_BLOCK_START,
+            Self::Statement => TEMPLATE_STATEMENT_START,
+            Self::Comment => TEMPLATE_COMMENT_START,
+        }
+    }
+
+    fn end(self) -> &'static str {
+        match self {
+            Self::Expression => TEMPLATE_BLOCK_END,
+            Self::Statement => TEMPLATE_STATEMENT_END,
+            Self::Comment => TEMPLATE_COMMENT_END,
+        }
+    }
+
+    pub(super) fn find(text: &str) -> Option<(usize, Self)> {
+        Self::ALL
+            .into_iter()
+            .filter_map(|delimiter| {
+                Some((text.find(delimiter.start())?, delimiter))
+            })
+            .min_by_key(|(start, _)| *start)
+    }
+
+    // Length of the block at the start of `block`, including both
+    // delimiters. Comments end at the first closing delimiter, while
+    // expressions and statements skip string literals and nested braces.
+    pub(super) fn block_len(self, block: &str) -> Option<usize> {
         #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-        enum ExpandState {
+        enum ScanState {
             BlockRoot,
-            StringLiteral,
-            Escaping,
+            StringLiteral(char),
+            Escaping(char),
         }
 
-        let mut value = &self.value[..];
-
+        let mut len = self.start().len();
+        if self == Self::Comment {
+            let end = block[len ..].find(self.end())?;
+            return Some(len + end + self.end().len());
+        }
+        let mut state = ScanState::BlockRoot;
+        let mut depth = 0usize;
         loop {
-            let Some(expand_start) = value.find(TEMPLATE_BLOCK_START) else {
-                write!(buf, "{}", tera::escape_html(value))?;
-                break;
-            };
-            write!(buf, "{}", tera::escape_html(&value[.. expand_start]))?;
-            let expanding = &value[expand_start ..];
-            let mut len = TEMPLATE_BLOCK_START.len();
-            let mut state = ExpandState::BlockRoot;
-
-            loop {
-                let Some(ch) = expanding[len ..].chars().next() else {
-                    Err(ToHtmlError::UnclosedBlock(value.to_owned()))?
-                };
-
-                match state {
-                    ExpandState::BlockRoot => {
-                        if expanding[len ..].starts_with(TEMPLATE_BLOCK_END) {
-                            len += TEMPLATE_BLOCK_END.len();
-                            break;
-                        }
-                        if ch == '"' {
-                            state = ExpandState::StringLiteral;
-                        }
-                    },
-                    ExpandState::StringLiteral => {
-                        if ch == '"' {
-                            state = ExpandState::BlockRoot;
-                        } else if ch == '\\' {
-                            state = ExpandState::Escaping;
-                        }
-                    },
-                    ExpandState::Escaping => {
-                        state = ExpandState::StringLiteral;
-                    },
-                }
-                len += ch.len_utf8();
+            let rest = &block[len ..];
+            let ch = rest.chars().next()?;
+            match state {
+                ScanState::BlockRoot => {
+                    if depth == 0 && rest.starts_with(self.end()) {
+                        return Some(len + self.end().len());
+                    }
+                    match ch {
+                        '"' | '\'' | '`' => {
+                            state = ScanState::StringLiteral(ch)
+                        },
+                        '{' | '(' | '[' => depth += 1,
+                        '}' | ')' | ']' => depth = depth.saturating_sub(1),
+                        _ => (),
+                    }
+                },
+                ScanState::StringLiteral(quote) => {
+                    if ch == quote {
+                        state = ScanState::BlockRoot;
+                    } else if ch == '\\' {
+                        state = ScanState::Escaping(quote);
+                    }
+                },
+                ScanState::Escaping(quote) => {
+                    state = ScanState::StringLiteral(quote);
+                },
             }
-            write!(buf, "{}", &expanding[.. len])?;
-            value = &expanding[len ..];
+            len += ch.len_utf8();
         }
+    }
+}
 
-        Ok(())
+fn write_text(
+    buf: &mut String,
+    mut text: &str,
+    context: &mut ToHtmlCtx,
+) -> Result<(), ToHtmlError> {
+    while let Some(start) = text.find(wiki::LINK_START) {
+        let inner_start = start + wiki::LINK_START.len();
+        let Some(inner_len) = text[inner_start ..].find(wiki::LINK_END) else {
+            break;
+        };
+        write_plain(buf, &text[.. start], context)?;
+        let inner = &text[inner_start .. inner_start + inner_len];
+        let (target, label) = inner
+            .split_once(wiki::LABEL_SEPARATOR)
+            .map_or((inner, inner), |(target, label)| {
+                (target.trim(), label.trim())
+            });
+        let Some(target_literal) =
+            tera_string(target).filter(|_| !target.is_empty())
+        else {
+            Err(ToHtmlError::InvalidWikiLink(inner.to_owned()))?
+        };
+        write!(
+            buf,
+            "<a{class} href=\"{{{{ wiki_url(to={target_literal}) \
+             }}}}\">{label}</a>",
+            class = class_attr(&context.options.classes.wiki_link),
+            label = tera::escape_html(label),
+        )?;
+        context.wiki_links.push(target.to_owned());
+        text = &text[inner_start + inner_len + wiki::LINK_END.len() ..];
     }
+    write_plain(buf, text, context)
+}
+
+fn write_plain(
+    buf: &mut String,
+    mut text: &str,
+    context: &mut ToHtmlCtx,
+) -> Result<(), ToHtmlError> {
+    while let Some((start, form, name)) = find_term(text, context) {
+        let (Some(name_literal), Some(form_literal)) =
+            (tera_string(&name), tera_string(form))
+        else {
+            break;
+        };
+        write!(buf, "{}", tera::escape_html(&text[.. start]))?;
+        write!(
+            buf,
+            "{{{{ term(name={name_literal}, text={form_literal}) }}}}"
+        )?;
+        text = &text[start + form.len() ..];
+        context.linked_terms.insert(name);
+    }
+    write!(buf, "{}", tera::escape_html(text))?;
+    Ok(())
+}
+
+fn find_term<'t>(
+    text: &'t str,
+    context: &ToHtmlCtx,
+) -> Option<(usize, &'t str, String)> {
+    if context.autolink_suspended {
+        return None;
+    }
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    let mut found: Option<(usize, &'t str, String)> = None;
+    for (form, name) in &context.options.glossary_terms {
+        if context.linked_terms.contains(name) {
+            continue;
+        }
+        let Some(start) = text.match_indices(&form[..]).find_map(|(i, _)| {
+            let end = i + form.len();
+            let before = text[.. i].chars().next_back();
+            let after = text[end ..].chars().next();
+            (!before.is_some_and(is_word) && !after.is_some_and(is_word))
+                .then_some(i)
+        }) else {
+            continue;
+        };
+        if found.as_ref().is_none_or(|(best, ..)| start < *best) {
+            let form = &text[start .. start + form.len()];
+            found = Some((start, form, name.clone()));
+        }
+    }
+    found
+}
+
+fn tera_string(value: &str) -> Option<String> {
+    ['"', '\'', '`']
+        .into_iter()
+        .find(|delimiter| !value.contains(*delimiter))
+        .map(|delimiter| format!("{delimiter}{value}{delimiter}"))
 }
 
 impl ToHtml for mdast::Code {
@@ -555,25 +1088,39 @@ impl ToHtml for mdast::Heading {
         buf: &mut String,
         context: &mut ToHtmlCtx,
     ) -> Result<(), ToHtmlError> {
-        let depth = match self.depth {
-            1 => "1",
-            2 => "2",
-            3 => "3",
-            4 => "4",
-            5 => "5",
-            _ => "6",
-        };
+        let depth =
+            match self.depth.saturating_add(context.options.heading_offset) {
+                1 => "1",
+                2 => "2",
+                3 => "3",
+                4 => "4",
+                5 => "5",
+                _ => "6",
+            };
         let id = buf.len();
         let mut title_slug = String::new();
         self.children.slugify(&mut title_slug)?;
-        let full_slug = context.enter_section(self.depth, title_slug, buf)?;
+        let full_slug = context.enter_section(
+            self.depth,
+            title_slug,
+            self.position.as_ref(),
+            buf,
+        )?;
         write!(
             buf,
             "<h{depth} id=\"section_{id}\"><a href=\"#section_{full_slug}\">"
         )?;
+        let suspended = mem::replace(&mut context.autolink_suspended, true);
         self.children.to_html(buf, context)?;
+        context.autolink_suspended = suspended;
         write!(buf, "</a></h{depth}>")?;
-        write!(buf, "<div class=\"section-body\">")?;
+        if let Some(tag) = context.options.section_wrapper.tag() {
+            write!(
+                buf,
+                "<{tag}{} aria-labelledby=\"section_{id}\">",
+                class_attr(&context.options.classes.section_body)
+            )?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,453 @@
+use std::{
+    fmt::{self, Write as _},
+    path::{self, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use markdown::mdast;
+use thiserror::Error;
+
+use super::{
+    shortcode::{self, ParsedShortcode},
+    to_html::{TemplateDelimiter, UnsupportedPolicy},
+};
+use crate::{images, wiki, Config, RealFs, SiteFs};
+
+pub const RAW_START: &str = "{% raw %}";
+pub const RAW_END: &str = "{% endraw %}";
+
+const SECTIONING: [&str; 5] =
+    ["section", "subsection", "subsubsection", "paragraph", "subparagraph"];
+
+const ENUM_COUNTERS: [&str; 4] = ["enumi", "enumii", "enumiii", "enumiv"];
+
+#[derive(Debug, Error)]
+pub enum ToLatexError {
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+    #[error("Converting markdown node {} to LaTeX is not supported", .0)]
+    Unsupported(String),
+    #[error("LaTeX/Markdown template block not closed, near {}", .0)]
+    UnclosedBlock(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ToLatexOptions {
+    pub on_unsupported: UnsupportedPolicy,
+    pub heading_offset: u8,
+    pub asset_dirs: Vec<PathBuf>,
+    pub fs: Arc<dyn SiteFs>,
+}
+
+impl ToLatexOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            on_unsupported: config.on_unsupported(),
+            heading_offset: config.heading_offset(),
+            asset_dirs: config.asset_dirs(),
+            fs: config.shared_fs(),
+        }
+    }
+}
+
+impl Default for ToLatexOptions {
+    fn default() -> Self {
+        Self {
+            on_unsupported: UnsupportedPolicy::default(),
+            heading_offset: 0,
+            asset_dirs: Vec::new(),
+            fs: Arc::new(RealFs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ToLatexCtx {
+    options: ToLatexOptions,
+    shortcodes: Rc<[ParsedShortcode]>,
+    ord_list_depth: usize,
+    unsupported: Vec<String>,
+}
+
+impl ToLatexCtx {
+    pub fn new(
+        options: ToLatexOptions,
+        shortcodes: Rc<[ParsedShortcode]>,
+    ) -> Self {
+        Self { options, shortcodes, ..Self::default() }
+    }
+
+    pub fn unsupported(&self) -> &[String] {
+        &self.unsupported
+    }
+
+    fn asset_file(&self, url: &str) -> Option<PathBuf> {
+        let logical = images::asset_logical_path(url)?;
+        self.options
+            .asset_dirs
+            .iter()
+            .map(|dir| dir.join(logical))
+            .find(|path| self.options.fs.is_file(path))
+            .and_then(|path| path::absolute(path).ok())
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            },
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+// Text content of an HTML fragment, for template functions that only know
+// how to render HTML.
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[.. start]);
+        rest = rest[start ..]
+            .find('>')
+            .map_or("", |end| &rest[start + end + 1 ..]);
+    }
+    text.push_str(rest);
+    [
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&quot;", "\""),
+        ("&#x27;", "'"),
+        ("&#x2F;", "/"),
+        ("&amp;", "&"),
+    ]
+    .into_iter()
+    .fold(text, |text, (entity, ch)| text.replace(entity, ch))
+}
+
+fn url(url: &str) -> String {
+    url.replace('%', "\\%").replace('#', "\\#")
+}
+
+fn write_raw(buf: &mut String, latex: fmt::Arguments) -> fmt::Result {
+    // Code and math may contain template delimiters of their own.
+    write!(buf, "{RAW_START}{latex}{RAW_END}")
+}
+
+pub trait ToLatex {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError>;
+}
+
+impl ToLatex for mdast::Node {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        let start = buf.len();
+        let unsupported =
+            |name: &str| Err(ToLatexError::Unsupported(name.to_owned()));
+        let result = match self {
+            Self::Root(node) => node.children.to_latex(buf, context),
+            Self::Blockquote(node) => node.to_latex(buf, context),
+            Self::List(node) => node.to_latex(buf, context),
+            Self::Break(_) => Ok(writeln!(buf, "\\\\")?),
+            Self::InlineCode(node) => Ok(write_raw(
+                buf,
+                format_args!("\\texttt{{{}}}", escape(&node.value)),
+            )?),
+            Self::InlineMath(node) => {
+                Ok(write_raw(buf, format_args!("${}$", node.value))?)
+            },
+            Self::Emphasis(node) => {
+                write!(buf, "\\emph{{")?;
+                node.children.to_latex(buf, context)?;
+                Ok(write!(buf, "}}")?)
+            },
+            Self::Strong(node) => {
+                write!(buf, "\\textbf{{")?;
+                node.children.to_latex(buf, context)?;
+                Ok(write!(buf, "}}")?)
+            },
+            Self::Html(node) => node.to_latex(buf, context),
+            Self::Image(node) => node.to_latex(buf, context),
+            Self::Link(node) => node.to_latex(buf, context),
+            Self::Text(node) => node.to_latex(buf, context),
+            Self::Code(node) => node.to_latex(buf, context),
+            Self::Math(node) => {
+                Ok(write_raw(buf, format_args!("\\[{}\\]\n\n", node.value))?)
+            },
+            Self::Heading(node) => node.to_latex(buf, context),
+            Self::ThematicBreak(_) => {
+                Ok(write!(buf, "\\bigskip\\hrule\\bigskip\n\n")?)
+            },
+            Self::ListItem(node) => node.to_latex(buf, context),
+            Self::Paragraph(node) => {
+                node.children.to_latex(buf, context)?;
+                Ok(write!(buf, "\n\n")?)
+            },
+            Self::FootnoteDefinition(_) => unsupported("FootnoteDefinition"),
+            Self::MdxJsxFlowElement(_) => unsupported("MdxJsxFlowElement"),
+            Self::MdxjsEsm(_) => unsupported("MdxjsEsm"),
+            Self::Toml(_) => unsupported("Toml"),
+            Self::Yaml(_) => unsupported("Yaml"),
+            Self::Delete(_) => unsupported("Delete"),
+            Self::MdxTextExpression(_) => unsupported("MdxTextExpression"),
+            Self::FootnoteReference(_) => unsupported("FootnoteReference"),
+            Self::ImageReference(_) => unsupported("ImageReference"),
+            Self::MdxJsxTextElement(_) => unsupported("MdxJsxTextElement"),
+            Self::LinkReference(_) => unsupported("LinkReference"),
+            Self::MdxFlowExpression(_) => unsupported("MdxFlowExpression"),
+            Self::Table(_) => unsupported("Table"),
+            Self::TableRow(_) => unsupported("TableRow"),
+            Self::TableCell(_) => unsupported("TableCell"),
+            Self::Definition(_) => unsupported("Definition"),
+        };
+        match result {
+            Err(ToLatexError::Unsupported(node))
+                if context.options.on_unsupported
+                    != UnsupportedPolicy::Strict =>
+            {
+                buf.truncate(start);
+                if context.options.on_unsupported == UnsupportedPolicy::Warn {
+                    write!(buf, "{}", escape(&self.to_string()))?;
+                }
+                if !context.unsupported.contains(&node) {
+                    context.unsupported.push(node);
+                }
+                Ok(())
+            },
+            result => result,
+        }
+    }
+}
+
+impl<T> ToLatex for [T]
+where
+    T: ToLatex,
+{
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        for child in self {
+            child.to_latex(buf, context)?;
+        }
+        Ok(())
+    }
+}
+
+impl ToLatex for mdast::Blockquote {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        writeln!(buf, "\\begin{{quote}}")?;
+        self.children.to_latex(buf, context)?;
+        write!(buf, "\\end{{quote}}\n\n")?;
+        Ok(())
+    }
+}
+
+impl ToLatex for mdast::List {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        if !self.ordered {
+            writeln!(buf, "\\begin{{itemize}}")?;
+            self.children.to_latex(buf, context)?;
+            write!(buf, "\\end{{itemize}}\n\n")?;
+            return Ok(());
+        }
+        let depth = context.ord_list_depth;
+        context.ord_list_depth += 1;
+        writeln!(buf, "\\begin{{enumerate}}")?;
+        if let (Some(start @ 2 ..), Some(counter)) =
+            (self.start, ENUM_COUNTERS.get(depth))
+        {
+            writeln!(
+                buf,
+                "\\setcounter{{{counter}}}{{{}}}",
+                start.saturating_sub(1)
+            )?;
+        }
+        self.children.to_latex(buf, context)?;
+        write!(buf, "\\end{{enumerate}}\n\n")?;
+        context.ord_list_depth = depth;
+        Ok(())
+    }
+}
+
+impl ToLatex for mdast::ListItem {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        if self.checked.is_some() {
+            Err(ToLatexError::Unsupported("checkable ListItem".to_owned()))?;
+        }
+        write!(buf, "\\item ")?;
+        self.children.to_latex(buf, context)?;
+        writeln!(buf)?;
+        Ok(())
+    }
+}
+
+impl ToLatex for mdast::Html {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        // Raw HTML has no LaTeX counterpart, but the markdown inside a
+        // shortcode still belongs to the document.
+        let shortcodes = context.shortcodes.clone();
+        let Some(shortcode) = shortcode::placeholder_index(&self.value)
+            .and_then(|index| shortcodes.get(index))
+        else {
+            return Ok(());
+        };
+        match &shortcode.ast {
+            mdast::Node::Root(root) => root.children.to_latex(buf, context),
+            node => node.to_latex(buf, context),
+        }
+    }
+}
+
+impl ToLatex for mdast::Image {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        let Some(path) = context.asset_file(&self.url) else {
+            write!(
+                buf,
+                "\\href{{{}}}{{{}}}",
+                url(&self.url),
+                escape(&self.alt)
+            )?;
+            return Ok(());
+        };
+        let path = path.to_string_lossy().replace('\\', "/");
+        writeln!(buf, "\\begin{{figure}}[h]\\centering")?;
+        writeln!(buf, "\\includegraphics[width=\\linewidth]{{{path}}}")?;
+        if !self.alt.is_empty() {
+            writeln!(buf, "\\caption{{{}}}", escape(&self.alt))?;
+        }
+        writeln!(buf, "\\end{{figure}}")?;
+        Ok(())
+    }
+}
+
+impl ToLatex for mdast::Link {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        write!(buf, "\\href{{{}}}{{", url(&self.url))?;
+        self.children.to_latex(buf, context)?;
+        write!(buf, "}}")?;
+        Ok(())
+    }
+}
+
+impl ToLatex for mdast::Text {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        _context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        let mut value = &self.value[..];
+
+        while let Some((start, delimiter)) = TemplateDelimiter::find(value) {
+            write_text(buf, &value[.. start])?;
+            let block = &value[start ..];
+            let len = delimiter
+                .block_len(block)
+                .ok_or_else(|| ToLatexError::UnclosedBlock(value.to_owned()))?;
+            write!(buf, "{}", &block[.. len])?;
+            value = &block[len ..];
+        }
+        write_text(buf, value)?;
+
+        Ok(())
+    }
+}
+
+// Wiki links keep only their label, since pages are not addressable by URL
+// in a single document.
+fn write_text(buf: &mut String, mut text: &str) -> fmt::Result {
+    while let Some(start) = text.find(wiki::LINK_START) {
+        let inner_start = start + wiki::LINK_START.len();
+        let Some(inner_len) = text[inner_start ..].find(wiki::LINK_END) else {
+            break;
+        };
+        write!(buf, "{}", escape(&text[.. start]))?;
+        let inner = &text[inner_start .. inner_start + inner_len];
+        let label = inner
+            .split_once(wiki::LABEL_SEPARATOR)
+            .map_or(inner, |(_, label)| label.trim());
+        write!(buf, "\\emph{{{}}}", escape(label))?;
+        text = &text[inner_start + inner_len + wiki::LINK_END.len() ..];
+    }
+    write!(buf, "{}", escape(text))
+}
+
+impl ToLatex for mdast::Code {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        _context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        write_raw(
+            buf,
+            format_args!(
+                "\\begin{{verbatim}}\n{}\n\\end{{verbatim}}\n\n",
+                self.value
+            ),
+        )?;
+        Ok(())
+    }
+}
+
+impl ToLatex for mdast::Heading {
+    fn to_latex(
+        &self,
+        buf: &mut String,
+        context: &mut ToLatexCtx,
+    ) -> Result<(), ToLatexError> {
+        let depth = self
+            .depth
+            .saturating_add(context.options.heading_offset)
+            .saturating_sub(1);
+        let command = SECTIONING[usize::from(depth).min(SECTIONING.len() - 1)];
+        write!(buf, "\\{command}{{")?;
+        self.children.to_latex(buf, context)?;
+        write!(buf, "}}\n\n")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub const CONTEXT_KEY: &str = "menus";
+
+pub const CONFIG_KEY: &str = "menu";
+
+pub const DEFAULT_MENU: &str = "main";
+
+#[derive(Debug, Error)]
+pub enum MenuError {
+    #[error("Menu item {} needs either a url or a page", .0)]
+    MissingTarget(String),
+    #[error("Menu item {} has both a url and a page", .0)]
+    AmbiguousTarget(String),
+    #[error(
+        "Menu item {} refers to page {}, which was not built",
+        .0,
+        .1.display()
+    )]
+    UnknownPage(String, PathBuf),
+    #[error("Menu item {} is declared twice in menu {}", .0, .1)]
+    Duplicate(String, String),
+    #[error("Menu item {} has unknown parent {}", .0, .1)]
+    UnknownParent(String, String),
+    #[error("Menu item {} is its own ancestor", .0)]
+    Cycle(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MenuEntry {
+    name: String,
+    // Defaults to the name, and is what children refer to as their parent.
+    id: Option<String>,
+    url: Option<String>,
+    // Relative to the page directory.
+    page: Option<PathBuf>,
+    #[serde(default)]
+    weight: i64,
+    parent: Option<String>,
+    #[serde(default = "default_menu")]
+    menu: String,
+}
+
+fn default_menu() -> String {
+    DEFAULT_MENU.to_owned()
+}
+
+impl MenuEntry {
+    fn id(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MenuItem {
+    pub name: String,
+    pub url: String,
+    pub weight: i64,
+    pub children: Vec<MenuItem>,
+}
+
+pub fn entries(
+    site_config: &toml::Table,
+) -> Result<Vec<MenuEntry>, toml::de::Error> {
+    site_config
+        .get(CONFIG_KEY)
+        .cloned()
+        .map_or(Ok(Vec::new()), toml::Value::try_into)
+}
+
+// Menus by name, each holding its top-level items. Siblings are ordered by
+// weight, and then by the order they were declared in.
+pub fn build<'a>(
+    entries: &[MenuEntry],
+    page_url: impl Fn(&Path) -> Option<&'a str>,
+) -> Result<BTreeMap<String, Vec<MenuItem>>, MenuError> {
+    let mut by_id = HashMap::new();
+    for entry in entries {
+        let key = (&entry.menu[..], entry.id());
+        if by_id.insert(key, entry).is_some() {
+            Err(MenuError::Duplicate(
+                entry.id().to_owned(),
+                entry.menu.clone(),
+            ))?;
+        }
+    }
+    let mut urls = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let url = match (&entry.url, &entry.page) {
+            (Some(url), None) => url.clone(),
+            (None, Some(page)) => page_url(page)
+                .ok_or_else(|| {
+                    MenuError::UnknownPage(entry.name.clone(), page.clone())
+                })?
+                .to_owned(),
+            (None, None) => Err(MenuError::MissingTarget(entry.name.clone()))?,
+            (Some(_), Some(_)) => {
+                Err(MenuError::AmbiguousTarget(entry.name.clone()))?
+            },
+        };
+        urls.push(url);
+        let mut ancestor = entry;
+        for _ in 0 ..= entries.len() {
+            let Some(parent) = &ancestor.parent else { break };
+            ancestor = by_id.get(&(&entry.menu[..], &parent[..])).ok_or_else(
+                || MenuError::UnknownParent(entry.name.clone(), parent.clone()),
+            )?;
+            if ancestor.id() == entry.id() {
+                Err(MenuError::Cycle(entry.name.clone()))?;
+            }
+        }
+    }
+
+    let mut menus = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| entry.parent.is_none()) {
+        menus.entry(entry.menu.clone()).or_insert_with(Vec::new);
+    }
+    for (menu, items) in &mut menus {
+        *items = children(entries, &urls, menu, None);
+    }
+    Ok(menus)
+}
+
+fn children(
+    entries: &[MenuEntry],
+    urls: &[String],
+    menu: &str,
+    parent: Option<&str>,
+) -> Vec<MenuItem> {
+    let mut items: Vec<_> = entries
+        .iter()
+        .zip(urls)
+        .filter(|(entry, _)| {
+            entry.menu == menu && entry.parent.as_deref() == parent
+        })
+        .map(|(entry, url)| MenuItem {
+            name: entry.name.clone(),
+            url: url.clone(),
+            weight: entry.weight,
+            children: children(entries, urls, menu, Some(entry.id())),
+        })
+        .collect();
+    items.sort_by_key(|item| item.weight);
+    items
+}
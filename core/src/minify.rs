@@ -0,0 +1,68 @@
+const RAW_TEXT_ELEMENTS: &[&str] = &["pre", "textarea", "script", "style"];
+
+pub fn minify_html(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input.trim();
+
+    while let Some(ch) = rest.chars().next() {
+        if rest.starts_with("<!--") && !rest.starts_with("<!--[if") {
+            rest = match rest.find("-->") {
+                Some(end) => &rest[end + "-->".len() ..],
+                None => "",
+            };
+        } else if ch == '<' {
+            let tag_len = tag_len(rest);
+            let tag = &rest[.. tag_len];
+            output.push_str(tag);
+            rest = &rest[tag_len ..];
+
+            if let Some(name) = raw_text_element(tag) {
+                let raw_len = raw_text_len(rest, name);
+                output.push_str(&rest[.. raw_len]);
+                rest = &rest[raw_len ..];
+            }
+        } else if ch.is_whitespace() {
+            let trimmed = rest.trim_start();
+            if !output.ends_with(' ') {
+                output.push(' ');
+            }
+            rest = trimmed;
+        } else {
+            output.push(ch);
+            rest = &rest[ch.len_utf8() ..];
+        }
+    }
+
+    output
+}
+
+pub fn tag_len(tag: &str) -> usize {
+    let mut quote = None;
+    for (i, ch) in tag.char_indices().skip(1) {
+        match quote {
+            Some(open) if ch == open => quote = None,
+            Some(_) => (),
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == '>' => return i + 1,
+            None => (),
+        }
+    }
+    tag.len()
+}
+
+fn raw_text_element(tag: &str) -> Option<&'static str> {
+    let name_len = tag[1 ..]
+        .find(|ch: char| ch.is_whitespace() || ch == '>' || ch == '/')
+        .unwrap_or(tag.len() - 1);
+    let name = &tag[1 .. 1 + name_len];
+    RAW_TEXT_ELEMENTS
+        .iter()
+        .copied()
+        .find(|element| element.eq_ignore_ascii_case(name))
+}
+
+fn raw_text_len(content: &str, element: &str) -> usize {
+    let closing = format!("</{element}");
+    let lowered = content.to_ascii_lowercase();
+    lowered.find(&closing).unwrap_or(content.len())
+}
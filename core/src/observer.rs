@@ -0,0 +1,96 @@
+use std::{
+    fmt,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::BuildWarning;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BuildPhase {
+    Clean,
+    Assets,
+    Pages,
+    Render,
+    Dictionary,
+    Reference,
+    Glossary,
+    ExtraOutputs,
+}
+
+impl fmt::Display for BuildPhase {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Clean => "clean",
+            Self::Assets => "assets",
+            Self::Pages => "pages",
+            Self::Render => "render",
+            Self::Dictionary => "dictionary",
+            Self::Reference => "reference",
+            Self::Glossary => "glossary",
+            Self::ExtraOutputs => "extra outputs",
+        };
+        fmtr.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildEvent<'a> {
+    PhaseStarted(BuildPhase),
+    PhaseFinished(BuildPhase, Duration),
+    AssetCopied(&'a Path),
+    PageCompiled(&'a Path),
+    PageWritten(&'a Path),
+    Warning(&'a BuildWarning),
+    Finished(Duration),
+}
+
+pub trait BuildObserver: Send + Sync + 'static {
+    fn observe(&self, event: &BuildEvent);
+}
+
+impl<F> BuildObserver for F
+where
+    F: Fn(&BuildEvent) + Send + Sync + 'static,
+{
+    fn observe(&self, event: &BuildEvent) {
+        self(event)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct BuildObservers {
+    observers: Vec<Arc<dyn BuildObserver>>,
+}
+
+impl BuildObservers {
+    pub fn register<O>(&mut self, observer: O)
+    where
+        O: BuildObserver,
+    {
+        self.observers.push(Arc::new(observer));
+    }
+
+    pub fn emit(&self, event: BuildEvent) {
+        for observer in &self.observers {
+            observer.observe(&event);
+        }
+    }
+
+    pub fn phase<T>(&self, phase: BuildPhase, run: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        self.emit(BuildEvent::PhaseStarted(phase));
+        let output = run();
+        self.emit(BuildEvent::PhaseFinished(phase, started.elapsed()));
+        output
+    }
+}
+
+impl fmt::Debug for BuildObservers {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("BuildObservers")
+            .field("len", &self.observers.len())
+            .finish()
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    collections::{hash_map::Entry, BTreeSet, HashMap},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::site_fs::{FileKind, SiteFs};
+
+pub const MANIFEST_FILE_NAME: &str = ".lin-ssg-manifest";
+
+pub fn is_preserved(path_rel: &Path, preserved: &[PathBuf]) -> bool {
+    path_rel == Path::new(MANIFEST_FILE_NAME)
+        || preserved.iter().any(|preserved| path_rel.starts_with(preserved))
+}
+
+pub fn list_files(
+    fs: &dyn SiteFs,
+    root: &Path,
+) -> io::Result<BTreeSet<PathBuf>> {
+    let mut files = BTreeSet::new();
+    if !fs.is_dir(root) {
+        return Ok(files);
+    }
+    let mut directories = vec![root.to_owned()];
+    while let Some(directory) = directories.pop() {
+        for path in fs.read_dir(&directory)? {
+            if !fs.is_symlink(&path)? && fs.kind(&path)? == FileKind::Dir {
+                directories.push(path);
+            } else if let Ok(path_rel) = path.strip_prefix(root) {
+                files.insert(path_rel.to_owned());
+            }
+        }
+    }
+    Ok(files)
+}
+
+pub fn generated_files(
+    fs: &dyn SiteFs,
+    output_dir: &Path,
+    preserved: &[PathBuf],
+) -> io::Result<BTreeSet<PathBuf>> {
+    let files = list_files(fs, output_dir)?;
+    let generated =
+        match fs.read_to_string(&output_dir.join(MANIFEST_FILE_NAME)) {
+            Ok(manifest) => manifest
+                .lines()
+                .map(PathBuf::from)
+                .filter(|path_rel| files.contains(path_rel))
+                .collect(),
+            // Without a manifest nothing is known to be ours, so a first
+            // build into an existing directory only replaces the files it
+            // generates again.
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                BTreeSet::new()
+            },
+            Err(error) => Err(error)?,
+        };
+    Ok(generated
+        .into_iter()
+        .filter(|path_rel| !is_preserved(path_rel, preserved))
+        .collect())
+}
+
+pub fn sweep(
+    fs: &dyn SiteFs,
+    output_dir: &Path,
+    preserved: &[PathBuf],
+) -> io::Result<BTreeSet<PathBuf>> {
+    fs.create_dir_all(output_dir)?;
+    for path_rel in generated_files(fs, output_dir, preserved)? {
+        fs.remove_file(&output_dir.join(&path_rel))?;
+        for parent in path_rel.ancestors().skip(1) {
+            if parent.as_os_str().is_empty()
+                || !fs.read_dir(&output_dir.join(parent))?.is_empty()
+            {
+                break;
+            }
+            fs.remove_dir(&output_dir.join(parent))?;
+        }
+    }
+    list_files(fs, output_dir)
+}
+
+pub fn write_manifest(
+    fs: &dyn SiteFs,
+    output_dir: &Path,
+    kept: &BTreeSet<PathBuf>,
+) -> io::Result<()> {
+    let mut manifest = String::new();
+    for path_rel in list_files(fs, output_dir)?.difference(kept) {
+        if path_rel != Path::new(MANIFEST_FILE_NAME) {
+            manifest.push_str(&path_rel.to_string_lossy());
+            manifest.push('\n');
+        }
+    }
+    fs.write(&output_dir.join(MANIFEST_FILE_NAME), manifest.as_bytes())
+}
+
+// Outputs that hosts with case-insensitive file systems cannot tell apart,
+// as `(output, source, other output, other source)`.
+pub fn case_collisions(
+    outputs: &HashMap<PathBuf, PathBuf>,
+) -> Vec<(&Path, &Path, &Path, &Path)> {
+    let mut sorted: Vec<_> = outputs
+        .iter()
+        .map(|(output, source)| (output.as_path(), source.as_path()))
+        .collect();
+    sorted.sort();
+    let mut folded = HashMap::new();
+    let mut collisions = Vec::new();
+    for (output, source) in sorted {
+        match folded.entry(output.to_string_lossy().to_lowercase()) {
+            Entry::Occupied(entry) => {
+                let (other, other_source) = *entry.get();
+                collisions.push((output, source, other, other_source));
+            },
+            Entry::Vacant(entry) => {
+                entry.insert((output, source));
+            },
+        }
+    }
+    collisions
+}
@@ -0,0 +1,177 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{site_fs::SiteFs, ssg::LinSsg};
+
+#[derive(Debug, Error)]
+pub enum PackError {
+    #[error("Failed to read site config {}", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("Failed to parse site config {}", .0.display())]
+    Toml(PathBuf, #[source] toml::de::Error),
+    #[error("Pack {} was already registered", .0)]
+    DuplicatedPack(String),
+    #[error("Pack {} is not registered", .0)]
+    UnknownPack(String),
+    #[error("Pack {} depends on {}, which is not registered", .pack, .dependency)]
+    MissingDependency { pack: String, dependency: String },
+    #[error("Pack {} depends on {}, which is disabled", .pack, .dependency)]
+    DisabledDependency { pack: String, dependency: String },
+    #[error("Pack {} depends on itself", .0)]
+    DependencyCycle(String),
+}
+
+pub trait Pack: Send + Sync + 'static {
+    fn name(&self) -> &str;
+
+    fn install(&self, ssg: &mut LinSsg);
+
+    fn doc(&self) -> String;
+
+    fn dependencies(&self) -> &[&str] {
+        &[]
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PackConfig {
+    pub enabled: Option<Vec<String>>,
+    pub disabled: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+struct SiteConfig {
+    packs: PackConfig,
+}
+
+impl PackConfig {
+    pub fn load(fs: &dyn SiteFs, path: &Path) -> Result<Self, PackError> {
+        let code = match fs.read_to_string(path) {
+            Ok(code) => code,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            },
+            Err(error) => Err(PackError::Io(path.to_owned(), error))?,
+        };
+        let site_config: SiteConfig = toml::from_str(&code)
+            .map_err(|error| PackError::Toml(path.to_owned(), error))?;
+        Ok(site_config.packs)
+    }
+
+    pub fn is_disabled(&self, name: &str) -> bool {
+        self.disabled.iter().any(|disabled| disabled == name)
+    }
+}
+
+#[derive(Default)]
+pub struct PackRegistry {
+    packs: BTreeMap<String, Box<dyn Pack>>,
+}
+
+impl fmt::Debug for PackRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PackRegistry")
+            .field("packs", &self.packs.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl PackRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<P>(mut self, pack: P) -> Result<Self, PackError>
+    where
+        P: Pack,
+    {
+        self.register(pack)?;
+        Ok(self)
+    }
+
+    pub fn register<P>(&mut self, pack: P) -> Result<(), PackError>
+    where
+        P: Pack,
+    {
+        let name = pack.name().to_owned();
+        if self.packs.contains_key(&name) {
+            return Err(PackError::DuplicatedPack(name));
+        }
+        self.packs.insert(name, Box::new(pack));
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Pack> {
+        self.packs.get(name).map(AsRef::as_ref)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.packs.keys().map(AsRef::as_ref)
+    }
+
+    pub fn resolve(
+        &self,
+        config: &PackConfig,
+    ) -> Result<Vec<&dyn Pack>, PackError> {
+        let requested: Vec<&str> = match &config.enabled {
+            Some(enabled) => enabled.iter().map(AsRef::as_ref).collect(),
+            None => {
+                self.names().filter(|name| !config.is_disabled(name)).collect()
+            },
+        };
+        let mut resolved = Vec::new();
+        let mut done = BTreeSet::new();
+        let mut visiting = BTreeSet::new();
+        for name in requested {
+            let pack = self
+                .get(name)
+                .ok_or_else(|| PackError::UnknownPack(name.to_owned()))?;
+            self.visit(pack, config, &mut visiting, &mut done, &mut resolved)?;
+        }
+        Ok(resolved)
+    }
+
+    fn visit<'a>(
+        &'a self,
+        pack: &'a dyn Pack,
+        config: &PackConfig,
+        visiting: &mut BTreeSet<&'a str>,
+        done: &mut BTreeSet<&'a str>,
+        resolved: &mut Vec<&'a dyn Pack>,
+    ) -> Result<(), PackError> {
+        if done.contains(pack.name()) {
+            return Ok(());
+        }
+        if !visiting.insert(pack.name()) {
+            Err(PackError::DependencyCycle(pack.name().to_owned()))?
+        }
+        for &dependency in pack.dependencies() {
+            if config.is_disabled(dependency) {
+                Err(PackError::DisabledDependency {
+                    pack: pack.name().to_owned(),
+                    dependency: dependency.to_owned(),
+                })?
+            }
+            let dependency_pack = self.get(dependency).ok_or_else(|| {
+                PackError::MissingDependency {
+                    pack: pack.name().to_owned(),
+                    dependency: dependency.to_owned(),
+                }
+            })?;
+            self.visit(dependency_pack, config, visiting, done, resolved)?;
+        }
+        visiting.remove(pack.name());
+        done.insert(pack.name());
+        resolved.push(pack);
+        Ok(())
+    }
+}
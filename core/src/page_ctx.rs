@@ -0,0 +1,66 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use serde_json::{Map, Value};
+
+use crate::{route, Config};
+
+#[derive(Debug, Clone, Default)]
+pub struct PageCtx {
+    path: Option<PathBuf>,
+    url: Option<String>,
+    metadata: Map<String, Value>,
+    config: Option<Arc<Config>>,
+}
+
+impl PageCtx {
+    pub fn new(config: Arc<Config>) -> Self {
+        Self { config: Some(config), ..Self::default() }
+    }
+
+    pub fn with_page(
+        mut self,
+        path: impl Into<PathBuf>,
+        metadata: Map<String, Value>,
+    ) -> Self {
+        let path = path.into();
+        self.url = Some(route::url_of(&path));
+        self.path = Some(path);
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    pub fn metadata(&self) -> &Map<String, Value> {
+        &self.metadata
+    }
+
+    pub fn config(&self) -> Option<&Config> {
+        self.config.as_deref()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CurrentPage {
+    inner: Arc<RwLock<Arc<PageCtx>>>,
+}
+
+impl CurrentPage {
+    pub fn get(&self) -> Arc<PageCtx> {
+        self.inner.read().unwrap_or_else(|error| error.into_inner()).clone()
+    }
+
+    pub fn set(&self, ctx: PageCtx) {
+        *self.inner.write().unwrap_or_else(|error| error.into_inner()) =
+            Arc::new(ctx);
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    Function,
+    Filter,
+    Tester,
+    Constant,
+    Symbol,
+    Pack,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReferenceItem {
+    pub name: String,
+    pub kind: ItemKind,
+    pub doc: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Reference {
+    items: BTreeMap<(ItemKind, String), ReferenceItem>,
+}
+
+impl Reference {
+    pub fn insert(
+        &mut self,
+        kind: ItemKind,
+        name: impl Into<String>,
+        doc: impl Into<String>,
+    ) {
+        let name = name.into();
+        let item = ReferenceItem { name: name.clone(), kind, doc: doc.into() };
+        self.items.insert((kind, name), item);
+    }
+
+    pub fn insert_value(
+        &mut self,
+        kind: ItemKind,
+        name: impl Into<String>,
+        value: &Value,
+    ) {
+        self.insert(kind, name, value.to_string());
+    }
+
+    pub fn get(&self, kind: ItemKind, name: &str) -> Option<&ReferenceItem> {
+        self.items.get(&(kind, name.to_owned()))
+    }
+
+    pub fn of_kind(
+        &self,
+        kind: ItemKind,
+    ) -> impl Iterator<Item = &ReferenceItem> + '_ {
+        self.items.values().filter(move |item| item.kind == kind)
+    }
+}
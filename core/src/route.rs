@@ -0,0 +1,185 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::markdown::page::Page;
+
+pub const INDEX_FILE_NAME: &str = "index.html";
+
+#[derive(Debug, Error)]
+pub enum RouteError {
+    #[error("Invalid permalink {:?}", .0)]
+    InvalidPermalink(String),
+    #[error("Invalid slug {:?}", .0)]
+    InvalidSlug(String),
+    #[error("Invalid alias {:?}", .0)]
+    InvalidAlias(String),
+    #[error("Page sets both a permalink and a slug")]
+    PermalinkAndSlug,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PageRoute {
+    pub source: PathBuf,
+    pub output_rel: PathBuf,
+    pub url: String,
+    pub lang: Option<String>,
+    pub translation_key: String,
+    pub aliases: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TranslationLink<'a> {
+    pub lang: &'a str,
+    pub url: &'a str,
+}
+
+impl PageRoute {
+    pub fn new(
+        source: PathBuf,
+        source_rel: &Path,
+        page: &Page,
+        languages: &[String],
+    ) -> Result<Self, RouteError> {
+        let dir_lang = source_rel
+            .parent()
+            .and_then(|parent| parent.components().next())
+            .and_then(|first| first.as_os_str().to_str())
+            .filter(|first| languages.iter().any(|lang| lang == first));
+        let lang = page
+            .lang
+            .as_deref()
+            .or(dir_lang)
+            .or(languages.first().map(String::as_str));
+        let unprefixed_rel = match dir_lang {
+            Some(dir_lang) => {
+                source_rel.strip_prefix(dir_lang).unwrap_or(source_rel)
+            },
+            None => source_rel,
+        };
+
+        let mut output_rel = match (&page.permalink, &page.slug) {
+            (None, None) => default_output(source_rel),
+            (Some(_), Some(_)) => Err(RouteError::PermalinkAndSlug)?,
+            (Some(permalink), None) => permalink_output(permalink)?,
+            (None, Some(slug)) => slug_output(source_rel, slug)?,
+        };
+        let is_default_lang = lang.is_none_or(|lang| {
+            languages.first().is_some_and(|first| first == lang)
+        });
+        if page.permalink.is_none() && !is_default_lang && lang != dir_lang {
+            let lang_rel = match dir_lang {
+                Some(dir_lang) => {
+                    output_rel.strip_prefix(dir_lang).unwrap_or(&output_rel)
+                },
+                None => &output_rel,
+            };
+            output_rel = Path::new(lang.unwrap_or_default()).join(lang_rel);
+        }
+        let url = url_of(&output_rel);
+        let translation_key = match &page.translation_key {
+            Some(translation_key) => translation_key.clone(),
+            None => url_of(&default_output(unprefixed_rel)),
+        };
+        let aliases = page
+            .aliases
+            .iter()
+            .map(|alias| {
+                permalink_output(alias)
+                    .map_err(|_| RouteError::InvalidAlias(alias.clone()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            source,
+            output_rel,
+            url,
+            lang: lang.map(ToOwned::to_owned),
+            translation_key,
+            aliases,
+        })
+    }
+}
+
+pub fn url_of(output_rel: &Path) -> String {
+    let mut segments: Vec<_> = output_rel
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    let is_index = segments.last().is_some_and(|last| last == INDEX_FILE_NAME);
+    if is_index {
+        segments.pop();
+    }
+    let mut url = String::new();
+    for segment in &segments {
+        url.push('/');
+        url.push_str(segment);
+    }
+    if is_index || segments.is_empty() {
+        url.push('/');
+    }
+    url
+}
+
+pub fn redirect_html(url: &str) -> String {
+    let url = tera::escape_html(url);
+    format!(
+        concat!(
+            "<!DOCTYPE html>",
+            "<html><head>",
+            "<meta charset=\"utf-8\">",
+            "<meta http-equiv=\"refresh\" content=\"0; url={url}\">",
+            "<link rel=\"canonical\" href=\"{url}\">",
+            "</head><body>",
+            "<a href=\"{url}\">{url}</a>",
+            "</body></html>",
+        ),
+        url = url,
+    )
+}
+
+fn default_output(source_rel: &Path) -> PathBuf {
+    let mut output = source_rel.to_owned();
+    match source_rel.file_stem() {
+        Some(stem) if !stem.eq_ignore_ascii_case("index") => {
+            output.set_file_name(stem);
+            output.push(INDEX_FILE_NAME);
+        },
+        _ => {
+            output.set_extension("html");
+        },
+    }
+    output
+}
+
+fn permalink_output(permalink: &str) -> Result<PathBuf, RouteError> {
+    let mut output = PathBuf::new();
+    for segment in permalink.split('/').filter(|s| !s.is_empty()) {
+        if !is_path_segment(segment) {
+            Err(RouteError::InvalidPermalink(permalink.to_owned()))?
+        }
+        output.push(segment);
+    }
+    if output.extension().is_none_or(|ext| ext != "html") {
+        output.push(INDEX_FILE_NAME);
+    }
+    Ok(output)
+}
+
+fn slug_output(source_rel: &Path, slug: &str) -> Result<PathBuf, RouteError> {
+    let default = default_output(source_rel);
+    let directory = default.parent().filter(|directory| {
+        directory.file_name().is_some() && is_path_segment(slug)
+    });
+    let Some(directory) = directory else {
+        Err(RouteError::InvalidSlug(slug.to_owned()))?
+    };
+    Ok(directory.with_file_name(slug).join(INDEX_FILE_NAME))
+}
+
+fn is_path_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment != "."
+        && segment != ".."
+        && !segment.contains(['/', '\\'])
+}
@@ -0,0 +1,104 @@
+(function () {
+  "use strict";
+
+  var script = document.currentScript;
+  var indexUrl = (script && script.dataset.index) || "/search-index.json";
+  var index = null;
+
+  function load() {
+    if (index === null) {
+      index = fetch(indexUrl).then(function (response) {
+        return response.json();
+      });
+    }
+    return index;
+  }
+
+  function score(entry, terms) {
+    var total = 0;
+    var title = entry.title.toLowerCase();
+    var headings = entry.headings.map(function (heading) {
+      return heading.text.toLowerCase();
+    });
+    var content = entry.content.toLowerCase();
+    var tags = entry.tags.map(function (tag) {
+      return tag.toLowerCase();
+    });
+    for (var i = 0; i < terms.length; i++) {
+      var term = terms[i];
+      var found = 0;
+      if (title.indexOf(term) >= 0) found += 10;
+      if (tags.indexOf(term) >= 0) found += 5;
+      headings.forEach(function (heading) {
+        if (heading.indexOf(term) >= 0) found += 3;
+      });
+      if (content.indexOf(term) >= 0) found += 1;
+      if (found === 0) return 0;
+      total += found;
+    }
+    return total;
+  }
+
+  function anchor(entry, terms) {
+    for (var i = 0; i < entry.headings.length; i++) {
+      var heading = entry.headings[i];
+      var text = heading.text.toLowerCase();
+      if (heading.id && terms.some(function (term) {
+        return text.indexOf(term) >= 0;
+      })) {
+        return entry.url + "#" + heading.id;
+      }
+    }
+    return entry.url;
+  }
+
+  function search(entries, query) {
+    var terms = query.toLowerCase().split(/\s+/).filter(Boolean);
+    if (terms.length === 0) return [];
+    return entries
+      .map(function (entry) {
+        return { entry: entry, score: score(entry, terms), terms: terms };
+      })
+      .filter(function (result) {
+        return result.score > 0;
+      })
+      .sort(function (a, b) {
+        return b.score - a.score;
+      });
+  }
+
+  function render(results, output) {
+    output.textContent = "";
+    results.forEach(function (result) {
+      var item = document.createElement("li");
+      var link = document.createElement("a");
+      link.href = anchor(result.entry, result.terms);
+      link.textContent = result.entry.title;
+      item.appendChild(link);
+      output.appendChild(item);
+    });
+  }
+
+  function attach(input) {
+    var output = document.querySelector(input.dataset.search);
+    if (!output) return;
+    input.addEventListener("input", function () {
+      var query = input.value;
+      load().then(function (entries) {
+        if (input.value === query) render(search(entries, query), output);
+      });
+    });
+  }
+
+  document.addEventListener("DOMContentLoaded", function () {
+    document.querySelectorAll("input[data-search]").forEach(attach);
+  });
+
+  window.linSsgSearch = function (query) {
+    return load().then(function (entries) {
+      return search(entries, query).map(function (result) {
+        return result.entry;
+      });
+    });
+  };
+})();
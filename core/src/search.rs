@@ -0,0 +1,146 @@
+use serde::Serialize;
+
+use crate::route::PageRoute;
+
+pub const INDEX_FILE_NAME: &str = "search-index.json";
+
+pub const SCRIPT_FILE_NAME: &str = "search.js";
+
+pub const SCRIPT: &str = include_str!("search.js");
+
+pub const CONTENT_START: &str = "<!--lin-ssg:search-start-->";
+
+pub const CONTENT_END: &str = "<!--lin-ssg:search-end-->";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchHeading {
+    pub id: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchEntry {
+    pub url: String,
+    pub title: String,
+    pub lang: Option<String>,
+    pub tags: Vec<String>,
+    pub headings: Vec<SearchHeading>,
+    pub content: String,
+}
+
+impl SearchEntry {
+    pub fn extract(
+        rendered: &str,
+        route: &PageRoute,
+        title: String,
+        tags: Vec<String>,
+    ) -> Self {
+        let content = rendered
+            .split_once(CONTENT_START)
+            .and_then(|(_, rest)| rest.split_once(CONTENT_END))
+            .map_or(rendered, |(content, _)| content);
+        Self {
+            url: route.url.clone(),
+            title,
+            lang: route.lang.clone(),
+            tags,
+            headings: headings(content),
+            content: plain_text(content),
+        }
+    }
+}
+
+pub fn strip_markers(rendered: &str) -> String {
+    rendered.replace(CONTENT_START, "").replace(CONTENT_END, "")
+}
+
+fn headings(html: &str) -> Vec<SearchHeading> {
+    let mut headings = Vec::new();
+    let mut rest = html;
+    while let Some(start) = find_heading_open(rest) {
+        let level = &rest[start + 1 .. start + 3];
+        let Some(open_end) = rest[start ..].find('>') else { break };
+        let open_tag = &rest[start .. start + open_end];
+        let body_start = start + open_end + 1;
+        let close = format!("</{level}>");
+        let Some(body_len) = rest[body_start ..].find(&close) else { break };
+        let text = plain_text(&rest[body_start .. body_start + body_len]);
+        headings.push(SearchHeading { id: attribute(open_tag, "id"), text });
+        rest = &rest[body_start + body_len + close.len() ..];
+    }
+    headings
+}
+
+fn find_heading_open(html: &str) -> Option<usize> {
+    html.match_indices("<h").map(|(index, _)| index).find(|&index| {
+        let bytes = html.as_bytes();
+        bytes
+            .get(index + 2)
+            .is_some_and(|digit| (b'1' ..= b'6').contains(digit))
+            && bytes.get(index + 3).is_some_and(|&ch| ch == b'>' || ch == b' ')
+    })
+}
+
+pub fn attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!(" {name}=\"");
+    let start = tag.find(&pattern)? + pattern.len();
+    let len = tag[start ..].find('"')?;
+    Some(decode_entities(&tag[start .. start + len]))
+}
+
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for ch in html.chars() {
+        match ch {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            },
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(ch),
+            _ => (),
+        }
+    }
+    decode_entities(&text).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[.. start]);
+        rest = &rest[start ..];
+        let entity = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let ch = match &rest[1 .. end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                code => {
+                    let code = code.strip_prefix('#')?;
+                    let number = match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => code.parse().ok()?,
+                    };
+                    char::from_u32(number)?
+                },
+            };
+            Some((ch, end))
+        });
+        match entity {
+            Some((ch, end)) => {
+                decoded.push(ch);
+                rest = &rest[end + 1 ..];
+            },
+            None => {
+                decoded.push('&');
+                rest = &rest[1 ..];
+            },
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
@@ -0,0 +1,71 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+
+use serde_json::Value;
+use tera::{Context, Tera};
+
+use crate::{theme, Config};
+
+pub const SHORTCODE_EXTENSION: &str = "html";
+
+#[derive(Debug, Clone)]
+pub struct ShortcodeRenderer {
+    tera: Arc<Tera>,
+    globals: Arc<RwLock<Context>>,
+}
+
+impl ShortcodeRenderer {
+    pub fn load(config: &Config) -> Result<Self, tera::Error> {
+        let glob =
+            config.shortcode_dir().join(format!("**/*.{SHORTCODE_EXTENSION}"));
+        let mut tera = Tera::default();
+        tera.add_raw_templates(theme::read_glob(config, &glob)?)?;
+        Ok(Self {
+            tera: Arc::new(tera),
+            globals: Arc::new(RwLock::new(Context::new())),
+        })
+    }
+
+    pub fn set_globals(&self, globals: Context) {
+        *self.globals.write().unwrap_or_else(|error| error.into_inner()) =
+            globals;
+    }
+
+    pub fn names(&self) -> HashSet<String> {
+        self.tera
+            .get_template_names()
+            .filter_map(|name| {
+                name.strip_suffix(SHORTCODE_EXTENSION)?
+                    .strip_suffix('.')
+                    .map(ToOwned::to_owned)
+            })
+            .collect()
+    }
+
+    pub fn render(
+        &self,
+        args: &HashMap<String, Value>,
+        body: Option<&Value>,
+    ) -> tera::Result<Value> {
+        let Some(name) = args.get("name").and_then(Value::as_str) else {
+            Err(tera::Error::msg("shortcode requires a string name argument"))?
+        };
+        let mut context = self
+            .globals
+            .read()
+            .unwrap_or_else(|error| error.into_inner())
+            .clone();
+        for (key, value) in args {
+            if key != "name" {
+                context.insert(key, value);
+            }
+        }
+        if let Some(body) = body {
+            context.insert("body", body);
+        }
+        let template = format!("{name}.{SHORTCODE_EXTENSION}");
+        Ok(Value::String(self.tera.render(&template, &context)?))
+    }
+}
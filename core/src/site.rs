@@ -0,0 +1,53 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::site_fs::SiteFs;
+
+pub const CONTEXT_KEY: &str = "site";
+
+#[derive(Debug, Error)]
+pub enum SiteConfigError {
+    #[error("Could not read site config {}", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("Invalid TOML in site config {}", .0.display())]
+    Toml(PathBuf, #[source] Box<toml::de::Error>),
+    #[error("Invalid menu in site config {}", .0.display())]
+    Menu(PathBuf, #[source] Box<toml::de::Error>),
+}
+
+// A missing config is an empty one, like for the packs.
+pub fn load_config(
+    fs: &dyn SiteFs,
+    path: &Path,
+) -> Result<toml::Table, SiteConfigError> {
+    let code = match fs.read_to_string(path) {
+        Ok(code) => code,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return Ok(toml::Table::new())
+        },
+        Err(error) => Err(SiteConfigError::Io(path.to_owned(), error))?,
+    };
+    toml::from_str(&code).map_err(|error| {
+        SiteConfigError::Toml(path.to_owned(), Box::new(error))
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Site<'a> {
+    pub pages: Vec<SitePage<'a>>,
+    pub config: &'a toml::Table,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SitePage<'a> {
+    pub url: &'a str,
+    pub title: &'a str,
+    pub lang: Option<&'a str>,
+    pub metadata: Value,
+}
@@ -0,0 +1,367 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    File,
+    Dir,
+    Other,
+}
+
+pub trait SiteFs: fmt::Debug + Send + Sync + 'static {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    // Fails with `AlreadyExists` instead of replacing the file.
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    // Paths of the entries, sorted by file name.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    // Follows symbolic links.
+    fn kind(&self, path: &Path) -> io::Result<FileKind>;
+
+    fn is_symlink(&self, _path: &Path) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.kind(path).is_ok_and(|kind| kind == FileKind::Dir)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.kind(path).is_ok_and(|kind| kind == FileKind::File)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFs;
+
+impl SiteFs for RealFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        File::create_new(path)?.write_all(contents)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+        Ok(entries)
+    }
+
+    fn kind(&self, path: &Path) -> io::Result<FileKind> {
+        let metadata = fs::metadata(path)?;
+        if metadata.is_dir() {
+            Ok(FileKind::Dir)
+        } else if metadata.is_file() {
+            Ok(FileKind::File)
+        } else {
+            Ok(FileKind::Other)
+        }
+    }
+
+    fn is_symlink(&self, path: &Path) -> io::Result<bool> {
+        Ok(fs::symlink_metadata(path)?.is_symlink())
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+}
+
+// Directories map to `None`.
+type Entries = BTreeMap<PathBuf, Option<Vec<u8>>>;
+
+// Paths are resolved lexically, and the empty path is the root, which always
+// exists. Clones share the same files.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFs {
+    entries: Arc<RwLock<Entries>>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_file(
+        self,
+        path: impl AsRef<Path>,
+        contents: impl Into<Vec<u8>>,
+    ) -> Self {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            let _ = self.create_dir_all(parent);
+        }
+        let _ = self.write(path, &contents.into());
+        self
+    }
+
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.read_entries()
+            .iter()
+            .filter(|(_, contents)| contents.is_some())
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    fn read_entries(&self) -> RwLockReadGuard<'_, Entries> {
+        self.entries.read().unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn write_entries(&self) -> RwLockWriteGuard<'_, Entries> {
+        self.entries.write().unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn check_parent(entries: &Entries, path: &Path) -> io::Result<()> {
+        match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => {
+                match entries.get(parent) {
+                    Some(None) => Ok(()),
+                    Some(Some(_)) => Err(not_a_dir()),
+                    None => Err(not_found()),
+                }
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+impl SiteFs for MemoryFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.read_entries().get(&normalize(path)) {
+            Some(Some(contents)) => Ok(contents.clone()),
+            Some(None) => Err(is_a_dir()),
+            None => Err(not_found()),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.write_entries();
+        Self::check_parent(&entries, &path)?;
+        match entries.get(&path) {
+            Some(None) => Err(is_a_dir()),
+            _ if path.as_os_str().is_empty() => Err(is_a_dir()),
+            _ => {
+                entries.insert(path, Some(contents.to_vec()));
+                Ok(())
+            },
+        }
+    }
+
+    fn create_new(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = normalize(path);
+        if self.read_entries().contains_key(&path) {
+            Err(io::Error::from(io::ErrorKind::AlreadyExists))?;
+        }
+        self.write(&path, contents)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.write_entries();
+        let mut ancestors: Vec<_> = path
+            .ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .collect();
+        ancestors.reverse();
+        for ancestor in ancestors {
+            match entries.get(ancestor) {
+                Some(Some(_)) => Err(not_a_dir())?,
+                Some(None) => (),
+                None => {
+                    entries.insert(ancestor.to_owned(), None);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let path = normalize(path);
+        let entries = self.read_entries();
+        if !path.as_os_str().is_empty() {
+            match entries.get(&path) {
+                Some(None) => (),
+                Some(Some(_)) => Err(not_a_dir())?,
+                None => Err(not_found())?,
+            }
+        }
+        // Keys sort component-wise, so siblings already come out sorted by
+        // file name.
+        Ok(entries
+            .keys()
+            .filter(|entry| entry.parent() == Some(&path))
+            .cloned()
+            .collect())
+    }
+
+    fn kind(&self, path: &Path) -> io::Result<FileKind> {
+        let path = normalize(path);
+        if path.as_os_str().is_empty() {
+            return Ok(FileKind::Dir);
+        }
+        match self.read_entries().get(&path) {
+            Some(Some(_)) => Ok(FileKind::File),
+            Some(None) => Ok(FileKind::Dir),
+            None => Err(not_found()),
+        }
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.kind(path)?;
+        Ok(normalize(path))
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (from, to) = (normalize(from), normalize(to));
+        let mut entries = self.write_entries();
+        Self::check_parent(&entries, &to)?;
+        let moved: Vec<_> = entries
+            .keys()
+            .filter(|entry| entry.starts_with(&from))
+            .cloned()
+            .collect();
+        if moved.is_empty() {
+            Err(not_found())?;
+        }
+        for entry in moved {
+            if let Some(contents) = entries.remove(&entry) {
+                let moved_to = match entry.strip_prefix(&from) {
+                    Ok(suffix) if !suffix.as_os_str().is_empty() => {
+                        to.join(suffix)
+                    },
+                    _ => to.clone(),
+                };
+                entries.insert(moved_to, contents);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.write_entries();
+        match entries.get(&path) {
+            Some(Some(_)) => {
+                entries.remove(&path);
+                Ok(())
+            },
+            Some(None) => Err(is_a_dir()),
+            None => Err(not_found()),
+        }
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.write_entries();
+        match entries.get(&path) {
+            Some(None) => {
+                if entries.keys().any(|entry| entry.parent() == Some(&path)) {
+                    Err(io::Error::from(io::ErrorKind::DirectoryNotEmpty))?;
+                }
+                entries.remove(&path);
+                Ok(())
+            },
+            Some(Some(_)) => Err(not_a_dir()),
+            None => Err(not_found()),
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.write_entries();
+        match entries.get(&path) {
+            Some(None) => {
+                entries.retain(|entry, _| !entry.starts_with(&path));
+                Ok(())
+            },
+            Some(Some(_)) => Err(not_a_dir()),
+            None => Err(not_found()),
+        }
+    }
+}
+
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                normalized.pop();
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn not_found() -> io::Error {
+    io::Error::from(io::ErrorKind::NotFound)
+}
+
+fn not_a_dir() -> io::Error {
+    io::Error::from(io::ErrorKind::NotADirectory)
+}
+
+fn is_a_dir() -> io::Error {
+    io::Error::from(io::ErrorKind::IsADirectory)
+}
@@ -0,0 +1,53 @@
+use crate::{
+    minify,
+    transform::{Transform, TransformError},
+};
+
+pub const TARGET_ID: &str = "main-content";
+pub const LINK_CLASS: &str = "skip-link";
+pub const LINK_TEXT: &str = "Skip to content";
+
+pub fn target() -> String {
+    format!("<a id=\"{TARGET_ID}\" tabindex=\"-1\"></a>")
+}
+
+fn body_tag_end(html: &str) -> Option<usize> {
+    let mut position = 0;
+    while let Some(found) = html[position ..].find('<') {
+        let start = position + found;
+        let tag = &html[start ..];
+        let is_body = tag
+            .get(1 .. 5)
+            .is_some_and(|name| name.eq_ignore_ascii_case("body"))
+            && tag[5 ..]
+                .starts_with(|ch: char| ch.is_whitespace() || ch == '>');
+        if is_body {
+            return Some(start + minify::tag_len(tag));
+        }
+        position = start + 1;
+    }
+    None
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SkipLink;
+
+impl Transform for SkipLink {
+    fn name(&self) -> &str {
+        "skip_link"
+    }
+
+    fn transform(&self, html: &str) -> Result<String, TransformError> {
+        // Pages whose layout drops the content block have nothing to skip to.
+        let has_target = html.contains(&format!("id=\"{TARGET_ID}\""));
+        let Some(insert_at) = body_tag_end(html).filter(|_| has_target) else {
+            return Ok(html.to_owned());
+        };
+        Ok(format!(
+            "{}<a class=\"{LINK_CLASS}\" \
+             href=\"#{TARGET_ID}\">{LINK_TEXT}</a>{}",
+            &html[.. insert_at],
+            &html[insert_at ..]
+        ))
+    }
+}
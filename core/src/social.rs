@@ -0,0 +1,116 @@
+use std::convert::Infallible;
+
+use serde_json::Value;
+use tera::escape_html;
+
+use crate::{
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+};
+
+pub const HEAD_META_BLOCK: &str = "head_meta";
+
+pub fn absolute_url(base_url: Option<&str>, url: &str) -> String {
+    let is_absolute = url.starts_with("//")
+        || url.split_once("://").is_some_and(|(scheme, _)| {
+            !scheme.is_empty()
+                && scheme.chars().all(|ch| ch.is_ascii_alphanumeric())
+        });
+    match base_url {
+        Some(base_url) if !is_absolute => format!(
+            "{}/{}",
+            base_url.trim_end_matches('/'),
+            url.trim_start_matches('/')
+        ),
+        _ => url.to_owned(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SocialMetaArgs<'a> {
+    title: Option<&'a str>,
+    description: Option<&'a str>,
+    image: Option<&'a str>,
+}
+
+impl<'a> Args<'a> for SocialMetaArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let title = args.retrive_arg_with_default("title", || None)?;
+        let description =
+            args.retrive_arg_with_default("description", || None)?;
+        let image = args.retrive_arg_with_default("image", || None)?;
+        Ok(Self { title, description, image })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SocialMetaFn;
+
+impl Function for SocialMetaFn {
+    type Args<'a> = SocialMetaArgs<'a>;
+    type Output = String;
+    type Error = Infallible;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let metadata = |key: &str| {
+            ctx.metadata()
+                .get(key)
+                .and_then(Value::as_str)
+                .filter(|value| !value.is_empty())
+        };
+        let base_url = ctx.config().and_then(|config| config.base_url());
+        let title = args.title.or_else(|| metadata("title"));
+        let description = args.description.or_else(|| metadata("description"));
+        let image = args
+            .image
+            .or_else(|| metadata("image"))
+            .map(|image| absolute_url(base_url, image));
+        let url = ctx.url().map(|url| absolute_url(base_url, url));
+
+        let card =
+            if image.is_some() { "summary_large_image" } else { "summary" };
+        let tags = [
+            ("property", "og:type", Some("website")),
+            ("property", "og:title", title),
+            ("property", "og:description", description),
+            ("property", "og:url", url.as_deref()),
+            ("property", "og:image", image.as_deref()),
+            ("name", "twitter:card", Some(card)),
+            ("name", "twitter:title", title),
+            ("name", "twitter:description", description),
+            ("name", "twitter:image", image.as_deref()),
+        ];
+        let mut buf = String::new();
+        for (attr, name, content) in tags {
+            if let Some(content) = content {
+                buf.push_str(&format!(
+                    "<meta {attr}=\"{name}\" content=\"{}\">",
+                    escape_html(content)
+                ));
+            }
+        }
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        "{# OpenGraph and Twitter meta tags for the current page, taken from \
+         its title, description and image #}
+        social_meta(
+            {# overrides the page title #}
+            title:string?,
+            {# overrides the page description #}
+            description:string?,
+            {# overrides the page image, relative to the site base URL #}
+            image:string?
+        ) -> String "
+            .to_owned()
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
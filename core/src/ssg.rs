@@ -1,54 +1,292 @@
 use std::{
-    borrow::Cow,
-    collections::{HashMap, HashSet},
-    error::Error,
-    fmt::Write as _,
-    fs::{self, File},
-    io::{self, Read, Write},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    error::Error as _,
+    fmt,
+    io,
+    mem,
+    ops::Range,
     path::{Path, PathBuf, StripPrefixError},
+    rc::Rc,
+    sync::{Arc, RwLock},
+    time::{Instant, SystemTime},
 };
 
+use ignore::gitignore::Gitignore;
+use lin_ssg_linguinput::{Table, TableLoadError, DEFAULT_TABLE_NAME};
 use serde::Serialize;
+use serde_json::{Map, Value};
 use tera::{Context, Tera};
 use thiserror::Error;
 
 use crate::{
-    function::{invoke_fn, Function},
-    markdown::page,
+    asset::{
+        self,
+        AssetFn,
+        AssetManifest,
+        AssetProcessError,
+        AssetProcessor,
+        AssetProcessors,
+        Fingerprinter,
+        ScssProcessor,
+    },
+    audio::{self, AudioFn},
+    backend::{
+        ContentBackend,
+        ContentBackends,
+        ContentWarning,
+        HtmlBackend,
+        PageCompiler,
+    },
+    breadcrumb,
+    bundle::{self, BundleError, BundleFn, BundleKind},
+    clock::{self, FixedNowFn},
+    config::NonPagePolicy,
+    csv_table::CsvTableFn,
+    data::{self, DataError},
+    diagnostic::{
+        self,
+        Diagnostic,
+        DiagnosticsSink,
+        DiagnosticsSinks,
+        Severity,
+        SourceSpan,
+    },
+    dictionary::{self, Dictionary},
+    dry_run::{self, BuildDiff},
+    epub::{self, Epub, EpubChapter, EpubError, EpubFont},
+    external::{self, ExternalLinks, LinkReport},
+    function::{
+        Filter,
+        Function,
+        TeraFilter,
+        TeraFunction,
+        TeraTester,
+        Tester,
+    },
+    glossary::{Glossary, GlossaryError, TermFn},
+    hook::{BuildHook, BuildHooks, HookError},
+    i18n::{TranslateFn, Translations, TranslationsError},
+    images::{ImageFn, ImagePipeline},
+    include::{self, IncludeError},
+    latex::{self, Chapter, LatexFallback},
+    lexicon::{LexFilterFn, LexFn, Lexicon, LexiconError},
+    markdown::{
+        backend::MarkdownBackend,
+        page::{self, RawPageParts},
+        to_html::{ToHtmlOptions, UnsupportedPolicy},
+        to_latex::{ToLatex, ToLatexCtx, ToLatexError, ToLatexOptions},
+    },
+    menu::{self, MenuEntry, MenuError},
+    minify,
+    observer::{BuildEvent, BuildObserver, BuildObservers, BuildPhase},
+    output,
+    pack::{PackConfig, PackError, PackRegistry},
+    page_ctx::{CurrentPage, PageCtx},
+    reference::{ItemKind, Reference},
+    route::{self, PageRoute, RouteError, TranslationLink},
+    search::{self, SearchEntry},
+    shortcode::ShortcodeRenderer,
+    site::{self, Site, SiteConfigError, SitePage},
+    site_fs::SiteFs,
+    skip_link::SkipLink,
+    social::SocialMetaFn,
+    summary,
+    template_check,
+    theme,
+    transform::{Transform, TransformError, Transforms},
+    typography::{self, Typography},
+    walk::{self, Walk, WalkEntry, WalkError},
+    wiki::{Backlink, WikiIndex, WikiUrlFn},
     Config,
 };
 
 #[derive(Debug, Error)]
 pub enum InitError {
-    #[error("Template directory path is not valid UTF-8")]
-    TemplateDirUtf8,
     #[error("Failed to initialize Tera")]
     Tera(
         #[source]
         #[from]
         tera::Error,
     ),
+    #[error("Failed to load lexicon")]
+    Lexicon(
+        #[source]
+        #[from]
+        LexiconError,
+    ),
+    #[error("Failed to load data files")]
+    Data(
+        #[source]
+        #[from]
+        DataError,
+    ),
+    #[error("Failed to load translations")]
+    Translations(
+        #[source]
+        #[from]
+        TranslationsError,
+    ),
+    #[error("Failed to load glossary")]
+    Glossary(
+        #[source]
+        #[from]
+        GlossaryError,
+    ),
+    #[error("Failed to load linguinput table")]
+    LinguinputTable(
+        #[source]
+        #[from]
+        TableLoadError,
+    ),
+    #[error("Failed to load site config")]
+    SiteConfig(
+        #[source]
+        #[from]
+        SiteConfigError,
+    ),
+    #[error("Failed to serialize template constant")]
+    Const(
+        #[source]
+        #[from]
+        serde_json::Error,
+    ),
 }
 
 #[derive(Debug, Error)]
-#[error("Error in {}", .path.display())]
+#[error("Error in {}{}", .path.display(), self.snippet())]
 pub struct BuildError {
     path: PathBuf,
     #[source]
-    kind: BuildErrorKind,
+    source: BuildErrorSource,
+    location: Option<Box<Location>>,
+}
+
+#[derive(Debug)]
+struct Location {
+    snippet: String,
+    span: Option<SourceSpan>,
+}
+
+impl From<WalkError> for BuildError {
+    fn from(error: WalkError) -> Self {
+        Self::on(error.path)(error.source)
+    }
 }
 
 impl BuildError {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn kind(&self) -> BuildErrorKind {
+        match &self.source {
+            BuildErrorSource::Tera(_) => BuildErrorKind::Template,
+            BuildErrorSource::Io(_) => BuildErrorKind::Io,
+            BuildErrorSource::NonUtf8Path
+            | BuildErrorSource::BadStripPrefix(_) => {
+                BuildErrorKind::InvalidPath
+            },
+            BuildErrorSource::Compile(_) | BuildErrorSource::Include(_) => {
+                BuildErrorKind::Compile
+            },
+            BuildErrorSource::Toml(_) => BuildErrorKind::Metadata,
+            BuildErrorSource::Json(_) => BuildErrorKind::Serialization,
+            BuildErrorSource::Bundle(_) | BuildErrorSource::AssetProcess(_) => {
+                BuildErrorKind::Asset
+            },
+            BuildErrorSource::Ignore(_) => BuildErrorKind::Ignore,
+            BuildErrorSource::Hook(_) => BuildErrorKind::Hook,
+            BuildErrorSource::Transform(_) => BuildErrorKind::Transform,
+            BuildErrorSource::Route(_) | BuildErrorSource::PageCollision(_) => {
+                BuildErrorKind::Route
+            },
+            BuildErrorSource::UnknownFormat(_)
+            | BuildErrorSource::UnknownBook(_)
+            | BuildErrorSource::Menu(_) => BuildErrorKind::Config,
+            BuildErrorSource::Latex(_)
+            | BuildErrorSource::Epub(_)
+            | BuildErrorSource::UnbuiltBookPage => BuildErrorKind::Export,
+            BuildErrorSource::NotAPage => BuildErrorKind::NotAPage,
+        }
+    }
+
     fn on<E>(path: impl Into<PathBuf>) -> impl FnOnce(E) -> Self
     where
-        BuildErrorKind: From<E>,
+        BuildErrorSource: From<E>,
     {
-        move |kind| Self { path: path.into(), kind: kind.into() }
+        move |source| Self {
+            path: path.into(),
+            source: source.into(),
+            location: None,
+        }
+    }
+
+    // The message joins the whole chain of causes, since there is no
+    // snippet to show under it.
+    pub fn diagnostic(&self) -> Diagnostic {
+        let mut message = self.source.to_string();
+        let mut cause = self.source.source();
+        while let Some(error) = cause {
+            message.push_str(": ");
+            message.push_str(&error.to_string());
+            cause = error.source();
+        }
+        Diagnostic::new(Severity::Error, &self.path, message).with_span(
+            self.location.as_ref().and_then(|location| location.span),
+        )
+    }
+
+    fn snippet(&self) -> &str {
+        self.location.as_ref().map_or("", |location| &location.snippet)
+    }
+
+    fn compile(path: &Path, code: &str, error: page::CompileError) -> Self {
+        let span = error.span();
+        Self::located(
+            path,
+            code,
+            span,
+            BuildErrorSource::Compile(Box::new(error)),
+        )
     }
+
+    fn located(
+        path: &Path,
+        code: &str,
+        span: Option<Range<usize>>,
+        source: BuildErrorSource,
+    ) -> Self {
+        let location = span.map(|span| {
+            Box::new(Location {
+                snippet: diagnostic::annotate(path, code, span.clone()),
+                span: SourceSpan::locate(code, span),
+            })
+        });
+        Self { path: path.to_owned(), source, location }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BuildErrorKind {
+    Io,
+    Template,
+    Compile,
+    Metadata,
+    InvalidPath,
+    Serialization,
+    Asset,
+    Ignore,
+    Hook,
+    Transform,
+    Route,
+    Config,
+    Export,
+    NotAPage,
 }
 
 #[derive(Debug, Error)]
-enum BuildErrorKind {
+enum BuildErrorSource {
     #[error(transparent)]
     Tera(#[from] tera::Error),
     #[error(transparent)]
@@ -58,47 +296,328 @@ enum BuildErrorKind {
     #[error(transparent)]
     BadStripPrefix(#[from] StripPrefixError),
     #[error(transparent)]
-    Compile(#[from] page::CompileError),
+    Compile(Box<page::CompileError>),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Toml(Box<toml::de::Error>),
+    #[error(transparent)]
+    Bundle(#[from] BundleError),
+    #[error(transparent)]
+    Latex(#[from] ToLatexError),
+    #[error(transparent)]
+    Ignore(#[from] ignore::Error),
+    #[error(transparent)]
+    AssetProcess(#[from] AssetProcessError),
+    #[error(transparent)]
+    Include(#[from] IncludeError),
+    #[error(transparent)]
+    Hook(#[from] HookError),
+    #[error(transparent)]
+    Transform(#[from] TransformError),
+    #[error(transparent)]
+    Route(#[from] RouteError),
+    #[error("Output path collides with page {}", .0.display())]
+    PageCollision(PathBuf),
+    #[error("Output format {} is not declared in the config", .0)]
+    UnknownFormat(String),
+    #[error("Book {} is not declared in the config", .0)]
+    UnknownBook(String),
+    #[error("Page of the book was not built")]
+    UnbuiltBookPage,
+    #[error("No content backend handles this file")]
+    NotAPage,
+    #[error(transparent)]
+    Menu(#[from] MenuError),
+    #[error(transparent)]
+    Epub(#[from] EpubError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildWarning {
+    path: PathBuf,
+    message: String,
+    span: Option<SourceSpan>,
+}
+
+impl BuildWarning {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> Option<SourceSpan> {
+        self.span
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, &self.path, &self.message)
+            .with_span(self.span)
+    }
+}
+
+impl fmt::Display for BuildWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Warning in {}", self.path.display())?;
+        if let Some(span) = self.span {
+            write!(f, ":{}:{}", span.line, span.column)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CompiledPage {
+    route: PageRoute,
+    context: Context,
+    body: String,
+    wiki_links: Vec<String>,
+    variants: Vec<PageVariant>,
+    summary_template: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct PageVariant {
+    format: String,
+    template: String,
+    output_rel: PathBuf,
+}
+
+impl CompiledPage {
+    fn title(&self) -> &str {
+        self.context.get("title").and_then(Value::as_str).unwrap_or_default()
+    }
+
+    fn site_page(&self) -> SitePage<'_> {
+        SitePage {
+            url: &self.route.url,
+            title: self.title(),
+            lang: self.route.lang.as_deref(),
+            metadata: self.context.clone().into_json(),
+        }
+    }
+
+    fn listing(&self) -> Value {
+        let mut listing = self.context.clone().into_json();
+        if let (Value::Object(listing), Ok(Value::Object(route))) =
+            (&mut listing, serde_json::to_value(&self.route))
+        {
+            listing.extend(route);
+        }
+        listing
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct LinSsg {
-    config: Config,
+    config: Arc<Config>,
+    current_page: CurrentPage,
     base_context: Context,
+    site_config: toml::Table,
+    menu_entries: Vec<MenuEntry>,
+    page_titles: HashMap<String, String>,
     tera: Tera,
-    pages: HashMap<String, tera::Context>,
-    docs: HashMap<String, String>,
+    // Every function, filter and tester of `tera`, without its templates, so
+    // a template reload starts over from the template files.
+    extensions: Tera,
+    latex_tera: Tera,
+    latex_fns: HashSet<String>,
+    pages: BTreeMap<String, CompiledPage>,
+    page_outputs: HashMap<PathBuf, PathBuf>,
+    reference: Reference,
+    shortcodes: ShortcodeRenderer,
+    asset_manifest: AssetManifest,
+    asset_processors: AssetProcessors,
+    backends: ContentBackends,
+    image_pipeline: ImagePipeline,
+    audio_fn: AudioFn,
+    builtin_theme: bool,
+    to_html_options: ToHtmlOptions,
+    lexicon: Arc<Lexicon>,
+    linguinput_table: Arc<Table>,
+    named_linguinput_tables: Arc<BTreeMap<String, Table>>,
+    wiki: WikiIndex,
+    glossary: Arc<Glossary>,
+    warnings: Vec<BuildWarning>,
+    observers: BuildObservers,
+    diagnostics: DiagnosticsSinks,
+    hooks: BuildHooks,
+    transforms: Transforms,
+    // The real output directory while a dry run builds into a staging one.
+    dry_run_output: Option<PathBuf>,
+    // Files in the output that no earlier build wrote, kept unless this build
+    // writes the same path.
+    foreign_outputs: Arc<RwLock<BTreeSet<PathBuf>>>,
 }
 
-impl LinSsg {
-    const ASSET_BUF_SIZE: usize = 8192;
+const EPUB_FORMAT: &str = "epub";
 
+const EPUB_DEFAULT_LANG: &str = "en";
+
+const SHORTCODE_FN: &str = "shortcode";
+
+impl LinSsg {
     pub(crate) fn new(config: Config) -> Result<Self, InitError> {
-        let template_dir = config
-            .template_dir_with_globs()
-            .to_str()
-            .ok_or(InitError::TemplateDirUtf8)?;
-        let tera = Tera::new(template_dir)?;
-        Ok(Self {
+        let (tera, builtin_theme) = theme::load_templates(&config)?;
+        let linguinput_table = match config.linguinput_table() {
+            Some(table_path) => {
+                read_table(config.fs(), Table::builtin(), table_path)?
+            },
+            None => Table::builtin(),
+        };
+        let mut named_linguinput_tables = BTreeMap::new();
+        for (name, table_path) in config.named_linguinput_tables() {
+            if name == DEFAULT_TABLE_NAME
+                || named_linguinput_tables.contains_key(name)
+            {
+                Err(TableLoadError::AlreadyRegistered(name.clone()))?;
+            }
+            let table = read_table(config.fs(), Table::empty(), table_path)?;
+            named_linguinput_tables.insert(name.clone(), table);
+        }
+        let asset_manifest = AssetManifest::default();
+        let asset_fn =
+            AssetFn::new(config.asset_fingerprints(), asset_manifest.clone());
+        let image_pipeline =
+            ImagePipeline::new(&config, asset_manifest.clone());
+        let image_fn = ImageFn::new(image_pipeline.clone());
+        let audio_fn = AudioFn::new(&config, asset_manifest.clone());
+        let bundle_fn = BundleFn::new(&config, asset_manifest.clone());
+        let shortcodes = ShortcodeRenderer::load(&config)?;
+        let mut to_html_options = ToHtmlOptions::from_config(&config);
+        to_html_options.shortcodes = shortcodes.names();
+        let lexicon =
+            Arc::new(Lexicon::load(config.fs(), config.lexicon_path())?);
+        let mut base_context = Context::new();
+        base_context.insert(
+            data::CONTEXT_KEY,
+            &data::load(config.fs(), config.data_dir())?,
+        );
+        let site_config =
+            site::load_config(config.fs(), config.site_config_path())?;
+        let menu_entries = menu::entries(&site_config).map_err(|error| {
+            SiteConfigError::Menu(
+                config.site_config_path().to_owned(),
+                Box::new(error),
+            )
+        })?;
+        let translations = Arc::new(Translations::load(
+            config.fs(),
+            config.translation_dir(),
+            config.languages(),
+        )?);
+        let glossary_url = config
+            .glossary_template()
+            .and_then(|_| config.glossary_dir().to_str());
+        let glossary = Arc::new(Glossary::load(
+            config.fs(),
+            config.glossary_path(),
+            glossary_url,
+        )?);
+        if config.glossary_autolink() {
+            to_html_options.glossary_terms = glossary.surface_forms();
+        }
+        let config = Arc::new(config);
+        let current_page = CurrentPage::default();
+        current_page.set(PageCtx::new(config.clone()));
+        let mut this = Self {
             config,
-            base_context: Context::new(),
+            current_page,
+            base_context,
+            site_config,
+            menu_entries,
+            page_titles: HashMap::new(),
             tera,
-            pages: HashMap::new(),
-            docs: HashMap::new(),
-        })
+            extensions: Tera::default(),
+            latex_tera: Tera::default(),
+            latex_fns: HashSet::new(),
+            pages: BTreeMap::new(),
+            page_outputs: HashMap::new(),
+            reference: Reference::default(),
+            shortcodes: shortcodes.clone(),
+            asset_manifest,
+            asset_processors: AssetProcessors::default(),
+            backends: ContentBackends::default(),
+            image_pipeline,
+            audio_fn: audio_fn.clone(),
+            builtin_theme,
+            to_html_options,
+            lexicon: lexicon.clone(),
+            linguinput_table: Arc::new(linguinput_table),
+            named_linguinput_tables: Arc::new(named_linguinput_tables),
+            wiki: WikiIndex::default(),
+            glossary: glossary.clone(),
+            warnings: Vec::new(),
+            observers: BuildObservers::default(),
+            diagnostics: DiagnosticsSinks::default(),
+            hooks: BuildHooks::default(),
+            transforms: Transforms::default(),
+            dry_run_output: None,
+            foreign_outputs: Arc::default(),
+        };
+        this.register_fn("asset", asset_fn);
+        this.register_fn("image", image_fn);
+        this.register_fn("audio", audio_fn);
+        this.register_fn("bundle", bundle_fn);
+        this.register_shortcodes(shortcodes);
+        this.register_fn("lex", LexFn::new(lexicon.clone()));
+        this.register_fn("lex_filter", LexFilterFn::new(lexicon));
+        this.register_fn("t", TranslateFn::new(translations));
+        this.register_fn("wiki_url", WikiUrlFn::new(this.wiki.clone()));
+        this.register_fn("term", TermFn::new(glossary));
+        this.register_fn("social_meta", SocialMetaFn);
+        this.register_fn("csv_table", CsvTableFn::new(&this.config));
+        if !this.config.languages().is_empty() {
+            let languages = this.config.languages().to_vec();
+            this.register_const("languages", languages)?;
+        }
+        if this.config.reproducible() {
+            let now = FixedNowFn::new(clock::source_date_epoch());
+            this.register_fn("now", now);
+        }
+        this.register_asset_processor(ScssProcessor);
+        let markdown = MarkdownBackend::new(this.config.page_extensions());
+        this.register_backend(markdown);
+        let html = HtmlBackend::new(this.config.html_page_extensions());
+        this.register_backend(html);
+        if this.config.skip_link() {
+            this.register_transform(SkipLink);
+        }
+        if this.config.smart_typography() {
+            this.register_transform(Typography);
+        }
+        if this.config.external_links() {
+            this.register_transform(ExternalLinks::new(
+                this.config.base_url().map(ToOwned::to_owned),
+                this.config.external_link_class(),
+            ));
+        }
+        Ok(this)
     }
 
     pub fn register_symbol(&mut self, name: impl Into<String>) {
         let name = name.into();
         let value = serde_json::Value::from(&name[..]);
-        self.register_const(name, value);
+        self.reference.insert_value(ItemKind::Symbol, &name, &value);
+        self.base_context.insert(name, &value);
     }
 
-    pub fn register_const<T>(&mut self, name: impl Into<String>, value: T)
+    pub fn register_const<T>(
+        &mut self,
+        name: impl Into<String>,
+        value: T,
+    ) -> Result<(), serde_json::Error>
     where
         T: Serialize,
     {
-        self.base_context.insert(name.into(), &value);
+        let name = name.into();
+        let json = serde_json::to_value(&value)?;
+        self.reference.insert_value(ItemKind::Constant, &name, &json);
+        self.base_context.insert(name, &json);
+        Ok(())
     }
 
     pub fn register_fn<F>(&mut self, name: impl Into<String>, fun: F)
@@ -106,206 +625,1672 @@ impl LinSsg {
         F: Function,
     {
         let name = name.into();
-        self.tera.register_function(
-            &name.clone(),
-            move |args: &HashMap<String, serde_json::Value>| match invoke_fn(
-                &name, &fun, args,
-            ) {
-                Ok(output) => Ok(output.into()),
-                Err(error) => {
-                    let mut buf = format!("error in {}(", name);
-                    for (i, (key, value)) in args.iter().enumerate() {
-                        if i > 0 {
-                            let _ = write!(buf, ", ");
-                        }
-                        let _ = write!(buf, "{}={}", key, value);
-                    }
-                    let _ = write!(buf, "):\n");
-                    let mut next_source = Some(&error as &dyn Error);
-                    while let Some(source) = next_source {
-                        let _ = write!(buf, "- caused by: {}\n", source);
-                        next_source = source.source();
-                    }
-                    Err(tera::Error::msg(buf))
-                },
-            },
+        self.reference.insert(ItemKind::Function, &name, fun.doc());
+        let function =
+            TeraFunction::new(name.clone(), fun, self.current_page.clone());
+        if function.takes_positional() {
+            self.extensions.register_filter(&name, function.clone());
+            self.tera.register_filter(&name, function.clone());
+        }
+        if !self.latex_fns.contains(&name) {
+            let html = tera::Function::is_safe(&function);
+            if function.takes_positional() {
+                self.latex_tera.register_filter(
+                    &name,
+                    LatexFallback::new(function.clone(), html),
+                );
+            }
+            self.latex_tera.register_function(
+                &name,
+                LatexFallback::new(function.clone(), html),
+            );
+        }
+        self.extensions.register_function(&name, function.clone());
+        self.tera.register_function(&name, function);
+    }
+
+    pub fn register_latex_fn<F>(&mut self, name: impl Into<String>, fun: F)
+    where
+        F: Function,
+    {
+        let name = name.into();
+        let function =
+            TeraFunction::new(name.clone(), fun, self.current_page.clone());
+        if function.takes_positional() {
+            self.latex_tera.register_filter(&name, function.clone());
+        }
+        self.latex_tera.register_function(&name, function);
+        self.latex_fns.insert(name);
+    }
+
+    pub fn register_filter<F>(&mut self, name: impl Into<String>, filter: F)
+    where
+        F: Filter,
+    {
+        let name = name.into();
+        self.reference.insert(ItemKind::Filter, &name, filter.doc());
+        let filter =
+            TeraFilter::new(name.clone(), filter, self.current_page.clone());
+        let html = tera::Filter::is_safe(&filter);
+        self.latex_tera
+            .register_filter(&name, LatexFallback::new(filter.clone(), html));
+        self.extensions.register_filter(&name, filter.clone());
+        self.tera.register_filter(&name, filter);
+    }
+
+    pub fn register_tester<T>(&mut self, name: impl Into<String>, tester: T)
+    where
+        T: Tester,
+    {
+        let name = name.into();
+        self.reference.insert(ItemKind::Tester, &name, tester.doc());
+        let tester =
+            TeraTester::new(name.clone(), tester, self.current_page.clone());
+        self.extensions.register_tester(&name, tester.clone());
+        self.tera.register_tester(&name, tester);
+    }
+
+    pub fn install_packs(
+        &mut self,
+        registry: &PackRegistry,
+    ) -> Result<(), PackError> {
+        let config =
+            PackConfig::load(self.config.fs(), self.config.site_config_path())?;
+        for pack in registry.resolve(&config)? {
+            self.reference.insert(ItemKind::Pack, pack.name(), pack.doc());
+            pack.install(self);
+        }
+        Ok(())
+    }
+
+    pub fn register_asset_processor<P>(&mut self, processor: P)
+    where
+        P: AssetProcessor,
+    {
+        self.asset_processors.register(processor);
+    }
+
+    pub fn register_backend<B>(&mut self, backend: B)
+    where
+        B: ContentBackend,
+    {
+        self.backends.register(backend);
+    }
+
+    pub fn register_observer<O>(&mut self, observer: O)
+    where
+        O: BuildObserver,
+    {
+        self.observers.register(observer);
+    }
+
+    pub fn register_diagnostics_sink<S>(&mut self, sink: S)
+    where
+        S: DiagnosticsSink,
+    {
+        self.diagnostics.register(sink);
+    }
+
+    pub fn register_hook<H>(&mut self, hook: H)
+    where
+        H: BuildHook,
+    {
+        self.hooks.register(hook);
+    }
+
+    pub fn register_transform<T>(&mut self, transform: T)
+    where
+        T: Transform,
+    {
+        self.transforms.register(transform);
+    }
+
+    fn register_shortcodes(&mut self, shortcodes: ShortcodeRenderer) {
+        let filter_shortcodes = shortcodes.clone();
+        let filter =
+            move |body: &serde_json::Value,
+                  args: &HashMap<String, serde_json::Value>| {
+                filter_shortcodes.render(args, Some(body))
+            };
+        let function = move |args: &HashMap<String, serde_json::Value>| {
+            shortcodes.render(args, None)
+        };
+        self.latex_tera.register_filter(
+            SHORTCODE_FN,
+            LatexFallback::new(filter.clone(), true),
+        );
+        self.latex_tera.register_function(
+            SHORTCODE_FN,
+            LatexFallback::new(function.clone(), true),
         );
+        self.extensions.register_filter(SHORTCODE_FN, filter.clone());
+        self.extensions.register_function(SHORTCODE_FN, function.clone());
+        self.tera.register_filter(SHORTCODE_FN, filter);
+        self.tera.register_function(SHORTCODE_FN, function);
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn lexicon(&self) -> &Lexicon {
+        &self.lexicon
+    }
+
+    // The builtin table with the site's own codes merged in, for packs to
+    // encode with.
+    pub fn linguinput_table(&self) -> Arc<Table> {
+        self.linguinput_table.clone()
+    }
+
+    pub fn named_linguinput_tables(&self) -> Arc<BTreeMap<String, Table>> {
+        self.named_linguinput_tables.clone()
     }
 
     pub fn doc(&self, fn_name: impl AsRef<str>) -> Option<&str> {
-        self.docs.get(fn_name.as_ref()).map(AsRef::as_ref)
+        let item = self.reference.get(ItemKind::Function, fn_name.as_ref())?;
+        Some(&item.doc)
+    }
+
+    pub fn reference(&self) -> &Reference {
+        &self.reference
+    }
+
+    pub fn warnings(&self) -> &[BuildWarning] {
+        &self.warnings
     }
 
     pub fn build(&mut self) -> Result<(), BuildError> {
-        self.prepare_build()?;
-        self.build_pages()?;
-        self.copy_assets()?;
-        Ok(())
+        let mut errors = Vec::new();
+        self.build_collecting(&mut errors)?;
+        errors.into_iter().next().map_or(Ok(()), Err)
+    }
+
+    // The build does blocking IO and heavy rendering, so on a multi-threaded
+    // runtime the worker hands its other tasks off while it runs. A
+    // current-thread runtime has nowhere to hand them to.
+    #[cfg(feature = "tokio")]
+    pub async fn build_async(&mut self) -> Result<(), BuildError> {
+        use tokio::runtime::{Handle, RuntimeFlavor};
+
+        match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+            Ok(RuntimeFlavor::MultiThread) => {
+                tokio::task::block_in_place(|| self.build())
+            },
+            _ => self.build(),
+        }
+    }
+
+    pub fn build_all_errors(&mut self) -> Result<(), Vec<BuildError>> {
+        let mut errors = Vec::new();
+        if let Err(error) = self.build_collecting(&mut errors) {
+            errors.push(error);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn build_collecting(
+        &mut self,
+        errors: &mut Vec<BuildError>,
+    ) -> Result<(), BuildError> {
+        let result = self.run_build(errors);
+        for error in errors.iter().chain(result.as_ref().err()) {
+            self.diagnostics.report(&error.diagnostic());
+        }
+        result
+    }
+
+    fn run_build(
+        &mut self,
+        errors: &mut Vec<BuildError>,
+    ) -> Result<(), BuildError> {
+        let started = Instant::now();
+        let observers = self.observers.clone();
+        self.warnings.clear();
+        // Hooks may act on the real output, so a dry run skips them.
+        let run_hooks = self.dry_run_output.is_none();
+        if run_hooks {
+            self.hooks
+                .before_build(&self.config)
+                .map_err(BuildError::on(self.config.output_dir()))?;
+        }
+        let kept =
+            observers.phase(BuildPhase::Clean, || self.prepare_build())?;
+        *self
+            .foreign_outputs
+            .write()
+            .unwrap_or_else(|error| error.into_inner()) = kept;
+        // Pages still compile after a failed asset so their errors show up in
+        // the same build, but nothing renders until the build is clean.
+        if let Err(error) =
+            observers.phase(BuildPhase::Assets, || self.copy_assets())
+        {
+            errors.push(error);
+        }
+        let result = self.build_pages(errors).and_then(|()| {
+            if !run_hooks {
+                return Ok(());
+            }
+            self.hooks
+                .after_build(&self.config)
+                .map_err(BuildError::on(self.config.output_dir()))
+        });
+        let kept = mem::take(
+            &mut *self
+                .foreign_outputs
+                .write()
+                .unwrap_or_else(|error| error.into_inner()),
+        );
+        output::write_manifest(
+            self.config.fs(),
+            self.config.output_dir(),
+            &kept,
+        )
+        .map_err(BuildError::on(self.config.output_dir()))?;
+        observers.emit(BuildEvent::Finished(started.elapsed()));
+        result
+    }
+
+    fn knows_function(&self, name: &str) -> bool {
+        name == SHORTCODE_FN
+            || template_check::TERA_FUNCTIONS.contains(&name)
+            || self.reference.get(ItemKind::Function, name).is_some()
+    }
+
+    fn push_warning(&mut self, warning: BuildWarning) {
+        self.warn(&warning);
+        self.warnings.push(warning);
+    }
+
+    fn warn(&self, warning: &BuildWarning) {
+        self.observers.emit(BuildEvent::Warning(warning));
+        self.diagnostics.report(&warning.diagnostic());
     }
 
-    fn create_empty_output_dir(&self) -> Result<(), BuildErrorKind> {
-        fs::create_dir_all(self.config.output_dir())?;
-        fs::remove_dir_all(self.config.output_dir())?;
-        fs::create_dir(self.config.output_dir())?;
+    pub fn build_dry_run(&mut self) -> Result<BuildDiff, BuildError> {
+        let config = self.config.clone();
+        let staging_dir = dry_run::staging_dir(config.fs())
+            .map_err(BuildError::on(config.output_dir()))?;
+        self.config =
+            Arc::new(Config::clone(&config).with_output(&staging_dir));
+        self.image_pipeline.set_output_dir(&staging_dir);
+        self.dry_run_output = Some(config.output_dir().to_owned());
+        let result = self.build();
+        self.dry_run_output = None;
+        self.config = config;
+        self.image_pipeline.set_output_dir(self.config.output_dir());
+        let diff = result.and_then(|()| {
+            BuildDiff::compare(
+                self.config.fs(),
+                self.config.output_dir(),
+                &staging_dir,
+                self.config.preserved_outputs(),
+            )
+            .map_err(BuildError::on(self.config.output_dir()))
+        });
+        let cleanup = self.config.fs().remove_dir_all(&staging_dir);
+        let diff = diff?;
+        cleanup.map_err(BuildError::on(&staging_dir))?;
+        Ok(diff)
+    }
+
+    pub fn reload_templates(&mut self) -> Result<(), BuildError> {
+        let (mut tera, builtin_theme) = theme::load_templates(&self.config)
+            .map_err(BuildError::on(self.config.template_dir()))?;
+        // Registered functions, filters and testers carry over, while
+        // templates come only from the files, so deleted ones are gone.
+        tera.extend(&self.extensions)
+            .map_err(BuildError::on(self.config.template_dir()))?;
+        self.tera = tera;
+        self.builtin_theme = builtin_theme;
+        // The reload drops the pages' own templates and any page may extend
+        // a changed layout, so every page is compiled and rendered again.
+        self.build()
+    }
+
+    // Renders a page in memory, as the build would write it. The page
+    // replaces its compiled version from the last build, while every other
+    // page is seen as that build left it.
+    pub fn render_page(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<String, BuildError> {
+        let path = path.as_ref();
+        let page_dir = self.config.page_dir().to_owned();
+        let path_rel = path
+            .strip_prefix(&page_dir)
+            .map_err(BuildError::on(path))?
+            .to_owned();
+        let backend =
+            self.backends.find(&path_rel).ok_or_else(|| BuildError {
+                path: path.to_owned(),
+                source: BuildErrorSource::NotAPage,
+                location: None,
+            })?;
+        let mut defaults =
+            directory_defaults(self.config.fs(), &page_dir, Rc::default())?;
+        let mut directory = page_dir.clone();
+        for component in path_rel.parent().into_iter().flatten() {
+            directory.push(component);
+            defaults =
+                directory_defaults(self.config.fs(), &directory, defaults)?;
+        }
+        let path = page_dir.join(&path_rel);
+        if let Some(old) =
+            path.to_str().and_then(|name| self.pages.remove(name))
+        {
+            self.page_outputs.retain(|_, source| *source != old.route.source);
+        }
+        self.add_page(&*backend, path.clone(), &path_rel, &defaults)?;
+        self.update_site_context()?;
+        let Some((template, compiled)) =
+            path.to_str().and_then(|name| self.pages.get_key_value(name))
+        else {
+            Err(BuildError::on(&path)(BuildErrorSource::NonUtf8Path))?
+        };
+        let equivalents: Vec<_> = self
+            .pages
+            .values()
+            .map(|other| &other.route)
+            .filter(|route| {
+                route.translation_key == compiled.route.translation_key
+            })
+            .collect();
+        let context =
+            self.page_context(compiled, &equivalents, &self.backlinks());
+        let metadata = match compiled.context.clone().into_json() {
+            Value::Object(metadata) => metadata,
+            _ => Map::new(),
+        };
+        let output_page =
+            self.config.output_dir().join(&compiled.route.output_rel);
+        let (mut rendered, ..) = self.render_output(
+            template,
+            &context,
+            &output_page,
+            metadata,
+            &compiled.route,
+        )?;
+        if self.config.minify_html() {
+            rendered = minify::minify_html(&rendered);
+        }
+        Ok(rendered)
+    }
+
+    pub fn export_latex(
+        &mut self,
+        output_path: &Path,
+    ) -> Result<(), BuildError> {
+        let walk = self.walk(self.config.page_dir())?;
+        let mut defaults = HashMap::new();
+        let mut chapters = Vec::new();
+        for entry in walk {
+            let WalkEntry { path, path_rel, is_dir } = entry?;
+            let parent_defaults = path_rel
+                .parent()
+                .and_then(|parent| defaults.get(parent))
+                .cloned()
+                .unwrap_or_default();
+            if is_dir {
+                let loaded = directory_defaults(
+                    self.config.fs(),
+                    &path,
+                    parent_defaults,
+                )?;
+                defaults.insert(path_rel, loaded);
+            } else if self.config.is_page(&path_rel) {
+                let chapter = self.latex_chapter(&path, &parent_defaults)?;
+                chapters.push((path_rel, chapter));
+            }
+        }
+        // Chapters follow the page tree, each directory's index first.
+        chapters.sort_by_cached_key(|(path_rel, _)| {
+            (
+                path_rel.parent().map(Path::to_owned),
+                path_rel.file_stem() != Some("index".as_ref()),
+                path_rel.clone(),
+            )
+        });
+        let chapters: Vec<_> =
+            chapters.into_iter().map(|(_, chapter)| chapter).collect();
+        let document = latex::document(self.config.latex_font(), &chapters);
+        self.config
+            .fs()
+            .write(output_path, document.as_bytes())
+            .map_err(BuildError::on(output_path))
+    }
+
+    pub fn export_epub(
+        &mut self,
+        book: &str,
+        output_path: &Path,
+    ) -> Result<(), BuildError> {
+        let config = self.config.clone();
+        let book = config.book(book).ok_or_else(|| BuildError {
+            path: output_path.to_owned(),
+            source: BuildErrorSource::UnknownBook(book.to_owned()),
+            location: None,
+        })?;
+        let mut pages = Vec::new();
+        for page in book.pages() {
+            let source = config.page_dir().join(page);
+            let compiled = source
+                .to_str()
+                .and_then(|name| Some((name, self.pages.get(name)?.clone())))
+                .ok_or_else(|| BuildError {
+                    path: source.clone(),
+                    source: BuildErrorSource::UnbuiltBookPage,
+                    location: None,
+                })?;
+            pages.push((format!("{}#{EPUB_FORMAT}", compiled.0), compiled.1));
+        }
+        // Links between pages of the book lead to their chapters instead.
+        let links: Vec<_> = pages
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (_, compiled))| {
+                let url = &compiled.route.url;
+                [url.clone(), tera::escape_html(url)].map(|url| {
+                    (
+                        format!("href=\"{url}\""),
+                        format!("href=\"{}\"", epub::chapter_file_name(i)),
+                    )
+                })
+            })
+            .collect();
+        let mut chapters = Vec::new();
+        for (template_name, compiled) in &pages {
+            let source = &compiled.route.source;
+            let layout =
+                page::extend_layout(config.epub_layout(), &compiled.body)
+                    .map_err(BuildError::on(source))?;
+            self.tera
+                .add_raw_template(template_name, &layout)
+                .map_err(BuildError::on(source))?;
+            let mut context = self.base_context.clone();
+            context.extend(compiled.context.clone());
+            context.insert("page", &compiled.route);
+            let metadata = match compiled.context.clone().into_json() {
+                Value::Object(metadata) => metadata,
+                _ => Map::new(),
+            };
+            self.enter_page(
+                &config.output_dir().join(&compiled.route.output_rel),
+                metadata,
+            );
+            let rendered = self
+                .tera
+                .render(template_name, &context)
+                .map_err(BuildError::on(source))?;
+            let rendered = summary::strip_markers(&rendered);
+            let rendered = search::strip_markers(&rendered);
+            let content = links.iter().fold(
+                typography::strip_markers(&rendered),
+                |content, (url, chapter)| content.replace(url, chapter),
+            );
+            chapters.push(EpubChapter {
+                title: compiled.title().to_owned(),
+                content,
+            });
+        }
+        self.current_page.set(PageCtx::new(config.clone()));
+        let mut fonts = Vec::new();
+        for path in config.epub_fonts() {
+            let data = config.fs().read(path).map_err(BuildError::on(path))?;
+            fonts
+                .push(EpubFont::new(path, data).map_err(BuildError::on(path))?);
+        }
+        let lang = pages
+            .first()
+            .and_then(|(_, compiled)| compiled.route.lang.clone())
+            .or_else(|| config.languages().first().cloned())
+            .unwrap_or_else(|| String::from(EPUB_DEFAULT_LANG));
+        let modified = if config.reproducible() {
+            clock::source_date_epoch()
+        } else {
+            SystemTime::now().into()
+        };
+        let epub = Epub {
+            identifier: format!("urn:lin-ssg:book:{}", book.name()),
+            title: book.title().to_owned(),
+            lang,
+            modified,
+            chapters,
+            fonts,
+        };
+        self.config
+            .fs()
+            .write(output_path, &epub.to_bytes())
+            .map_err(BuildError::on(output_path))
+    }
+
+    fn latex_chapter(
+        &mut self,
+        path: &Path,
+        defaults: &toml::Table,
+    ) -> Result<Chapter, BuildError> {
+        let fs = self.config.fs();
+        let code = fs.read_to_string(path).map_err(BuildError::on(path))?;
+        let code = include::expand(fs, &code, self.config.partial_dir())
+            .map_err(BuildError::on(path))?;
+        let parts = RawPageParts::split(&code)
+            .map_err(page::CompileError::from)
+            .and_then(|raw_parts| {
+                let markdown = MarkdownBackend::default();
+                Ok(raw_parts.parse(
+                    defaults,
+                    &markdown,
+                    &self.to_html_options,
+                )?)
+            })
+            .map_err(|error| BuildError::compile(path, &code, error))?;
+        let mut options = ToLatexOptions::from_config(&self.config);
+        if let Some(heading_offset) = parts.metadata.heading_offset() {
+            options.heading_offset = heading_offset;
+        }
+        let mut context =
+            ToLatexCtx::new(options, parts.document.shortcodes.clone());
+        let mut body = String::new();
+        parts
+            .document
+            .ast
+            .to_latex(&mut body, &mut context)
+            .map_err(BuildError::on(path))?;
+        if !context.unsupported().is_empty() {
+            let warning = BuildWarning {
+                path: path.to_owned(),
+                message: format!(
+                    "unsupported markdown nodes were left out of the LaTeX \
+                     export: {}",
+                    context.unsupported().join(", ")
+                ),
+                span: None,
+            };
+            self.warn(&warning);
+        }
+        let mut template_context = self.base_context.clone();
+        template_context.insert("title", parts.metadata.title());
+        let body = self
+            .latex_tera
+            .render_str(&body, &template_context)
+            .map_err(BuildError::on(path))?;
+        Ok(Chapter { title: parts.metadata.title().to_owned(), body })
+    }
+
+    fn prepare_build(&self) -> Result<BTreeSet<PathBuf>, BuildError> {
+        output::sweep(
+            self.config.fs(),
+            self.config.output_dir(),
+            self.config.preserved_outputs(),
+        )
+        .map_err(BuildError::on(self.config.output_dir()))
+    }
+
+    fn build_pages(
+        &mut self,
+        errors: &mut Vec<BuildError>,
+    ) -> Result<(), BuildError> {
+        self.image_pipeline.clear();
+        self.audio_fn.clear();
+        let observers = self.observers.clone();
+        observers.phase(BuildPhase::Pages, || self.convert_pages(errors))?;
+        let mut site_context = self.update_site_context();
+        if site_context.is_ok() && errors.is_empty() {
+            self.render_summaries(errors);
+            site_context = self.update_site_context();
+        }
+        if let Err(error) = site_context {
+            errors.push(error);
+        }
+        // Pages that failed to compile would only cause spurious link errors
+        // in the others, so rendering waits until every page compiles.
+        if errors.is_empty() {
+            self.index_wiki();
+            observers.phase(BuildPhase::Render, || self.write_pages(errors))?;
+            let results = [
+                observers
+                    .phase(BuildPhase::Dictionary, || self.write_dictionary()),
+                observers
+                    .phase(BuildPhase::Reference, || self.write_reference()),
+                observers.phase(BuildPhase::Glossary, || self.write_glossary()),
+                observers.phase(BuildPhase::ExtraOutputs, || {
+                    self.write_extra_outputs()
+                }),
+            ];
+            errors.extend(results.into_iter().filter_map(Result::err));
+        }
+        self.current_page.set(PageCtx::new(self.config.clone()));
         Ok(())
     }
 
-    fn prepare_build(&self) -> Result<(), BuildError> {
-        self.create_empty_output_dir()
-            .map_err(BuildError::on(self.config.output_dir()))?;
+    fn copy_assets(&mut self) -> Result<(), BuildError> {
+        self.asset_manifest.clear();
+
+        // Site assets are copied first, so theme assets they override are
+        // skipped.
+        let mut copied = HashMap::new();
+        for asset_dir in self.config.asset_dirs() {
+            if !self.config.fs().is_dir(&asset_dir) {
+                continue;
+            }
+            self.copy_asset_dir(&asset_dir, &mut copied)?;
+        }
+        for warning in case_collision_warnings(&copied) {
+            self.push_warning(warning);
+        }
+        if self.builtin_theme {
+            self.write_builtin_assets(&copied)?;
+        }
+        self.write_bundles()?;
+
+        if self.config.asset_fingerprints() {
+            self.write_asset_manifest()?;
+        }
         Ok(())
     }
 
-    fn build_pages(&mut self) -> Result<(), BuildError> {
-        self.convert_pages()?;
-        self.write_pages()?;
+    fn copy_asset_dir(
+        &self,
+        asset_dir: &Path,
+        copied: &mut HashMap<PathBuf, PathBuf>,
+    ) -> Result<(), BuildError> {
+        let walk = self.walk(asset_dir)?;
+        for entry in walk {
+            let WalkEntry { path, path_rel, is_dir } = entry?;
+            if !is_dir && self.copy_asset(&path, &path_rel, copied)? {
+                self.observers.emit(BuildEvent::AssetCopied(&path));
+            }
+        }
         Ok(())
     }
 
-    fn copy_assets(&self) -> Result<(), BuildError> {
-        let mut buf = vec![0; Self::ASSET_BUF_SIZE];
-
-        let mut directories = vec![Cow::Borrowed(self.config.asset_dir())];
-        let mut expanded_symlinks = HashSet::new();
-        while let Some(directory) = directories.pop() {
-            let entries = fs::read_dir(directory.as_ref())
-                .map_err(BuildError::on(directory.as_ref()))?;
-
-            for result in entries {
-                let entry =
-                    result.map_err(BuildError::on(directory.as_ref()))?;
-                let mut path = entry.path();
-                let mut file_type =
-                    entry.file_type().map_err(BuildError::on(&path))?;
-
-                while file_type.is_symlink()
-                    && expanded_symlinks.insert(path.clone())
-                {
-                    path =
-                        fs::read_link(&path).map_err(BuildError::on(&path))?;
-                    file_type = fs::symlink_metadata(&path)
-                        .map_err(BuildError::on(&path))?
-                        .file_type();
-                }
+    fn walk(&self, root: &Path) -> Result<Walk, BuildError> {
+        let mut walk = Walk::new(
+            self.config.shared_fs(),
+            root,
+            self.config.follow_external_symlinks(),
+        )?
+        .excluding(self.config.output_dir());
+        if let Some(output_dir) = &self.dry_run_output {
+            walk = walk.excluding(output_dir);
+        }
+        Ok(walk.ignoring(self.ignore_rules(root)?))
+    }
 
-                if file_type.is_dir() {
-                    directories.push(Cow::Owned(path));
-                } else if file_type.is_file() {
-                    let mut output_path =
-                        PathBuf::from(self.config.output_dir());
-                    let suffix = path
-                        .strip_prefix(self.config.asset_dir())
-                        .map_err(BuildError::on(&path))?;
-                    output_path.push("assets");
-                    output_path.extend(suffix);
-                    let mut output_base_dir = output_path.clone();
-                    output_base_dir.pop();
-                    fs::create_dir_all(&output_base_dir)
-                        .map_err(BuildError::on(&output_base_dir))?;
-                    let mut output_file = File::create_new(&output_path)
-                        .map_err(BuildError::on(&output_path))?;
-                    let mut input_file =
-                        File::open(&path).map_err(BuildError::on(&path))?;
-
-                    loop {
-                        let read = input_file
-                            .read(&mut buf[..])
-                            .map_err(BuildError::on(&path))?;
-                        if read == 0 {
-                            break;
-                        }
-                        output_file
-                            .write_all(&buf[.. read])
-                            .map_err(BuildError::on(&output_path))?;
-                    }
-                }
+    fn ignore_rules(&self, root: &Path) -> Result<Gitignore, BuildError> {
+        walk::ignore_rules(
+            self.config.fs(),
+            root,
+            self.config.ignore_file(),
+            self.config.ignore_patterns(),
+        )
+        .map_err(BuildError::on(self.config.ignore_file()))
+    }
+
+    fn copy_asset(
+        &self,
+        path: &Path,
+        path_rel: &Path,
+        copied: &mut HashMap<PathBuf, PathBuf>,
+    ) -> Result<bool, BuildError> {
+        let processor = self.asset_processors.find(path);
+        if processor.is_some_and(|processor| !processor.emits(path)) {
+            return Ok(false);
+        }
+
+        let mut output_path = PathBuf::from(self.config.output_dir());
+        output_path.push("assets");
+        output_path.push(path_rel);
+        if let Some(processor) = processor {
+            output_path.set_extension(processor.output_extension());
+        }
+        if copied.contains_key(&output_path) {
+            return Ok(false);
+        }
+        copied.insert(output_path.clone(), path.to_owned());
+        // Processors read their input themselves, since they may pull in
+        // other files.
+        let contents = match processor {
+            Some(processor) => processor
+                .process(self.config.fs(), path)
+                .map_err(BuildError::on(path))?,
+            None => {
+                self.config.fs().read(path).map_err(BuildError::on(path))?
+            },
+        };
+        self.write_output(&output_path, &contents)?;
+        let mut fingerprinter = Fingerprinter::new();
+        fingerprinter.write(&contents);
+
+        if self.config.asset_fingerprints() {
+            let output_suffix = output_path
+                .strip_prefix(self.config.output_dir())
+                .and_then(|suffix| suffix.strip_prefix("assets"))
+                .map_err(BuildError::on(&output_path))?;
+            self.fingerprint_asset(
+                output_suffix,
+                &output_path,
+                &fingerprinter.finish(),
+            )?;
+        }
+        Ok(true)
+    }
+
+    fn write_builtin_assets(
+        &self,
+        copied: &HashMap<PathBuf, PathBuf>,
+    ) -> Result<(), BuildError> {
+        for (logical, code) in theme::BUILTIN_ASSETS {
+            let mut output_path = PathBuf::from(self.config.output_dir());
+            output_path.push("assets");
+            output_path.push(logical);
+            if copied.contains_key(&output_path) {
+                continue;
+            }
+            self.write_output(&output_path, code.as_bytes())?;
+            if self.config.asset_fingerprints() {
+                let mut fingerprinter = Fingerprinter::new();
+                fingerprinter.write(code.as_bytes());
+                self.fingerprint_asset(
+                    Path::new(logical),
+                    &output_path,
+                    &fingerprinter.finish(),
+                )?;
             }
         }
         Ok(())
     }
 
-    fn convert_pages(&mut self) -> Result<(), BuildError> {
-        let mut directories =
-            vec![Cow::<Path>::Owned(self.config.page_dir().to_owned())];
-        let mut expanded_symlinks = HashSet::new();
-        while let Some(directory) = directories.pop() {
-            let entries = fs::read_dir(directory.as_ref())
-                .map_err(BuildError::on(directory.as_ref()))?;
-
-            for result in entries {
-                let entry =
-                    result.map_err(BuildError::on(directory.as_ref()))?;
-                let mut path = entry.path();
-                let mut file_type =
-                    entry.file_type().map_err(BuildError::on(&path))?;
-
-                while file_type.is_symlink()
-                    && expanded_symlinks.insert(path.clone())
-                {
-                    path =
-                        fs::read_link(&path).map_err(BuildError::on(&path))?;
-                    file_type = fs::symlink_metadata(&path)
-                        .map_err(BuildError::on(&path))?
-                        .file_type();
+    fn write_bundles(&self) -> Result<(), BuildError> {
+        let asset_dirs = self.config.asset_dirs();
+        for (name, files) in self.config.bundles() {
+            let logical = bundle::logical_path(name);
+            let mut output_path = PathBuf::from(self.config.output_dir());
+            output_path.push("assets");
+            output_path.push(&logical);
+            let kind = BundleKind::of(name);
+            let mut contents = Vec::new();
+            for file in files {
+                let file = file.trim_start_matches('/');
+                let path =
+                    asset::find_source(self.config.fs(), &asset_dirs, file)
+                        .ok_or_else(|| {
+                            BuildError::on(&output_path)(
+                                BundleError::MissingFile(
+                                    name.clone(),
+                                    file.to_owned(),
+                                ),
+                            )
+                        })?;
+                let bytes = match self.asset_processors.find(&path) {
+                    Some(processor) => processor
+                        .process(self.config.fs(), &path)
+                        .map_err(BuildError::on(&path))?,
+                    None => self
+                        .config
+                        .fs()
+                        .read(&path)
+                        .map_err(BuildError::on(&path))?,
+                };
+                if !contents.is_empty() {
+                    contents.extend_from_slice(
+                        kind.map_or(b"\n", BundleKind::separator),
+                    );
                 }
+                contents.extend(bytes);
+            }
+            if self.config.minify_bundles() && kind == Some(BundleKind::Css) {
+                contents = bundle::minify_css(name, contents)
+                    .map_err(BuildError::on(&output_path))?;
+            }
+            self.write_output(&output_path, &contents)?;
+            if self.config.asset_fingerprints() {
+                let mut fingerprinter = Fingerprinter::new();
+                fingerprinter.write(&contents);
+                self.fingerprint_asset(
+                    Path::new(&logical),
+                    &output_path,
+                    &fingerprinter.finish(),
+                )?;
+            }
+        }
+        Ok(())
+    }
 
-                if file_type.is_dir() {
-                    directories.push(Cow::Owned(path));
-                } else if file_type.is_file() {
-                    self.add_page(path)?;
+    fn fingerprint_asset(
+        &self,
+        suffix: &Path,
+        output_path: &Path,
+        fingerprint: &str,
+    ) -> Result<(), BuildError> {
+        let (Some(logical), Some(file_name)) = (
+            asset::logical_path(suffix),
+            output_path.file_name().and_then(|name| name.to_str()),
+        ) else {
+            Err(BuildError {
+                path: output_path.to_owned(),
+                source: BuildErrorSource::NonUtf8Path,
+                location: None,
+            })?
+        };
+        let fingerprinted_name =
+            asset::fingerprinted_name(file_name, fingerprint);
+        let fingerprinted_path =
+            output_path.with_file_name(&fingerprinted_name);
+        self.config
+            .fs()
+            .rename(output_path, &fingerprinted_path)
+            .map_err(BuildError::on(&fingerprinted_path))?;
+        let fingerprinted_logical = match logical.rsplit_once('/') {
+            Some((directory, _)) => format!("{directory}/{fingerprinted_name}"),
+            None => fingerprinted_name,
+        };
+        self.asset_manifest.insert(logical, fingerprinted_logical);
+        Ok(())
+    }
+
+    fn write_asset_manifest(&self) -> Result<(), BuildError> {
+        let mut manifest_path = PathBuf::from(self.config.output_dir());
+        manifest_path.push("assets");
+        self.config
+            .fs()
+            .create_dir_all(&manifest_path)
+            .map_err(BuildError::on(&manifest_path))?;
+        manifest_path.push(asset::MANIFEST_FILE_NAME);
+        self.claim_output(&manifest_path);
+        let manifest = self
+            .asset_manifest
+            .to_json()
+            .map_err(BuildError::on(&manifest_path))?;
+        self.config
+            .fs()
+            .write(&manifest_path, manifest.as_bytes())
+            .map_err(BuildError::on(&manifest_path))?;
+        Ok(())
+    }
+
+    fn convert_pages(
+        &mut self,
+        errors: &mut Vec<BuildError>,
+    ) -> Result<(), BuildError> {
+        self.pages.clear();
+        self.page_outputs.clear();
+        let walk = self.walk(self.config.page_dir())?;
+        let mut defaults = HashMap::new();
+        for entry in walk {
+            let WalkEntry { path, path_rel, is_dir } = entry?;
+            let parent_defaults = path_rel
+                .parent()
+                .and_then(|parent| defaults.get(parent))
+                .cloned()
+                .unwrap_or_default();
+            if is_dir {
+                let loaded = directory_defaults(
+                    self.config.fs(),
+                    &path,
+                    parent_defaults,
+                )?;
+                defaults.insert(path_rel, loaded);
+            } else if let Some(backend) = self.backends.find(&path_rel) {
+                match self.add_page(
+                    &*backend,
+                    path.clone(),
+                    &path_rel,
+                    &parent_defaults,
+                ) {
+                    Ok(()) => {
+                        self.observers.emit(BuildEvent::PageCompiled(&path))
+                    },
+                    Err(error) => errors.push(error),
+                }
+            } else if self.config.on_non_page() == NonPagePolicy::Copy
+                && path_rel.file_name()
+                    != Some(page::DEFAULTS_FILE_NAME.as_ref())
+            {
+                if let Err(error) = self.copy_non_page(&path, path_rel) {
+                    errors.push(error);
                 }
             }
         }
+        for warning in case_collision_warnings(&self.page_outputs) {
+            self.push_warning(warning);
+        }
 
         Ok(())
     }
 
-    fn add_page(&mut self, mut path: PathBuf) -> Result<(), BuildError> {
-        let code = fs::read_to_string(&path).map_err(BuildError::on(&path))?;
-        let page = page::compile(&code).map_err(BuildError::on(&path))?;
+    fn copy_non_page(
+        &mut self,
+        path: &Path,
+        path_rel: PathBuf,
+    ) -> Result<(), BuildError> {
+        if let Some(other) = self.page_outputs.get(&path_rel) {
+            return Err(BuildError {
+                path: path.to_owned(),
+                source: BuildErrorSource::PageCollision(other.clone()),
+                location: None,
+            });
+        }
+        let output_path = self.config.output_dir().join(&path_rel);
+        let contents =
+            self.config.fs().read(path).map_err(BuildError::on(path))?;
+        self.write_output(&output_path, &contents)?;
+        self.page_outputs.insert(path_rel, path.to_owned());
+        Ok(())
+    }
 
-        match path.file_stem() {
-            Some(stem) if !stem.eq_ignore_ascii_case("index") => {
-                let directory = stem.to_owned();
-                path.pop();
-                path.push(directory);
-                path.push("index.html");
+    fn add_page(
+        &mut self,
+        backend: &dyn PageCompiler,
+        path: PathBuf,
+        path_rel: &Path,
+        defaults: &toml::Table,
+    ) -> Result<(), BuildError> {
+        let fs = self.config.fs();
+        let code = fs.read_to_string(&path).map_err(BuildError::on(&path))?;
+        let code = include::expand(fs, &code, self.config.partial_dir())
+            .map_err(BuildError::on(&path))?;
+        let mut page = backend
+            .compile(&code, defaults, &self.to_html_options)
+            .map_err(|error| BuildError::compile(&path, &code, error))?;
+        if !page.unsupported.is_empty() {
+            let warning = BuildWarning {
+                path: path.clone(),
+                message: format!(
+                    "unsupported markdown nodes were {}: {}",
+                    match self.config.on_unsupported() {
+                        UnsupportedPolicy::Skip => "skipped",
+                        _ => "rendered as text",
+                    },
+                    page.unsupported.join(", ")
+                ),
+                span: None,
+            };
+            self.push_warning(warning);
+        }
+        for name in template_check::called_functions(&page.body) {
+            if !self.knows_function(&name) {
+                let message =
+                    format!("template calls unknown function {name:?}");
+                page.warnings.push(ContentWarning::new(message));
+            }
+        }
+        for warning in &page.warnings {
+            let warning = BuildWarning {
+                path: path.clone(),
+                message: warning.message().to_owned(),
+                span: warning
+                    .span()
+                    .and_then(|span| SourceSpan::locate(&code, span)),
+            };
+            self.push_warning(warning);
+        }
+        let Some(template_name) = path.to_str().map(ToOwned::to_owned) else {
+            Err(BuildError {
+                path,
+                source: BuildErrorSource::NonUtf8Path,
+                location: None,
+            })?
+        };
+        let route =
+            PageRoute::new(path, path_rel, &page, self.config.languages())
+                .map_err(BuildError::on(&template_name))?;
+        page.base_context.insert("lang", &route.lang);
+        self.hooks
+            .after_page_compile(&route.source, &mut page.base_context)
+            .map_err(BuildError::on(&route.source))?;
+        let formats = match &page.formats {
+            Some(names) => names
+                .iter()
+                .map(|name| {
+                    self.config
+                        .output_formats()
+                        .iter()
+                        .find(|format| format.name() == name)
+                        .ok_or_else(|| BuildError {
+                            path: route.source.clone(),
+                            source: BuildErrorSource::UnknownFormat(
+                                name.clone(),
+                            ),
+                            location: None,
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => self.config.output_formats().iter().collect(),
+        };
+        let mut variants = Vec::new();
+        for format in formats {
+            let variant_name = format!("{template_name}#{}", format.name());
+            let variant = page::extend_layout(format.layout(), &page.body)
+                .map_err(BuildError::on(&route.source))?;
+            self.tera
+                .add_raw_template(&variant_name, &variant)
+                .map_err(BuildError::on(&route.source))?;
+            variants.push(PageVariant {
+                format: format.name().to_owned(),
+                template: variant_name,
+                output_rel: route.output_rel.with_extension(format.extension()),
+            });
+        }
+        let variant_outputs =
+            variants.iter().map(|variant| &variant.output_rel);
+        for output_rel in route
+            .aliases
+            .iter()
+            .chain([&route.output_rel])
+            .chain(variant_outputs)
+        {
+            if let Some(other) = self.page_outputs.get(output_rel) {
+                return Err(BuildError {
+                    path: route.source.clone(),
+                    source: BuildErrorSource::PageCollision(other.clone()),
+                    location: None,
+                });
+            }
+            self.page_outputs.insert(output_rel.clone(), route.source.clone());
+        }
+        self.tera
+            .add_raw_template(&template_name, &page.template)
+            .map_err(BuildError::on(&template_name))?;
+        let summary_template = match summary::extract(&page.body) {
+            Some(extracted) => {
+                let summary_name = format!("{template_name}#summary");
+                self.tera
+                    .add_raw_template(&summary_name, &extracted)
+                    .map_err(BuildError::on(&route.source))?;
+                Some(summary_name)
             },
-            _ => {
-                path.set_extension("html");
+            None => None,
+        };
+        self.pages.insert(
+            template_name,
+            CompiledPage {
+                route,
+                context: page.base_context,
+                body: page.body,
+                wiki_links: page.wiki_links,
+                variants,
+                summary_template,
             },
+        );
+        Ok(())
+    }
+
+    // Listings read summaries from the site context, so they are rendered on
+    // their own before any full page is. A summary may itself list pages, so
+    // it sees the site context as it was before summaries were added.
+    fn render_summaries(&mut self, errors: &mut Vec<BuildError>) {
+        let mut summaries = Vec::new();
+        for (page, compiled) in &self.pages {
+            let Some(template) = &compiled.summary_template else {
+                continue;
+            };
+            let output_page =
+                self.config.output_dir().join(&compiled.route.output_rel);
+            let metadata = match compiled.context.clone().into_json() {
+                Value::Object(metadata) => metadata,
+                _ => Map::new(),
+            };
+            self.enter_page(&output_page, metadata);
+            let mut context = self.base_context.clone();
+            context.extend(compiled.context.clone());
+            context.insert("page", &compiled.route);
+            match self.tera.render(template, &context) {
+                Ok(rendered) => summaries
+                    .push((page.clone(), typography::strip_markers(&rendered))),
+                Err(error) => errors.push(BuildError::on(&output_page)(error)),
+            }
         }
+        for (page, summary) in summaries {
+            if let Some(compiled) = self.pages.get_mut(&page) {
+                compiled.context.insert("summary", &summary);
+            }
+        }
+    }
 
-        let Some(stringified_path) = path.to_str().map(ToOwned::to_owned)
+    // Rendering any page may list every other one, so this waits until all of
+    // them compile.
+    fn update_site_context(&mut self) -> Result<(), BuildError> {
+        let mut pages: Vec<_> =
+            self.pages.values().map(CompiledPage::site_page).collect();
+        pages.sort_by(|a, b| a.url.cmp(b.url));
+        let site = Site { pages, config: &self.site_config };
+        self.base_context.insert(site::CONTEXT_KEY, &site);
+        self.page_titles = self
+            .pages
+            .values()
+            .map(|page| (page.route.url.clone(), page.title().to_owned()))
+            .collect();
+        let urls: HashMap<_, _> = self
+            .pages
+            .values()
+            .map(|page| (page.route.source.as_path(), &page.route.url[..]))
+            .collect();
+        let menus = menu::build(&self.menu_entries, |page| {
+            urls.get(self.config.page_dir().join(page).as_path()).copied()
+        })
+        .map_err(BuildError::on(self.config.site_config_path()))?;
+        self.base_context.insert(menu::CONTEXT_KEY, &menus);
+        self.shortcodes.set_globals(self.base_context.clone());
+        Ok(())
+    }
+
+    fn write_pages(
+        &mut self,
+        errors: &mut Vec<BuildError>,
+    ) -> Result<(), BuildError> {
+        let mut equivalents = HashMap::<_, Vec<_>>::new();
+        for CompiledPage { route, .. } in self.pages.values() {
+            equivalents
+                .entry(&route.translation_key[..])
+                .or_default()
+                .push(route);
+        }
+        let mut search_entries = Vec::new();
+        let mut link_report = LinkReport::default();
+        let backlinks = self.backlinks();
+        for (page, compiled) in &self.pages {
+            let CompiledPage {
+                route, context: context_extra, variants, ..
+            } = compiled;
+            let mut output_page = PathBuf::from(self.config.output_dir());
+            output_page.push(&route.output_rel);
+            let context = self.page_context(
+                compiled,
+                &equivalents[&route.translation_key[..]],
+                &backlinks,
+            );
+            let metadata = match context_extra.clone().into_json() {
+                Value::Object(metadata) => metadata,
+                _ => Map::new(),
+            };
+            let written = self
+                .write_page(
+                    page,
+                    &context,
+                    &output_page,
+                    metadata.clone(),
+                    route,
+                    &mut link_report,
+                )
+                .and_then(|written| {
+                    for alias in &route.aliases {
+                        let alias_page = self.config.output_dir().join(alias);
+                        self.write_redirect(&alias_page, &route.url)?;
+                    }
+                    for variant in variants {
+                        self.write_rendered(
+                            &variant.template,
+                            &context,
+                            &self.config.output_dir().join(&variant.output_rel),
+                            metadata.clone(),
+                        )?;
+                    }
+                    Ok(written)
+                });
+            match written {
+                Ok(entry) => search_entries.extend(entry),
+                Err(error) => errors.push(error),
+            }
+        }
+        if self.config.search_index() {
+            self.write_search_index(search_entries)?;
+        }
+        if self.audio_fn.needs_script() {
+            let script_path =
+                self.config.output_dir().join(audio::SCRIPT_FILE_NAME);
+            self.write_output(&script_path, audio::SCRIPT.as_bytes())?;
+        }
+        if let Some(report_path) = self
+            .config
+            .external_link_report()
+            .filter(|_| self.dry_run_output.is_none())
+        {
+            let report = serde_json::to_string_pretty(&link_report)
+                .map_err(BuildError::on(report_path))?;
+            let fs = self.config.fs();
+            if let Some(parent) = report_path.parent() {
+                fs.create_dir_all(parent)
+                    .map_err(BuildError::on(report_path))?;
+            }
+            fs.write(report_path, report.as_bytes())
+                .map_err(BuildError::on(report_path))?;
+        }
+        Ok(())
+    }
+
+    fn page_context(
+        &self,
+        compiled: &CompiledPage,
+        equivalents: &[&PageRoute],
+        backlinks: &HashMap<String, Vec<Backlink>>,
+    ) -> Context {
+        let CompiledPage { route, context: context_extra, variants, .. } =
+            compiled;
+        let mut context = self.base_context.clone();
+        context.extend(context_extra.clone());
+        context.insert("page", route);
+        let formats: BTreeMap<_, _> = variants
+            .iter()
+            .map(|variant| {
+                (&variant.format, route::url_of(&variant.output_rel))
+            })
+            .collect();
+        context.insert("formats", &formats);
+        context.insert(
+            breadcrumb::CONTEXT_KEY,
+            &breadcrumb::trail(&route.url, &self.page_titles),
+        );
+        context.insert(
+            "backlinks",
+            backlinks.get(&route.url[..]).map_or(&[][..], |links| links),
+        );
+        context.insert(
+            "translations",
+            &self.translation_links(route, equivalents),
+        );
+        context
+    }
+
+    fn write_page(
+        &self,
+        template: &str,
+        context: &Context,
+        output_page: &Path,
+        metadata: Map<String, Value>,
+        route: &PageRoute,
+        link_report: &mut LinkReport,
+    ) -> Result<Option<SearchEntry>, BuildError> {
+        let (rendered, entry) = self.render_output(
+            template,
+            context,
+            output_page,
+            metadata,
+            route,
+        )?;
+        let mut rendered = rendered;
+        if self.config.external_link_report().is_some() {
+            link_report.record(
+                &route.url,
+                external::external_urls(&rendered, self.config.base_url()),
+            );
+        }
+        if self.config.minify_html() {
+            rendered = minify::minify_html(&rendered);
+        }
+        self.write_output(output_page, rendered.as_bytes())?;
+        self.observers.emit(BuildEvent::PageWritten(output_page));
+        Ok(entry)
+    }
+
+    fn render_output(
+        &self,
+        template: &str,
+        context: &Context,
+        output_page: &Path,
+        metadata: Map<String, Value>,
+        route: &PageRoute,
+    ) -> Result<(String, Option<SearchEntry>), BuildError> {
+        let title = metadata
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned();
+        let tags = metadata
+            .get("tags")
+            .and_then(|tags| serde_json::from_value(tags.clone()).ok())
+            .unwrap_or_default();
+        self.enter_page(output_page, metadata);
+        let rendered = self
+            .tera
+            .render(template, context)
+            .map_err(BuildError::on(output_page))?;
+        let rendered = summary::strip_markers(&rendered);
+        let entry = self
+            .config
+            .search_index()
+            .then(|| SearchEntry::extract(&rendered, route, title, tags));
+        let rendered = search::strip_markers(&rendered);
+        let mut rendered = self
+            .transforms
+            .apply(rendered)
+            .map_err(BuildError::on(output_page))?;
+        self.hooks
+            .after_render(output_page, &mut rendered)
+            .map_err(BuildError::on(output_page))?;
+        rendered = typography::strip_markers(&rendered);
+        Ok((rendered, entry))
+    }
+
+    fn write_search_index(
+        &self,
+        mut entries: Vec<SearchEntry>,
+    ) -> Result<(), BuildError> {
+        entries.sort_by(|a, b| a.url.cmp(&b.url));
+        let index_path = self.config.output_dir().join(search::INDEX_FILE_NAME);
+        let index = serde_json::to_string(&entries)
+            .map_err(BuildError::on(&index_path))?;
+        self.write_output(&index_path, index.as_bytes())?;
+        let script_path =
+            self.config.output_dir().join(search::SCRIPT_FILE_NAME);
+        self.write_output(&script_path, search::SCRIPT.as_bytes())?;
+        Ok(())
+    }
+
+    fn index_wiki(&self) {
+        self.wiki.clear();
+        for compiled in self.pages.values() {
+            let route = &compiled.route;
+            self.wiki.insert(
+                compiled.title(),
+                &route.url,
+                route.lang.as_deref(),
+            );
+        }
+    }
+
+    fn backlinks(&self) -> HashMap<String, Vec<Backlink>> {
+        let mut backlinks = HashMap::<_, Vec<_>>::new();
+        for compiled in self.pages.values() {
+            let route = &compiled.route;
+            for target in &compiled.wiki_links {
+                let Ok(url) = self.wiki.resolve(target, route.lang.as_deref())
+                else {
+                    continue;
+                };
+                let links = backlinks.entry(url).or_default();
+                let link = Backlink {
+                    url: route.url.clone(),
+                    title: compiled.title().to_owned(),
+                };
+                if !links.contains(&link) {
+                    links.push(link);
+                }
+            }
+        }
+        for links in backlinks.values_mut() {
+            links.sort_by(|a, b| a.url.cmp(&b.url));
+        }
+        backlinks
+    }
+
+    fn translation_links<'a>(
+        &self,
+        route: &PageRoute,
+        equivalents: &[&'a PageRoute],
+    ) -> Vec<TranslationLink<'a>> {
+        let languages = self.config.languages();
+        let mut links: Vec<_> = equivalents
+            .iter()
+            .filter(|other| other.lang != route.lang)
+            .filter_map(|other| {
+                let lang = other.lang.as_deref()?;
+                Some(TranslationLink { lang, url: &other.url })
+            })
+            .collect();
+        links.sort_by_key(|link| {
+            languages.iter().position(|lang| lang == link.lang)
+        });
+        links
+    }
+
+    fn write_dictionary(&self) -> Result<(), BuildError> {
+        let Some(entry_template) = self.config.dictionary_template() else {
+            return Ok(());
+        };
+        let base_url =
+            self.config.dictionary_dir().to_str().ok_or_else(|| {
+                BuildError {
+                    path: self.config.dictionary_dir().to_owned(),
+                    source: BuildErrorSource::NonUtf8Path,
+                    location: None,
+                }
+            })?;
+        let dictionary = Dictionary::new(&self.lexicon, base_url);
+        let mut output_dir = PathBuf::from(self.config.output_dir());
+        output_dir.push(self.config.dictionary_dir());
+
+        let mut base_context = self.base_context.clone();
+        base_context.insert("dictionary_url", dictionary.base_url());
+        base_context.insert("letters", dictionary.letters());
+
+        for entry in dictionary.entries() {
+            let mut output_page = output_dir.clone();
+            output_page.push(dictionary::ENTRY_DIR);
+            output_page.push(&entry.slug);
+            output_page.push("index.html");
+            let mut context = base_context.clone();
+            context.insert("key", entry.key);
+            context.insert("slug", &entry.slug);
+            context.insert("url", &entry.url);
+            context.insert("entry", entry.entry);
+            self.write_rendered(
+                entry_template,
+                &context,
+                &output_page,
+                Map::new(),
+            )?;
+        }
+
+        let Some(index_template) = self.config.dictionary_index_template()
         else {
-            Err(BuildError { path, kind: BuildErrorKind::NonUtf8Path })?
+            return Ok(());
         };
-        self.tera
-            .add_raw_template(&stringified_path, &page.template)
-            .map_err(BuildError::on(&stringified_path))?;
-        self.pages.insert(stringified_path, page.base_context);
+        self.write_rendered(
+            index_template,
+            &base_context,
+            &output_dir.join("index.html"),
+            Map::new(),
+        )?;
+        for letter in dictionary.letters() {
+            let mut output_page = output_dir.clone();
+            output_page.push(&letter.letter);
+            output_page.push("index.html");
+            let mut context = base_context.clone();
+            context.insert("letter", letter);
+            self.write_rendered(
+                index_template,
+                &context,
+                &output_page,
+                Map::new(),
+            )?;
+        }
         Ok(())
     }
 
-    fn write_pages(&mut self) -> Result<(), BuildError> {
-        for (page, context_extra) in &self.pages {
-            let mut output_page = PathBuf::from(self.config.output_dir());
-            let suffix = Path::new(page)
-                .strip_prefix(self.config.page_dir())
-                .map_err(BuildError::on(&page))?;
-            output_page.extend(suffix);
-            let mut directory = output_page.clone();
-            directory.pop();
-            fs::create_dir_all(&directory)
-                .map_err(BuildError::on(&directory))?;
-            let mut output_file = File::create_new(&output_page)
-                .map_err(BuildError::on(&output_page))?;
-            let mut context = self.base_context.clone();
-            context.extend(context_extra.clone());
-            self.tera
-                .render_to(page, &context, &mut output_file)
-                .map_err(BuildError::on(&output_page))?;
+    fn write_reference(&self) -> Result<(), BuildError> {
+        let Some(template) = self.config.reference_template() else {
+            return Ok(());
+        };
+        let mut output_page = PathBuf::from(self.config.output_dir());
+        output_page.push(self.config.reference_dir());
+        output_page.push("index.html");
+
+        let mut context = self.base_context.clone();
+        for (key, kind) in [
+            ("functions", ItemKind::Function),
+            ("filters", ItemKind::Filter),
+            ("testers", ItemKind::Tester),
+            ("constants", ItemKind::Constant),
+            ("symbols", ItemKind::Symbol),
+        ] {
+            let items: Vec<_> = self.reference.of_kind(kind).collect();
+            context.insert(key, &items);
+        }
+        self.write_rendered(template, &context, &output_page, Map::new())
+    }
+
+    fn write_glossary(&self) -> Result<(), BuildError> {
+        let Some(template) = self.config.glossary_template() else {
+            return Ok(());
+        };
+        let output_rel =
+            self.config.glossary_dir().join(route::INDEX_FILE_NAME);
+        let output_page = self.config.output_dir().join(&output_rel);
+
+        let mut context = self.base_context.clone();
+        context.insert("terms", self.glossary.terms());
+        context.insert("glossary_url", &route::url_of(&output_rel));
+        self.write_rendered(template, &context, &output_page, Map::new())
+    }
+
+    fn write_extra_outputs(&self) -> Result<(), BuildError> {
+        if self.config.extra_outputs().is_empty() {
+            return Ok(());
+        }
+        let mut context = self.base_context.clone();
+        let mut pages: Vec<_> = self.pages.values().collect();
+        pages.sort_by(|a, b| a.route.url.cmp(&b.route.url));
+        let pages: Vec<_> =
+            pages.into_iter().map(CompiledPage::listing).collect();
+        context.insert("pages", &pages);
+        for (template, output) in self.config.extra_outputs() {
+            let output_page = self.config.output_dir().join(output);
+            self.write_rendered(template, &context, &output_page, Map::new())?;
         }
         Ok(())
     }
+
+    fn write_redirect(
+        &self,
+        output_page: &Path,
+        url: &str,
+    ) -> Result<(), BuildError> {
+        self.write_output(output_page, route::redirect_html(url).as_bytes())
+    }
+
+    fn write_rendered(
+        &self,
+        template: &str,
+        context: &Context,
+        output_page: &Path,
+        metadata: Map<String, Value>,
+    ) -> Result<(), BuildError> {
+        self.enter_page(output_page, metadata);
+        let rendered = self
+            .tera
+            .render(template, context)
+            .map_err(BuildError::on(output_page))?;
+        // Page variants carry the same content markers as the page itself.
+        let rendered = summary::strip_markers(&rendered);
+        let mut rendered = search::strip_markers(&rendered);
+        if output_page.extension().is_some_and(|ext| ext == "html") {
+            rendered = self
+                .transforms
+                .apply(rendered)
+                .map_err(BuildError::on(output_page))?;
+            if self.config.minify_html() {
+                rendered = minify::minify_html(&rendered);
+            }
+        }
+        let rendered = typography::strip_markers(&rendered);
+        self.write_output(output_page, rendered.as_bytes())
+    }
+
+    fn enter_page(&self, output_page: &Path, metadata: Map<String, Value>) {
+        let page_path = output_page
+            .strip_prefix(self.config.output_dir())
+            .unwrap_or(output_page);
+        self.current_page.set(
+            PageCtx::new(self.config.clone()).with_page(page_path, metadata),
+        );
+    }
+
+    fn write_output(
+        &self,
+        output_page: &Path,
+        contents: &[u8],
+    ) -> Result<(), BuildError> {
+        let fs = self.config.fs();
+        let mut directory = output_page.to_owned();
+        directory.pop();
+        fs.create_dir_all(&directory).map_err(BuildError::on(&directory))?;
+        match fs.create_new(output_page, contents) {
+            Err(error)
+                if error.kind() == io::ErrorKind::AlreadyExists
+                    && self.claim_output(output_page) =>
+            {
+                fs.write(output_page, contents)
+            },
+            result => result,
+        }
+        .map_err(BuildError::on(output_page))
+    }
+
+    // A file left by hand or by an older lin-ssg is overwritten once this
+    // build generates the same path, and from then on the manifest lists it.
+    // Preserved paths are never taken over.
+    fn claim_output(&self, output_page: &Path) -> bool {
+        let Ok(path_rel) = output_page.strip_prefix(self.config.output_dir())
+        else {
+            return false;
+        };
+        !output::is_preserved(path_rel, self.config.preserved_outputs())
+            && self
+                .foreign_outputs
+                .write()
+                .unwrap_or_else(|error| error.into_inner())
+                .remove(path_rel)
+    }
+}
+
+fn read_table(
+    fs: &dyn SiteFs,
+    base: Table,
+    path: &Path,
+) -> Result<Table, TableLoadError> {
+    let source = fs.read(path).map_err(|error| {
+        TableLoadError::Io(path.display().to_string(), error)
+    })?;
+    base.with_source(path, &source)
+}
+
+fn case_collision_warnings(
+    outputs: &HashMap<PathBuf, PathBuf>,
+) -> Vec<BuildWarning> {
+    output::case_collisions(outputs)
+        .into_iter()
+        .map(|(output, source, other, other_source)| BuildWarning {
+            path: source.to_owned(),
+            message: format!(
+                "output {} differs only by case from {}, written for {}, so \
+                 only one of them survives on case-insensitive hosts",
+                output.display(),
+                other.display(),
+                other_source.display(),
+            ),
+            span: None,
+        })
+        .collect()
+}
+
+fn directory_defaults(
+    fs: &dyn SiteFs,
+    directory: &Path,
+    inherited: Rc<toml::Table>,
+) -> Result<Rc<toml::Table>, BuildError> {
+    let path = directory.join(page::DEFAULTS_FILE_NAME);
+    let code = match fs.read_to_string(&path) {
+        Ok(code) => code,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return Ok(inherited)
+        },
+        Err(error) => Err(BuildError::on(&path)(error))?,
+    };
+    let overrides = toml::from_str(&code).map_err(|error| {
+        let span = error.span();
+        BuildError::located(
+            &path,
+            &code,
+            span,
+            BuildErrorSource::Toml(Box::new(error)),
+        )
+    })?;
+    let mut defaults = toml::Table::clone(&inherited);
+    page::merge_metadata(&mut defaults, overrides);
+    Ok(Rc::new(defaults))
 }
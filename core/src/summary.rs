@@ -0,0 +1,28 @@
+pub const MORE_MARKER: &str = "<!-- more -->";
+
+pub const SUMMARY_START: &str = "<!--lin-ssg:summary-start-->";
+
+pub const SUMMARY_END: &str = "<!--lin-ssg:summary-end-->";
+
+pub fn is_more_marker(html: &str) -> bool {
+    html.trim() == MORE_MARKER
+}
+
+pub fn extract(rendered: &str) -> Option<String> {
+    let mut summary = String::new();
+    let mut rest = rendered;
+    while let Some((_, after_start)) = rest.split_once(SUMMARY_START) {
+        let Some((segment, after_end)) = after_start.split_once(SUMMARY_END)
+        else {
+            break;
+        };
+        summary.push_str(segment);
+        rest = after_end;
+    }
+    let summary = summary.trim();
+    (!summary.is_empty()).then(|| summary.to_owned())
+}
+
+pub fn strip_markers(rendered: &str) -> String {
+    rendered.replace(SUMMARY_START, "").replace(SUMMARY_END, "")
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeSet;
+
+use tera::{
+    ast::{Expr, ExprVal, FunctionCall, Node},
+    Template,
+};
+
+pub const TERA_FUNCTIONS: &[&str] =
+    &["range", "now", "throw", "get_random", "get_env"];
+
+// Names of the functions the template calls anywhere, including branches that
+// never run. Templates that fail to parse call nothing here, since adding them
+// to Tera reports the syntax error anyway.
+pub fn called_functions(template: &str) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    if let Ok(template) = Template::new("", None, template) {
+        visit_nodes(&template.ast, &mut names);
+    }
+    names
+}
+
+fn visit_nodes(nodes: &[Node], names: &mut BTreeSet<String>) {
+    for node in nodes {
+        match node {
+            Node::VariableBlock(_, expr) => visit_expr(expr, names),
+            Node::MacroDefinition(_, definition, _) => {
+                for expr in definition.args.values().flatten() {
+                    visit_expr(expr, names);
+                }
+                visit_nodes(&definition.body, names);
+            },
+            Node::Set(_, set) => visit_expr(&set.value, names),
+            Node::FilterSection(_, section, _) => {
+                visit_args(&section.filter, names);
+                visit_nodes(&section.body, names);
+            },
+            Node::Block(_, block, _) => visit_nodes(&block.body, names),
+            Node::Forloop(_, forloop, _) => {
+                visit_expr(&forloop.container, names);
+                visit_nodes(&forloop.body, names);
+                if let Some(body) = &forloop.empty_body {
+                    visit_nodes(body, names);
+                }
+            },
+            Node::If(branches, _) => {
+                for (_, condition, body) in &branches.conditions {
+                    visit_expr(condition, names);
+                    visit_nodes(body, names);
+                }
+                if let Some((_, body)) = &branches.otherwise {
+                    visit_nodes(body, names);
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+fn visit_expr(expr: &Expr, names: &mut BTreeSet<String>) {
+    visit_value(&expr.val, names);
+    for filter in &expr.filters {
+        visit_args(filter, names);
+    }
+}
+
+fn visit_value(value: &ExprVal, names: &mut BTreeSet<String>) {
+    match value {
+        ExprVal::FunctionCall(call) => {
+            names.insert(call.name.clone());
+            visit_args(call, names);
+        },
+        ExprVal::Math(math) => {
+            visit_expr(&math.lhs, names);
+            visit_expr(&math.rhs, names);
+        },
+        ExprVal::Logic(logic) => {
+            visit_expr(&logic.lhs, names);
+            visit_expr(&logic.rhs, names);
+        },
+        ExprVal::In(test) => {
+            visit_expr(&test.lhs, names);
+            visit_expr(&test.rhs, names);
+        },
+        ExprVal::Test(test) => {
+            for arg in &test.args {
+                visit_expr(arg, names);
+            }
+        },
+        ExprVal::MacroCall(call) => {
+            for arg in call.args.values() {
+                visit_expr(arg, names);
+            }
+        },
+        ExprVal::Array(items) => {
+            for item in items {
+                visit_expr(item, names);
+            }
+        },
+        ExprVal::StringConcat(concat) => {
+            for value in &concat.values {
+                visit_value(value, names);
+            }
+        },
+        _ => (),
+    }
+}
+
+fn visit_args(call: &FunctionCall, names: &mut BTreeSet<String>) {
+    for arg in call.args.values() {
+        visit_expr(arg, names);
+    }
+}
@@ -0,0 +1,259 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Config, ConfigError, MemoryFs, SiteFs};
+
+fn site() -> MemoryFs {
+    MemoryFs::new()
+        .with_file("pages/index.md", "title = \"Home\"\n+++\n# Welcome\n")
+        .with_file(
+            "templates/default.html",
+            "<main>{% block content %}{% endblock content %}</main>",
+        )
+}
+
+fn read(fs: &MemoryFs, path: &str) -> String {
+    fs.read_to_string(Path::new(path)).unwrap()
+}
+
+fn exists(fs: &MemoryFs, path: &str) -> bool {
+    fs.is_file(Path::new(path))
+}
+
+#[test]
+fn pages_render_with_site_templates() {
+    let fs = site();
+    let mut ssg = Config::default().with_fs(fs.clone()).finish().unwrap();
+    ssg.build().unwrap();
+    let html = read(&fs, "public/index.html");
+    assert!(html.starts_with("<main>"), "{html}");
+    assert!(html.contains("Welcome"), "{html}");
+}
+
+#[test]
+fn shortcodes_and_data_come_from_the_site_fs() {
+    let fs = site()
+        .with_file(
+            "pages/about.md",
+            "title = \"About\"\n+++\n{{ shortcode(name=\"note\", n=3) | safe \
+             }} {{ data.site.name }}\n",
+        )
+        .with_file("shortcodes/note.html", "<aside>{{ n }}</aside>")
+        .with_file("data/site.toml", "name = \"Lexicon\"\n");
+    let mut ssg = Config::default().with_fs(fs.clone()).finish().unwrap();
+    ssg.build().unwrap();
+    let html = read(&fs, "public/about/index.html");
+    assert!(html.contains("<aside>3</aside>"), "{html}");
+    assert!(html.contains("Lexicon"), "{html}");
+}
+
+#[test]
+fn scss_imports_resolve_through_the_site_fs() {
+    let fs =
+        site().with_file("assets/_colors.scss", "$accent: red;\n").with_file(
+            "assets/style.scss",
+            "@use \"colors\";\na { color: colors.$accent; }\n",
+        );
+    let mut ssg = Config::default().with_fs(fs.clone()).finish().unwrap();
+    ssg.build().unwrap();
+    assert!(read(&fs, "public/assets/style.css").contains("color: red"));
+    assert!(!exists(&fs, "public/assets/_colors.css"));
+}
+
+#[test]
+fn bundles_find_their_sources_in_the_site_fs() {
+    let fs = site()
+        .with_file("assets/a.css", "a { color: red; }")
+        .with_file("assets/b.css", "b { color: blue; }");
+    let mut ssg = Config::default()
+        .with_fs(fs.clone())
+        .with_bundle("main.css", ["a.css", "b.css"])
+        .finish()
+        .unwrap();
+    ssg.build_all_errors().unwrap();
+    let bundle = read(&fs, "public/assets/bundles/main.css");
+    assert!(bundle.contains("red") && bundle.contains("blue"), "{bundle}");
+}
+
+#[test]
+fn first_build_keeps_files_it_did_not_write() {
+    let fs = site()
+        .with_file("public/README.md", "Deployed from CI")
+        .with_file("public/.nojekyll", "");
+    let mut ssg = Config::default().with_fs(fs.clone()).finish().unwrap();
+    ssg.build().unwrap();
+    ssg.build().unwrap();
+    assert_eq!(read(&fs, "public/README.md"), "Deployed from CI");
+    assert!(exists(&fs, "public/.nojekyll"));
+}
+
+#[test]
+fn first_build_overwrites_stale_outputs_it_regenerates() {
+    let fs = site()
+        .with_file("pages/old.md", "title = \"Old\"\n+++\nGone\n")
+        .with_file("public/index.html", "<p>Hand written</p>")
+        .with_file("public/old/index.html", "<p>Older lin-ssg</p>")
+        .with_file("public/CNAME", "example.com");
+    let mut ssg = Config::default().with_fs(fs.clone()).finish().unwrap();
+    ssg.build().unwrap();
+    assert!(read(&fs, "public/index.html").contains("Welcome"));
+    assert!(read(&fs, "public/old/index.html").contains("Gone"));
+    fs.remove_file(Path::new("pages/old.md")).unwrap();
+    ssg.build().unwrap();
+    assert!(!exists(&fs, "public/old/index.html"));
+    assert_eq!(read(&fs, "public/CNAME"), "example.com");
+}
+
+#[test]
+fn rebuild_removes_outputs_of_deleted_pages() {
+    let fs = site().with_file("pages/old.md", "title = \"Old\"\n+++\nGone\n");
+    let mut ssg = Config::default().with_fs(fs.clone()).finish().unwrap();
+    ssg.build().unwrap();
+    assert!(exists(&fs, "public/old/index.html"));
+    fs.remove_file(Path::new("pages/old.md")).unwrap();
+    ssg.build().unwrap();
+    assert!(!exists(&fs, "public/old/index.html"));
+    assert!(exists(&fs, "public/index.html"));
+}
+
+#[test]
+fn reloaded_templates_drop_deleted_files() {
+    let fs = site()
+        .with_file(
+            "pages/about.md",
+            "title = \"About\"\n+++\n{% include \"aside.html\" %} {{ \
+             shortcode(name=\"note\") | safe }}\n",
+        )
+        .with_file("templates/aside.html", "<aside>Old</aside>")
+        .with_file("shortcodes/note.html", "<b>Note</b>");
+    let mut ssg = Config::default().with_fs(fs.clone()).finish().unwrap();
+    ssg.build().unwrap();
+    fs.write(Path::new("templates/aside.html"), b"<aside>New</aside>").unwrap();
+    ssg.reload_templates().unwrap();
+    let html = read(&fs, "public/about/index.html");
+    assert!(html.contains("<aside>New</aside>"), "{html}");
+    assert!(html.contains("<b>Note</b>"), "{html}");
+    fs.remove_file(Path::new("templates/aside.html")).unwrap();
+    assert!(ssg.reload_templates().is_err());
+}
+
+#[test]
+fn sites_keep_their_own_linguinput_tables() {
+    let thorn = Config::default()
+        .with_fs(site().with_file("table.toml", "\"QQa\" = \"ᚦ\"\n"))
+        .with_linguinput_table("table.toml")
+        .finish()
+        .unwrap();
+    let fehu = Config::default()
+        .with_fs(site().with_file("table.toml", "\"QQa\" = \"ᚠ\"\n"))
+        .with_linguinput_table("table.toml")
+        .finish()
+        .unwrap();
+    let plain = Config::default().with_fs(site()).finish().unwrap();
+    assert_eq!(thorn.linguinput_table().code_to_char("QQa"), Some("ᚦ"));
+    assert_eq!(fehu.linguinput_table().code_to_char("QQa"), Some("ᚠ"));
+    assert_eq!(plain.linguinput_table().code_to_char("QQa"), None);
+}
+
+#[test]
+fn sites_keep_their_own_named_linguinput_tables() {
+    let named = |rune: &str| {
+        Config::default()
+            .with_fs(
+                site()
+                    .with_file("runes.toml", format!("\"th\" = \"{rune}\"\n")),
+            )
+            .with_named_linguinput_table("runes", "runes.toml")
+            .finish()
+            .unwrap()
+    };
+    let thorn = named("ᚦ");
+    let fehu = named("ᚠ");
+    assert_eq!(
+        thorn.named_linguinput_tables()["runes"].code_to_char("th"),
+        Some("ᚦ")
+    );
+    assert_eq!(
+        fehu.named_linguinput_tables()["runes"].code_to_char("th"),
+        Some("ᚠ")
+    );
+    let default = Config::default()
+        .with_fs(site().with_file("runes.toml", "\"th\" = \"ᚦ\"\n"))
+        .with_named_linguinput_table("default", "runes.toml")
+        .finish();
+    assert!(default.is_err());
+}
+
+#[test]
+fn dry_run_reports_changes_without_writing_them() {
+    let fs = site();
+    let mut ssg = Config::default().with_fs(fs.clone()).finish().unwrap();
+    ssg.build().unwrap();
+    fs.write(Path::new("pages/new.md"), b"title = \"New\"\n+++\nFresh\n")
+        .unwrap();
+    let diff = ssg.build_dry_run().unwrap();
+    assert_eq!(diff.created, [PathBuf::from("new/index.html")]);
+    assert!(diff.changed.is_empty() && diff.deleted.is_empty(), "{diff}");
+    assert!(!exists(&fs, "public/new/index.html"));
+    assert_eq!(ssg.build_dry_run().unwrap().created.len(), 1);
+}
+
+#[test]
+fn listings_see_summaries_of_other_pages() {
+    let fs = site()
+        .with_file(
+            "pages/post.md",
+            "title = \"Post\"\n+++\nOpening line.\n\n<!-- more -->\n\nRest.\n",
+        )
+        .with_file(
+            "pages/list.md",
+            "title = \"List\"\n+++\n{% for p in site.pages %}[{{ p.url }}: {{ \
+             p.metadata.summary | default(value=\"none\") | safe }}]{% endfor \
+             %}\n",
+        );
+    let mut ssg = Config::default().with_fs(fs.clone()).finish().unwrap();
+    ssg.build().unwrap();
+    let html = read(&fs, "public/list/index.html");
+    assert!(html.contains("[/post/: <p>Opening line.</p>]"), "{html}");
+    assert!(!read(&fs, "public/post/index.html").contains("lin-ssg:summary"));
+}
+
+#[test]
+fn external_link_report_creates_its_directory() {
+    let fs = site().with_file(
+        "pages/links.md",
+        "title = \"Links\"\n+++\n[Out](https://example.com/)\n",
+    );
+    let mut ssg = Config::default()
+        .with_fs(fs.clone())
+        .with_external_link_report("/reports/links/external.json")
+        .finish()
+        .unwrap();
+    ssg.build().unwrap();
+    let report = read(&fs, "/reports/links/external.json");
+    assert!(report.contains("https://example.com/"), "{report}");
+}
+
+#[test]
+fn validate_reports_every_problem_at_once() {
+    let config = Config::default()
+        .with_fs(site())
+        .with_theme("themes/missing")
+        .with_lexicon("missing.csv")
+        .with_output("elsewhere/../pages/public");
+    let errors = config.validate().unwrap_err();
+    assert!(errors
+        .iter()
+        .any(|error| matches!(error, ConfigError::MissingDir("Theme", _))));
+    assert!(errors
+        .iter()
+        .any(|error| matches!(error, ConfigError::MissingFile("Lexicon", _))));
+    assert!(errors
+        .iter()
+        .any(|error| matches!(error, ConfigError::OutputInside(..))));
+}
+
+#[test]
+fn validate_checks_paths_in_the_site_fs() {
+    let config = Config::default().with_fs(site()).with_theme("pages");
+    assert!(config.validate().is_ok(), "{:?}", config.validate());
+}
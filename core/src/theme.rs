@@ -0,0 +1,148 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use ignore::overrides::OverrideBuilder;
+use tera::Tera;
+
+use crate::{
+    config::TEMPLATE_GLOB,
+    walk::{Walk, WalkEntry},
+    Config,
+};
+
+pub const TEMPLATE_DIR: &str = "templates";
+pub const ASSET_DIR: &str = "assets";
+
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] =
+    &[("default.html", include_str!("theme/default.html"))];
+
+pub const BUILTIN_ASSETS: &[(&str, &str)] =
+    &[("lin-ssg.css", include_str!("theme/lin-ssg.css"))];
+
+// Kept apart from the other builtin templates, since books are an opt-in
+// export and a site should not need to override this layout.
+pub const EPUB_LAYOUT: (&str, &str) =
+    ("epub.xhtml", include_str!("theme/epub.xhtml"));
+
+const SCAFFOLD_PAGE: (&str, &str) =
+    ("index.md", include_str!("theme/index.md"));
+
+pub fn load_templates(config: &Config) -> Result<(Tera, bool), tera::Error> {
+    let mut layers = config
+        .template_dirs()
+        .iter()
+        .map(|(template_dir, glob)| read_glob(config, &template_dir.join(glob)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(theme_dir) = config.theme_dir() {
+        layers.push(templates(config, theme_dir)?);
+    }
+    let builtin = BUILTIN_TEMPLATES.iter().any(|(name, _)| {
+        !layers.iter().any(|layer| layer.iter().any(|(known, _)| known == name))
+    });
+    // Layers are added from the bottom up, so earlier layers take precedence
+    // and may still extend templates of later ones.
+    let mut tera = builtin_templates()?;
+    for layer in layers.into_iter().rev() {
+        tera.add_raw_templates(layer)?;
+    }
+    Ok((tera, builtin))
+}
+
+// Names and sources of the templates a glob matches, named relative to the
+// glob's base directory, as `Tera::new` names them.
+pub fn read_glob(
+    config: &Config,
+    glob: &Path,
+) -> Result<Vec<(String, String)>, tera::Error> {
+    let glob = glob.to_str().ok_or_else(|| {
+        tera::Error::msg("Template directory path is not valid UTF-8")
+    })?;
+    let Some(star) = glob.find('*') else {
+        Err(tera::Error::msg(format!(
+            "Template glob {glob:?} has no wildcard"
+        )))?
+    };
+    let (base_dir, pattern) = glob.split_at(star);
+    let base_dir = match base_dir {
+        "" => Path::new("."),
+        base_dir => Path::new(base_dir),
+    };
+    let fs = config.fs();
+    let mut templates = Vec::new();
+    if !fs.is_dir(base_dir) {
+        return Ok(templates);
+    }
+    let matcher = OverrideBuilder::new(base_dir)
+        .add(pattern)
+        .and_then(|builder| builder.build())
+        .map_err(|error| {
+            tera::Error::chain(format!("Invalid template glob {glob:?}"), error)
+        })?;
+    let walk = Walk::new(
+        config.shared_fs(),
+        base_dir,
+        config.follow_external_symlinks(),
+    )
+    .map_err(|error| tera::Error::chain("Failed to list templates", error))?;
+    for entry in walk {
+        let WalkEntry { path, path_rel, is_dir } = entry.map_err(|error| {
+            tera::Error::chain("Failed to list templates", error)
+        })?;
+        if is_dir || !matcher.matched(&path_rel, false).is_whitelist() {
+            continue;
+        }
+        let code = fs.read_to_string(&path).map_err(|error| {
+            tera::Error::chain(
+                format!("Failed to read template {}", path.display()),
+                error,
+            )
+        })?;
+        templates.push((path_rel.to_string_lossy().replace('\\', "/"), code));
+    }
+    Ok(templates)
+}
+
+pub fn templates(
+    config: &Config,
+    theme_dir: &Path,
+) -> Result<Vec<(String, String)>, tera::Error> {
+    read_glob(config, &theme_dir.join(TEMPLATE_DIR).join(TEMPLATE_GLOB))
+}
+
+pub fn builtin_templates() -> Result<Tera, tera::Error> {
+    let mut tera = Tera::default();
+    tera.add_raw_templates(
+        BUILTIN_TEMPLATES.iter().copied().chain([EPUB_LAYOUT]),
+    )?;
+    Ok(tera)
+}
+
+pub fn scaffold(config: &Config) -> io::Result<Vec<PathBuf>> {
+    let files = BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, code)| (config.template_dir(), *name, *code))
+        .chain(
+            BUILTIN_ASSETS
+                .iter()
+                .map(|(name, code)| (config.asset_dir(), *name, *code)),
+        )
+        .chain([(config.page_dir(), SCAFFOLD_PAGE.0, SCAFFOLD_PAGE.1)]);
+    let fs = config.fs();
+    let mut written = Vec::new();
+    for (directory, name, code) in files {
+        fs.create_dir_all(directory)?;
+        let path = directory.join(name);
+        // Files the site already has are left as they are.
+        match fs.create_new(&path, code.as_bytes()) {
+            Ok(()) => (),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                continue
+            },
+            Err(error) => Err(error)?,
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
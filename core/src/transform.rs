@@ -0,0 +1,55 @@
+use std::{error::Error, fmt, sync::Arc};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("Transform {} failed", .transform)]
+pub struct TransformError {
+    transform: String,
+    #[source]
+    source: Box<dyn Error + Send + Sync>,
+}
+
+impl TransformError {
+    pub fn new(
+        transform: impl Into<String>,
+        source: impl Into<Box<dyn Error + Send + Sync>>,
+    ) -> Self {
+        Self { transform: transform.into(), source: source.into() }
+    }
+}
+
+pub trait Transform: Send + Sync + 'static {
+    fn name(&self) -> &str;
+
+    fn transform(&self, html: &str) -> Result<String, TransformError>;
+}
+
+#[derive(Clone, Default)]
+pub struct Transforms {
+    transforms: Vec<Arc<dyn Transform>>,
+}
+
+impl Transforms {
+    pub fn register<T>(&mut self, transform: T)
+    where
+        T: Transform,
+    {
+        self.transforms.push(Arc::new(transform));
+    }
+
+    pub fn apply(&self, mut html: String) -> Result<String, TransformError> {
+        for transform in &self.transforms {
+            html = transform.transform(&html)?;
+        }
+        Ok(html)
+    }
+}
+
+impl fmt::Debug for Transforms {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_list()
+            .entries(self.transforms.iter().map(|transform| transform.name()))
+            .finish()
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    minify,
+    transform::{Transform, TransformError},
+};
+
+pub const VERBATIM_START: &str = "<!--lin-ssg:verbatim-start-->";
+
+pub const VERBATIM_END: &str = "<!--lin-ssg:verbatim-end-->";
+
+const VERBATIM_ELEMENTS: &[&str] =
+    &["code", "pre", "kbd", "samp", "script", "style", "textarea", "math"];
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta",
+    "source", "track", "wbr",
+];
+
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "cite", "data", "del", "dfn", "em", "i",
+    "ins", "mark", "q", "rp", "rt", "ruby", "s", "small", "span", "strong",
+    "sub", "sup", "time", "u", "var",
+];
+
+const QUOTE_ENTITIES: &[(&str, char)] =
+    &[("&quot;", '"'), ("&#34;", '"'), ("&#x27;", '\''), ("&#39;", '\'')];
+
+pub fn verbatim(html: &str) -> String {
+    format!("{VERBATIM_START}{html}{VERBATIM_END}")
+}
+
+pub fn strip_verbatim(html: &str) -> &str {
+    html.trim_start()
+        .trim_start_matches(VERBATIM_START)
+        .trim_end()
+        .trim_end_matches(VERBATIM_END)
+}
+
+pub fn strip_markers(rendered: &str) -> String {
+    rendered.replace(VERBATIM_START, "").replace(VERBATIM_END, "")
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Typography;
+
+impl Typography {
+    fn is_one_of(name: &str, elements: &[&str]) -> bool {
+        elements.iter().any(|element| element.eq_ignore_ascii_case(name))
+    }
+
+    fn opens(previous: Option<char>) -> bool {
+        previous.is_none_or(|ch| ch.is_whitespace() || "([{‘“—–-/".contains(ch))
+    }
+}
+
+impl Transform for Typography {
+    fn name(&self) -> &str {
+        "typography"
+    }
+
+    fn transform(&self, html: &str) -> Result<String, TransformError> {
+        let mut output = String::with_capacity(html.len());
+        let mut rest = html;
+        let mut markers = 0usize;
+        let mut skipped = Vec::new();
+        let mut previous = None;
+
+        while let Some(ch) = rest.chars().next() {
+            if let Some(marker) = [VERBATIM_START, VERBATIM_END]
+                .into_iter()
+                .find(|marker| rest.starts_with(marker))
+            {
+                if marker == VERBATIM_START {
+                    markers += 1;
+                } else {
+                    markers = markers.saturating_sub(1);
+                }
+                output.push_str(marker);
+                rest = &rest[marker.len() ..];
+            } else if rest.starts_with("<!--") {
+                let len = rest.find("-->").map_or(rest.len(), |end| end + 3);
+                output.push_str(&rest[.. len]);
+                rest = &rest[len ..];
+            } else if ch == '<' {
+                let tag_len = minify::tag_len(rest);
+                let tag = &rest[.. tag_len];
+                let closing = tag.starts_with("</");
+                let name_start = if closing { 2 } else { 1 };
+                let name = tag[name_start ..]
+                    .split(|ch: char| {
+                        ch.is_whitespace() || ch == '>' || ch == '/'
+                    })
+                    .next()
+                    .unwrap_or_default();
+                if closing {
+                    if skipped.last().is_some_and(|open: &String| {
+                        open.eq_ignore_ascii_case(name)
+                    }) {
+                        skipped.pop();
+                    }
+                } else if !tag.ends_with("/>")
+                    && !Self::is_one_of(name, VOID_ELEMENTS)
+                    && (!skipped.is_empty()
+                        || tag.contains(" translate=\"no\"")
+                        || Self::is_one_of(name, VERBATIM_ELEMENTS))
+                {
+                    skipped.push(name.to_owned());
+                }
+                // Quotes never pair across paragraphs, list items and the
+                // like, only across inline markup.
+                if !Self::is_one_of(name, INLINE_ELEMENTS) {
+                    previous = None;
+                }
+                output.push_str(tag);
+                rest = &rest[tag_len ..];
+            } else if markers > 0 || !skipped.is_empty() {
+                output.push(ch);
+                previous = Some(ch);
+                rest = &rest[ch.len_utf8() ..];
+            } else if let Some((entity, quote)) = QUOTE_ENTITIES
+                .iter()
+                .copied()
+                .find(|(entity, _)| rest.starts_with(entity))
+            {
+                let curly = match (quote, Self::opens(previous)) {
+                    ('"', true) => '“',
+                    ('"', false) => '”',
+                    (_, true) => '‘',
+                    (_, false) => '’',
+                };
+                output.push(curly);
+                previous = Some(curly);
+                rest = &rest[entity.len() ..];
+            } else {
+                let (replacement, len) = if rest.starts_with("---") {
+                    ('—', 3)
+                } else if rest.starts_with("--") {
+                    ('–', 2)
+                } else if rest.starts_with("...") {
+                    ('…', 3)
+                } else if ch == '"' {
+                    (if Self::opens(previous) { '“' } else { '”' }, 1)
+                } else if ch == '\'' {
+                    (if Self::opens(previous) { '‘' } else { '’' }, 1)
+                } else {
+                    (ch, ch.len_utf8())
+                };
+                output.push(replacement);
+                previous = Some(replacement);
+                rest = &rest[len ..];
+            }
+        }
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    collections::VecDeque,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use thiserror::Error;
+
+use crate::site_fs::{FileKind, SiteFs};
+
+#[derive(Debug, Error)]
+#[error("Could not walk {}", .path.display())]
+pub struct WalkError {
+    pub path: PathBuf,
+    #[source]
+    pub source: io::Error,
+}
+
+impl WalkError {
+    fn on(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        move |source| Self { path: path.into(), source }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkEntry {
+    pub path: PathBuf,
+    pub path_rel: PathBuf,
+    pub is_dir: bool,
+}
+
+#[derive(Debug, Clone)]
+struct PendingDir {
+    path: PathBuf,
+    path_rel: PathBuf,
+    // Canonical paths of this directory and its ancestors, so that a link
+    // back into any of them is recognized as a loop.
+    chain: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Walk {
+    fs: Arc<dyn SiteFs>,
+    root: PathBuf,
+    follow_external: bool,
+    excluded: Vec<PathBuf>,
+    ignore: Gitignore,
+    directories: Vec<PendingDir>,
+    entries: VecDeque<WalkEntry>,
+}
+
+impl Walk {
+    pub fn new(
+        fs: Arc<dyn SiteFs>,
+        root: &Path,
+        follow_external: bool,
+    ) -> Result<Self, WalkError> {
+        let canonical = fs.canonicalize(root).map_err(WalkError::on(root))?;
+        Ok(Self {
+            fs,
+            root: canonical.clone(),
+            follow_external,
+            excluded: Vec::new(),
+            ignore: Gitignore::empty(),
+            directories: vec![PendingDir {
+                path: root.to_owned(),
+                path_rel: PathBuf::new(),
+                chain: vec![canonical],
+            }],
+            entries: VecDeque::new(),
+        })
+    }
+
+    pub fn excluding(mut self, path: &Path) -> Self {
+        // Paths that do not exist yet cannot be reached by the walk anyway.
+        if let Ok(canonical) = self.fs.canonicalize(path) {
+            self.excluded.push(canonical);
+        }
+        self
+    }
+
+    pub fn ignoring(mut self, ignore: Gitignore) -> Self {
+        self.ignore = ignore;
+        self
+    }
+
+    fn enter(&mut self, directory: PendingDir) -> Result<(), WalkError> {
+        let entries = self
+            .fs
+            .read_dir(&directory.path)
+            .map_err(WalkError::on(&directory.path))?;
+
+        self.entries.push_back(WalkEntry {
+            path: directory.path.clone(),
+            path_rel: directory.path_rel.clone(),
+            is_dir: true,
+        });
+        for path in entries {
+            // The entry keeps the path through its link, so it lands in the
+            // output where the link sits rather than where its target does.
+            let Some(file_name) = path.file_name() else {
+                continue;
+            };
+            let path_rel = directory.path_rel.join(file_name);
+            let is_symlink =
+                self.fs.is_symlink(&path).map_err(WalkError::on(&path))?;
+            let kind = self.fs.kind(&path).map_err(WalkError::on(&path))?;
+            let is_dir = kind == FileKind::Dir;
+            if self.ignore.matched(&path_rel, is_dir).is_ignore() {
+                continue;
+            }
+            let canonical = if is_symlink {
+                let canonical = self
+                    .fs
+                    .canonicalize(&path)
+                    .map_err(WalkError::on(&path))?;
+                if !self.follow_external && !canonical.starts_with(&self.root) {
+                    continue;
+                }
+                Some(canonical)
+            } else {
+                None
+            };
+
+            if is_dir {
+                let canonical = match canonical {
+                    Some(canonical) => canonical,
+                    None => self
+                        .fs
+                        .canonicalize(&path)
+                        .map_err(WalkError::on(&path))?,
+                };
+                if directory.chain.contains(&canonical)
+                    || self.excluded.contains(&canonical)
+                {
+                    continue;
+                }
+                let mut chain = directory.chain.clone();
+                chain.push(canonical);
+                self.directories.push(PendingDir { path, path_rel, chain });
+            } else if kind == FileKind::File {
+                self.entries.push_back(WalkEntry {
+                    path,
+                    path_rel,
+                    is_dir: false,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn ignore_rules(
+    fs: &dyn SiteFs,
+    root: &Path,
+    ignore_file: &Path,
+    patterns: &[String],
+) -> Result<Gitignore, ignore::Error> {
+    let mut builder = GitignoreBuilder::new(root);
+    if fs.is_file(ignore_file) {
+        let code = fs.read_to_string(ignore_file).map_err(|error| {
+            ignore::Error::WithPath {
+                path: ignore_file.to_owned(),
+                err: Box::new(error.into()),
+            }
+        })?;
+        for line in code.lines() {
+            builder.add_line(Some(ignore_file.to_owned()), line)?;
+        }
+    }
+    for pattern in patterns {
+        builder.add_line(None, pattern)?;
+    }
+    builder.build()
+}
+
+impl Iterator for Walk {
+    type Item = Result<WalkEntry, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.entries.pop_front() {
+                return Some(Ok(entry));
+            }
+            let directory = self.directories.pop()?;
+            if let Err(error) = self.enter(directory) {
+                return Some(Err(error));
+            }
+        }
+    }
+}
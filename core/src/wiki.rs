@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    function::{ArgError, ArgParser, Args, Function},
+    page_ctx::PageCtx,
+};
+
+pub const LINK_START: &str = "[[";
+
+pub const LINK_END: &str = "]]";
+
+pub const LABEL_SEPARATOR: char = '|';
+
+#[derive(Debug, Error)]
+pub enum WikiError {
+    #[error("Wiki link target {} matches no page", .0)]
+    NotFound(String),
+    #[error("Wiki link target {} matches several pages: {}", .0, .1.join(", "))]
+    Ambiguous(String, Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Backlink {
+    pub url: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WikiPage {
+    url: String,
+    lang: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WikiIndex {
+    pages: Arc<RwLock<HashMap<String, Vec<WikiPage>>>>,
+}
+
+impl WikiIndex {
+    pub fn clear(&self) {
+        self.pages.write().unwrap_or_else(|error| error.into_inner()).clear();
+    }
+
+    pub fn insert(&self, title: &str, url: &str, lang: Option<&str>) {
+        let mut pages =
+            self.pages.write().unwrap_or_else(|error| error.into_inner());
+        let page =
+            WikiPage { url: url.to_owned(), lang: lang.map(ToOwned::to_owned) };
+        let slug = url.trim_end_matches('/').rsplit('/').next();
+        let keys = [Some(title), slug, Some(url)];
+        for key in keys.into_iter().flatten().filter(|key| !key.is_empty()) {
+            let candidates = pages.entry(normalize(key)).or_default();
+            if !candidates.contains(&page) {
+                candidates.push(page.clone());
+            }
+        }
+    }
+
+    pub fn resolve(
+        &self,
+        target: &str,
+        lang: Option<&str>,
+    ) -> Result<String, WikiError> {
+        let pages =
+            self.pages.read().unwrap_or_else(|error| error.into_inner());
+        let candidates = pages.get(&normalize(target)).map_or(&[][..], |c| c);
+        let preferred: Vec<_> = candidates
+            .iter()
+            .filter(|page| page.lang.as_deref() == lang)
+            .collect();
+        let candidates: Vec<_> = if preferred.is_empty() {
+            candidates.iter().collect()
+        } else {
+            preferred
+        };
+        match &candidates[..] {
+            [] => Err(WikiError::NotFound(target.to_owned())),
+            [page] => Ok(page.url.clone()),
+            _ => Err(WikiError::Ambiguous(
+                target.to_owned(),
+                candidates.iter().map(|page| page.url.clone()).collect(),
+            )),
+        }
+    }
+}
+
+fn normalize(key: &str) -> String {
+    key.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WikiUrlArgs<'a> {
+    to: &'a str,
+}
+
+impl<'a> Args<'a> for WikiUrlArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let to = args.retrive_arg("to")?;
+        Ok(Self { to })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WikiUrlFn {
+    index: WikiIndex,
+}
+
+impl WikiUrlFn {
+    pub fn new(index: WikiIndex) -> Self {
+        Self { index }
+    }
+}
+
+impl Function for WikiUrlFn {
+    type Args<'a> = WikiUrlArgs<'a>;
+    type Output = String;
+    type Error = WikiError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let lang = ctx.metadata().get("lang").and_then(|lang| lang.as_str());
+        self.index.resolve(args.to, lang)
+    }
+
+    fn doc(&self) -> String {
+        "{# URL of the page with the given title, slug or URL, as [[to]] #}
+        wiki_url(
+            {# page title, slug or URL #}
+            to:string
+        ) -> String "
+            .to_owned()
+    }
+}
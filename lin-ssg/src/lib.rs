@@ -0,0 +1,50 @@
+#[cfg(feature = "citations")]
+pub use lin_ssg_citations as citations;
+pub use lin_ssg_core::*;
+#[cfg(feature = "linguistics")]
+pub use lin_ssg_linguistics as linguistics;
+#[cfg(feature = "soundchange")]
+pub use lin_ssg_soundchange as soundchange;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SetupError {
+    #[error(transparent)]
+    Init(Box<InitError>),
+    #[error(transparent)]
+    Pack(#[from] PackError),
+}
+
+impl From<InitError> for SetupError {
+    fn from(error: InitError) -> Self {
+        Self::Init(Box::new(error))
+    }
+}
+
+// Every pack enabled by the crate features, with its default settings. The
+// site config still picks which of them are installed.
+pub fn default_packs() -> Result<PackRegistry, PackError> {
+    let registry = PackRegistry::new();
+    #[cfg(feature = "linguistics")]
+    let registry = registry.with(linguistics::LinguisticsPack::default())?;
+    #[cfg(feature = "soundchange")]
+    let registry = registry.with(soundchange::SoundChangePack::default())?;
+    #[cfg(feature = "citations")]
+    let registry = registry.with(citations::CitationsPack::default())?;
+    Ok(registry)
+}
+
+// Lets `LinSsg::with_default_packs(config)` build a site with the default
+// packs installed.
+pub trait DefaultPacks: Sized {
+    fn with_default_packs(config: Config) -> Result<Self, SetupError>;
+}
+
+impl DefaultPacks for LinSsg {
+    fn with_default_packs(config: Config) -> Result<Self, SetupError> {
+        let mut ssg = config.finish()?;
+        ssg.install_packs(&default_packs()?)?;
+        Ok(ssg)
+    }
+}
@@ -0,0 +1,89 @@
+use std::{collections::BTreeMap, env, fmt::Write as _, fs, path::Path};
+
+#[path = "src/table/raw.rs"]
+mod raw;
+
+#[path = "src/table/xsampa.rs"]
+mod xsampa;
+
+#[derive(Debug, Default)]
+struct Node {
+    parent: usize,
+    label: char,
+    value: Option<&'static str>,
+    edges: BTreeMap<char, usize>,
+}
+
+fn build<I>(name: &str, entries: I) -> Vec<Node>
+where
+    I: IntoIterator<Item = (&'static str, &'static str)>,
+{
+    let mut nodes = vec![Node::default()];
+    for (key, value) in entries {
+        let mut node = 0;
+        for ch in key.chars() {
+            let parent = node;
+            let next = nodes.len();
+            node = *nodes[parent].edges.entry(ch).or_insert(next);
+            if node == next {
+                nodes.push(Node { parent, label: ch, ..Node::default() });
+            }
+        }
+        if let Some(previous) = nodes[node].value.replace(value) {
+            panic!(
+                "duplicated key {key:?} in {name} ({previous:?}, {value:?})"
+            );
+        }
+    }
+    nodes
+}
+
+fn emit(code: &mut String, name: &str, nodes: &[Node]) {
+    writeln!(code, "static {name}_NODES: [TrieNode; {}] = [", nodes.len())
+        .unwrap();
+    for node in nodes {
+        write!(
+            code,
+            "    TrieNode::new_static({}, {:?}, {:?}, &[",
+            node.parent, node.label, node.value
+        )
+        .unwrap();
+        for (label, child) in &node.edges {
+            write!(code, "({label:?}, {child}), ").unwrap();
+        }
+        writeln!(code, "]),").unwrap();
+    }
+    writeln!(code, "];").unwrap();
+    writeln!(
+        code,
+        "pub static {name}: Trie = Trie::from_static(&{name}_NODES);"
+    )
+    .unwrap();
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/table/raw.rs");
+    println!("cargo:rerun-if-changed=src/table/xsampa.rs");
+
+    let codes = build("builtin codes", raw::TABLE.iter().copied());
+    let chars =
+        build("builtin chars", raw::TABLE.iter().map(|&(code, ch)| (ch, code)));
+    let xsampa = build("X-SAMPA codes", xsampa::TABLE.iter().copied());
+
+    let mut code = String::new();
+    emit(&mut code, "BUILTIN_CODES", &codes);
+    emit(&mut code, "BUILTIN_CHARS", &chars);
+    emit(&mut code, "XSAMPA_CODES", &xsampa);
+    let xsampa_max_key_len =
+        xsampa::TABLE.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    writeln!(
+        code,
+        "pub const XSAMPA_MAX_KEY_LEN: usize = {xsampa_max_key_len};"
+    )
+    .unwrap();
+
+    let out_dir = env::var_os("OUT_DIR").expect("OUT_DIR is not set");
+    fs::write(Path::new(&out_dir).join("tries.rs"), code)
+        .expect("failed to write generated tries");
+}
@@ -0,0 +1,83 @@
+use std::{
+    env,
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+    process,
+};
+
+use lin_ssg_linguinput::{decode_io_with, encode_io_with, Table};
+
+const USAGE: &str = "usage: linguinput <encode|decode> [--table FILE] [FILE]
+
+Reads FILE (or stdin when FILE is missing or -) and writes the result to
+stdout. --table merges extra codes from a .toml, .csv or .tsv file into the
+builtin table.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Encode,
+    Decode,
+}
+
+#[derive(Debug)]
+struct Options {
+    command: Command,
+    table: Option<PathBuf>,
+    input: Option<PathBuf>,
+}
+
+fn parse_args() -> Option<Options> {
+    let mut args = env::args_os().skip(1);
+    let command = match args.next()?.to_str()? {
+        "encode" => Command::Encode,
+        "decode" => Command::Decode,
+        _ => None?,
+    };
+    let mut table = None;
+    let mut input = None;
+    while let Some(arg) = args.next() {
+        if arg == "--table" {
+            table = Some(PathBuf::from(args.next()?));
+        } else if input.is_none() {
+            input = Some(PathBuf::from(arg)).filter(|path| path != "-");
+        } else {
+            None?;
+        }
+    }
+    Some(Options { command, table, input })
+}
+
+fn run(options: Options) -> Result<(), Box<dyn Error>> {
+    let table = match &options.table {
+        Some(path) => Table::load_with(path)?,
+        None => Table::builtin(),
+    };
+    let input: Box<dyn BufRead> = match &options.input {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(io::stdin().lock()),
+    };
+    let output = io::stdout().lock();
+    match options.command {
+        Command::Encode => encode_io_with(input, output, &table)?,
+        Command::Decode => decode_io_with(input, output, &table)?,
+    }
+    Ok(())
+}
+
+fn main() {
+    let Some(options) = parse_args() else {
+        eprintln!("{USAGE}");
+        process::exit(2);
+    };
+    if let Err(error) = run(options) {
+        eprintln!("linguinput: {error}");
+        let mut source = error.source();
+        while let Some(error) = source {
+            eprintln!("- caused by: {error}");
+            source = error.source();
+        }
+        process::exit(1);
+    }
+}
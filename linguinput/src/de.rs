@@ -1,9 +1,8 @@
 use std::fmt;
 
 use thiserror::Error;
-use unicode_segmentation::UnicodeSegmentation;
 
-use crate::{table::Table, TableInitError};
+use crate::{en::LITERAL_QUOTE, table::Table, TableInitError};
 
 #[derive(Debug, Error)]
 pub enum DecodingError {
@@ -22,26 +21,65 @@ pub enum DecodingError {
 }
 
 #[derive(Debug, Clone)]
-pub struct Decoder<W> {
-    table: &'static Table,
+pub struct Decoder<'t, W> {
+    table: &'t Table,
     target: W,
 }
 
-impl<W> Decoder<W>
+impl<'t, W> Decoder<'t, W>
 where
     W: fmt::Write,
 {
     pub fn new(target: W) -> Result<Self, DecodingError> {
-        Ok(Self { table: Table::load()?, target })
+        Ok(Self::with_table(target, Table::load()?))
     }
 
-    pub fn push(
-        &mut self,
-        ch: &'static str,
-    ) -> Result<&mut Self, DecodingError> {
-        match self.table.char_to_code(ch) {
-            Some(code) => write!(self.target, "{}", code)?,
-            None => write!(self.target, "{}", ch)?,
+    pub fn with_table(target: W, table: &'t Table) -> Self {
+        Self { table, target }
+    }
+
+    pub fn target(&self) -> &W {
+        &self.target
+    }
+
+    pub fn target_mut(&mut self) -> &mut W {
+        &mut self.target
+    }
+
+    pub fn into_target(self) -> W {
+        self.target
+    }
+
+    pub fn push(&mut self, ch: &str) -> Result<&mut Self, DecodingError> {
+        let mut rest = ch;
+        while let Some(first) = rest.chars().next() {
+            match self.table.chars().longest_prefix(rest) {
+                Some((end, code)) => {
+                    write!(self.target, "{{{}}}", code)?;
+                    rest = &rest[end ..];
+                },
+                None if first == '}' => {
+                    write!(self.target, "}}}}")?;
+                    rest = &rest[1 ..];
+                },
+                None if first == '{' => {
+                    let end = rest
+                        .find(|ch| ch != '{' && ch != '}')
+                        .unwrap_or(rest.len());
+                    write!(
+                        self.target,
+                        "{{{}{}{}}}",
+                        LITERAL_QUOTE,
+                        &rest[.. end],
+                        LITERAL_QUOTE
+                    )?;
+                    rest = &rest[end ..];
+                },
+                None => {
+                    write!(self.target, "{}", first)?;
+                    rest = &rest[first.len_utf8() ..];
+                },
+            }
         }
         Ok(self)
     }
@@ -50,9 +88,6 @@ where
         &mut self,
         content: &str,
     ) -> Result<&mut Self, DecodingError> {
-        for ch in content.graphemes(true) {
-            self.push_str(ch)?;
-        }
-        Ok(self)
+        self.push(content)
     }
 }
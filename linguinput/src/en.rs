@@ -1,8 +1,14 @@
-use std::fmt::{self, Write};
+use std::{
+    fmt::{self, Write},
+    str,
+};
 
 use thiserror::Error;
 
-use crate::{table::Table, TableInitError};
+use crate::{
+    table::{NodeId, Table, Trie, XSAMPA_CODES, XSAMPA_MAX_KEY_LEN},
+    TableInitError,
+};
 
 #[derive(Debug, Error)]
 pub enum EncodingError {
@@ -18,97 +24,353 @@ pub enum EncodingError {
         #[from]
         fmt::Error,
     ),
-    #[error("Unmatched '{{'")]
-    UnmatchedOpen,
-    #[error("Unmatched '}}'")]
-    UnmatchedClose,
-    #[error("Code {} is too big", .0)]
-    CodeTooBig(String),
-    #[error("Unknown code {}", .0)]
-    UnknownCode(String),
+    #[error("Unmatched '{{' at {} near `{}`", .position, .snippet)]
+    UnmatchedOpen { position: Position, snippet: String },
+    #[error("Unmatched '}}' at {} near `{}`", .position, .snippet)]
+    UnmatchedClose { position: Position, snippet: String },
+    #[error(
+        "Unknown code {} at {} near `{}`{}",
+        .code,
+        .position,
+        .snippet,
+        .suggestions
+    )]
+    UnknownCode {
+        code: String,
+        position: Position,
+        snippet: String,
+        suggestions: Suggestions,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Suggestions(pub Vec<String>);
+
+impl fmt::Display for Suggestions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, code) in self.0.iter().enumerate() {
+            let prefix = if i == 0 { "; did you mean " } else { ", " };
+            write!(f, "{}{{{}}}", prefix, code)?;
+        }
+        if !self.0.is_empty() {
+            write!(f, "?")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub const START: Self = Self { line: 1, column: 1 };
+
+    fn advance(&mut self, ch: char) {
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::START
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 enum EncoderState {
+    #[default]
     Default,
     Opening,
+    Unknown,
     Closing,
+    QuoteStart,
+    Quoted,
+    QuoteEnding,
 }
 
-impl Default for EncoderState {
-    fn default() -> Self {
-        Self::Default
-    }
+pub const LITERAL_QUOTE: char = '\'';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Syntax {
+    #[default]
+    Braces,
+    XSampa,
 }
 
+const SNIPPET_LEN: usize = 24;
+
+// X-SAMPA input waiting for a longer code to match. It only ever holds a
+// proper prefix of some code plus the char that was just pushed.
+const PENDING_LEN: usize = XSAMPA_MAX_KEY_LEN + 4;
+
 #[derive(Debug, Clone)]
-pub struct Encoder<W> {
-    table: &'static Table,
-    buf: String,
+pub struct Encoder<'t, W> {
+    table: &'t Table,
+    node: NodeId,
+    pending: [u8; PENDING_LEN],
+    pending_len: usize,
     state: EncoderState,
+    syntax: Syntax,
+    position: Position,
+    open_position: Position,
+    // The last chars pushed, as a ring indexed by `pushed`, so that errors
+    // can quote them without the encoder buffering anything on the heap.
+    recent: [char; SNIPPET_LEN],
+    pushed: usize,
+    open_at: usize,
     target: W,
 }
 
-impl<W> Encoder<W>
+impl<'t, W> Encoder<'t, W>
 where
     W: fmt::Write,
 {
+    const MAX_SUGGESTIONS: usize = 3;
+
     pub fn new(target: W) -> Result<Self, EncodingError> {
-        let table = Table::load()?;
-        Ok(Self {
+        Ok(Self::with_table(target, Table::load()?))
+    }
+
+    pub fn with_table(target: W, table: &'t Table) -> Self {
+        Self {
             table,
             state: EncoderState::Default,
-            buf: String::with_capacity(table.max_code_len()),
+            syntax: Syntax::Braces,
+            node: Trie::ROOT,
+            pending: [0; PENDING_LEN],
+            pending_len: 0,
+            position: Position::START,
+            open_position: Position::START,
+            recent: ['\0'; SNIPPET_LEN],
+            pushed: 0,
+            open_at: 0,
             target,
-        })
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    fn recent_since(&self, start: usize) -> impl Iterator<Item = char> + '_ {
+        let start = start.max(self.pushed.saturating_sub(SNIPPET_LEN));
+        (start .. self.pushed).map(|i| self.recent[i % SNIPPET_LEN])
+    }
+
+    fn snippet(&self) -> String {
+        self.recent_since(0).collect()
+    }
+
+    // The code between the last '{' and the '}' just pushed, cut to the
+    // chars still in `recent`.
+    fn open_code(&self) -> String {
+        let start = self.open_at + 1;
+        let mut code = String::new();
+        if start + SNIPPET_LEN < self.pushed {
+            code.push('…');
+        }
+        code.extend(self.recent_since(start));
+        code.pop();
+        code
+    }
+
+    fn pending(&self) -> &str {
+        str::from_utf8(&self.pending[.. self.pending_len])
+            .expect("pending X-SAMPA input holds whole chars")
+    }
+
+    pub fn with_syntax(
+        target: W,
+        syntax: Syntax,
+    ) -> Result<Self, EncodingError> {
+        let mut this = Self::new(target)?;
+        this.set_syntax(syntax);
+        Ok(this)
+    }
+
+    pub fn syntax(&self) -> Syntax {
+        self.syntax
+    }
+
+    pub fn set_syntax(&mut self, syntax: Syntax) -> &mut Self {
+        self.syntax = syntax;
+        self
+    }
+
+    pub fn target(&self) -> &W {
+        &self.target
+    }
+
+    pub fn target_mut(&mut self) -> &mut W {
+        &mut self.target
+    }
+
+    pub fn into_target(self) -> W {
+        self.target
     }
 
     pub fn push(&mut self, ch: char) -> Result<&mut Self, EncodingError> {
+        self.recent[self.pushed % SNIPPET_LEN] = ch;
+        self.pushed += 1;
+
+        if self.syntax == Syntax::XSampa {
+            let len =
+                ch.encode_utf8(&mut self.pending[self.pending_len ..]).len();
+            self.pending_len += len;
+            self.flush_xsampa(false)?;
+            self.position.advance(ch);
+            return Ok(self);
+        }
+
         match self.state {
             EncoderState::Default if ch == '{' => {
                 self.state = EncoderState::Opening;
+                self.node = Trie::ROOT;
+                self.open_position = self.position;
+                self.open_at = self.pushed - 1;
             },
             EncoderState::Default if ch == '}' => {
                 self.state = EncoderState::Closing;
+                self.open_position = self.position;
             },
             EncoderState::Default => {
                 write!(self.target, "{}", ch)?;
             },
-            EncoderState::Opening if self.buf.is_empty() && ch == '{' => {
+            EncoderState::Opening if self.node == Trie::ROOT && ch == '{' => {
                 write!(self.target, "{}", ch)?;
                 self.state = EncoderState::Default;
             },
-            EncoderState::Opening if ch == '}' => {
-                let Some(encoded) = self.table.code_to_char(&self.buf) else {
-                    Err(EncodingError::UnknownCode(self.buf.clone()))?
-                };
-                write!(self.target, "{}", encoded)?;
-                self.buf.clear();
-                self.state = EncoderState::Default;
-            },
             EncoderState::Opening
-                if self.buf.len() + ch.len_utf8()
-                    > self.table.max_code_len() =>
+                if self.node == Trie::ROOT && ch == LITERAL_QUOTE =>
             {
-                let mut code = self.buf.clone();
-                code.push(ch);
-                Err(EncodingError::CodeTooBig(code))?;
+                self.state = EncoderState::QuoteStart;
+            },
+            EncoderState::Opening if ch == '}' => {
+                self.close_code()?;
             },
             EncoderState::Opening => {
-                self.buf.push(ch);
+                self.step_code(ch);
             },
-            EncoderState::Closing if self.buf.is_empty() && ch == '}' => {
+            EncoderState::Unknown if ch == '}' => {
+                Err(self.unknown_code(self.open_code()))?;
+            },
+            EncoderState::Unknown => (),
+            EncoderState::Closing if ch == '}' => {
                 write!(self.target, "{}", ch)?;
                 self.state = EncoderState::Default;
             },
             EncoderState::Closing => {
-                Err(EncodingError::UnmatchedClose)?;
+                Err(EncodingError::UnmatchedClose {
+                    position: self.open_position,
+                    snippet: self.snippet(),
+                })?;
+            },
+            EncoderState::QuoteStart if ch == '}' => {
+                self.step_code(LITERAL_QUOTE);
+                match self.state {
+                    EncoderState::Unknown => {
+                        Err(self.unknown_code(self.open_code()))?
+                    },
+                    _ => self.close_code()?,
+                }
+            },
+            EncoderState::Quoted | EncoderState::QuoteStart
+                if ch == LITERAL_QUOTE =>
+            {
+                self.state = EncoderState::QuoteEnding;
+            },
+            EncoderState::Quoted | EncoderState::QuoteStart => {
+                write!(self.target, "{}", ch)?;
+                self.state = EncoderState::Quoted;
+            },
+            EncoderState::QuoteEnding if ch == '}' => {
+                self.state = EncoderState::Default;
+            },
+            EncoderState::QuoteEnding if ch == LITERAL_QUOTE => {
+                write!(self.target, "{}", ch)?;
+                self.state = EncoderState::Quoted;
+            },
+            EncoderState::QuoteEnding => {
+                write!(self.target, "{}{}", LITERAL_QUOTE, ch)?;
+                self.state = EncoderState::Quoted;
             },
         }
 
+        self.position.advance(ch);
         Ok(self)
     }
 
+    fn flush_xsampa(&mut self, all: bool) -> Result<(), EncodingError> {
+        while self.pending_len > 0
+            && (all
+                || XSAMPA_CODES
+                    .walk(Trie::ROOT, self.pending())
+                    .is_none_or(|node| XSAMPA_CODES.is_leaf(node)))
+        {
+            let end = match XSAMPA_CODES.longest_prefix(self.pending()) {
+                Some((end, encoded)) => {
+                    write!(self.target, "{}", encoded)?;
+                    end
+                },
+                None => {
+                    let first =
+                        self.pending().chars().next().unwrap_or_default();
+                    write!(self.target, "{}", first)?;
+                    first.len_utf8()
+                },
+            };
+            self.pending.copy_within(end .. self.pending_len, 0);
+            self.pending_len -= end;
+        }
+        Ok(())
+    }
+
+    fn step_code(&mut self, ch: char) {
+        match self.table.codes().step(self.node, ch) {
+            Some(node) => {
+                self.node = node;
+                self.state = EncoderState::Opening;
+            },
+            None => {
+                self.state = EncoderState::Unknown;
+            },
+        }
+    }
+
+    fn close_code(&mut self) -> Result<(), EncodingError> {
+        let Some(encoded) = self.table.codes().value(self.node) else {
+            Err(self.unknown_code(self.table.codes().key(self.node)))?
+        };
+        write!(self.target, "{}", encoded)?;
+        self.node = Trie::ROOT;
+        self.state = EncoderState::Default;
+        Ok(())
+    }
+
+    fn unknown_code(&self, code: String) -> EncodingError {
+        let suggestions = self.table.suggest(&code, Self::MAX_SUGGESTIONS);
+        EncodingError::UnknownCode {
+            position: self.open_position,
+            snippet: self.snippet(),
+            suggestions: Suggestions(suggestions),
+            code,
+        }
+    }
+
     pub fn push_str(
         &mut self,
         content: &str,
@@ -136,12 +398,12 @@ where
         &mut self,
         arguments: fmt::Arguments,
     ) -> Result<(), EncodingError> {
-        struct Adapter<'a, W> {
-            encoder: &'a mut Encoder<W>,
+        struct Adapter<'a, 't, W> {
+            encoder: &'a mut Encoder<'t, W>,
             result: Result<(), EncodingError>,
         }
 
-        impl<'a, W> fmt::Write for Adapter<'a, W>
+        impl<W> fmt::Write for Adapter<'_, '_, W>
         where
             W: fmt::Write,
         {
@@ -163,10 +425,23 @@ where
     }
 
     pub fn finish(&mut self) -> Result<(), EncodingError> {
+        if self.syntax == Syntax::XSampa {
+            return self.flush_xsampa(true);
+        }
         match self.state {
             EncoderState::Default => Ok(()),
-            EncoderState::Opening => Err(EncodingError::UnmatchedOpen),
-            EncoderState::Closing => Err(EncodingError::UnmatchedClose),
+            EncoderState::Opening
+            | EncoderState::Unknown
+            | EncoderState::QuoteStart
+            | EncoderState::Quoted
+            | EncoderState::QuoteEnding => Err(EncodingError::UnmatchedOpen {
+                position: self.open_position,
+                snippet: self.snippet(),
+            }),
+            EncoderState::Closing => Err(EncodingError::UnmatchedClose {
+                position: self.open_position,
+                snippet: self.snippet(),
+            }),
         }
     }
 }
@@ -191,7 +466,7 @@ where
     }
 }
 
-impl<'a, T, F> Encode<F> for &'a T
+impl<T, F> Encode<F> for &T
 where
     F: Copy,
     T: Encode<F> + ?Sized,
@@ -0,0 +1,135 @@
+use std::{
+    fmt,
+    io::{self, BufRead},
+};
+
+use thiserror::Error;
+
+use crate::{Decoder, DecodingError, Encoder, EncodingError, Table};
+
+#[derive(Debug, Error)]
+pub enum StreamError {
+    #[error("I/O error while streaming")]
+    Io(
+        #[from]
+        #[source]
+        io::Error,
+    ),
+    #[error(transparent)]
+    Encoding(#[from] EncodingError),
+    #[error(transparent)]
+    Decoding(#[from] DecodingError),
+}
+
+#[derive(Debug)]
+pub struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W> IoWriter<W>
+where
+    W: io::Write,
+{
+    pub fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn check(&mut self) -> Result<(), StreamError> {
+        match self.take_error() {
+            Some(error) => Err(StreamError::Io(error)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W> fmt::Write for IoWriter<W>
+where
+    W: io::Write,
+{
+    fn write_str(&mut self, content: &str) -> fmt::Result {
+        self.inner.write_all(content.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+pub fn encode_io<R, W>(input: R, output: W) -> Result<(), StreamError>
+where
+    R: BufRead,
+    W: io::Write,
+{
+    encode_io_with(input, output, Table::load().map_err(EncodingError::from)?)
+}
+
+pub fn encode_io_with<R, W>(
+    mut input: R,
+    output: W,
+    table: &Table,
+) -> Result<(), StreamError>
+where
+    R: BufRead,
+    W: io::Write,
+{
+    let mut writer = IoWriter::new(output);
+    let mut encoder = Encoder::with_table(&mut writer, table);
+    let mut line = String::new();
+    while input.read_line(&mut line)? > 0 {
+        let result = encoder.push_str(&line).map(drop);
+        line.clear();
+        if let Err(error) = result {
+            encoder.target_mut().check()?;
+            Err(error)?;
+        }
+    }
+    encoder.finish()?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn decode_io<R, W>(input: R, output: W) -> Result<(), StreamError>
+where
+    R: BufRead,
+    W: io::Write,
+{
+    decode_io_with(input, output, Table::load().map_err(DecodingError::from)?)
+}
+
+pub fn decode_io_with<R, W>(
+    mut input: R,
+    output: W,
+    table: &Table,
+) -> Result<(), StreamError>
+where
+    R: BufRead,
+    W: io::Write,
+{
+    let mut writer = IoWriter::new(output);
+    let mut line = String::new();
+    {
+        let mut decoder = Decoder::with_table(&mut writer, table);
+        while input.read_line(&mut line)? > 0 {
+            let result = decoder.push_str(&line).map(drop);
+            line.clear();
+            if let Err(error) = result {
+                decoder.target_mut().check()?;
+                Err(error)?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -8,12 +8,33 @@ pub use en::{
     Encode,
     Encoder,
     EncodingError,
+    Position,
+    Suggestions,
+    Syntax,
 };
-pub use table::TableInitError;
+pub use io::{
+    decode_io,
+    decode_io_with,
+    encode_io,
+    encode_io_with,
+    IoWriter,
+    StreamError,
+};
+pub use table::{Table, TableInitError, TableLoadError, DEFAULT_TABLE_NAME};
+
+#[cfg(feature = "serde")]
+pub use self::serde::Encoded;
 
 mod table;
 mod en;
 mod de;
+mod io;
+
+#[cfg(feature = "serde")]
+pub mod serde;
+
+#[cfg(feature = "wasm")]
+mod wasm;
 
 #[cfg(test)]
 mod test;
@@ -34,12 +55,41 @@ where
     Ok(())
 }
 
+pub fn encode_with(
+    input: &str,
+    table: &Table,
+) -> Result<String, EncodingError> {
+    let mut buf = String::new();
+    let mut encoder = Encoder::with_table(&mut buf, table);
+    encoder.push_str(input)?;
+    encoder.finish()?;
+    Ok(buf)
+}
+
+pub fn encode_xsampa(input: &str) -> Result<String, EncodingError> {
+    let mut buf = String::new();
+    let mut encoder = Encoder::with_syntax(&mut buf, Syntax::XSampa)?;
+    encoder.push_str(input)?;
+    encoder.finish()?;
+    Ok(buf)
+}
+
 pub fn decode(input: &str) -> Result<String, DecodingError> {
     let mut buf = String::new();
     decode_to(input, &mut buf)?;
     Ok(buf)
 }
 
+pub fn decode_with(
+    input: &str,
+    table: &Table,
+) -> Result<String, DecodingError> {
+    let mut buf = String::new();
+    let mut decoder = Decoder::with_table(&mut buf, table);
+    decoder.push_str(input)?;
+    Ok(buf)
+}
+
 pub fn decode_to<W>(input: &str, target: W) -> Result<(), DecodingError>
 where
     W: fmt::Write,
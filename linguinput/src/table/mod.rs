@@ -1,73 +1,223 @@
 use std::{
-    collections::{hash_map, HashMap},
+    borrow::Cow,
+    collections::BTreeMap,
+    fs,
+    io,
+    path::Path,
+    str,
     sync::OnceLock,
 };
 
 use thiserror::Error;
 
-pub mod raw;
+use trie::TrieNode;
+pub use trie::{NodeId, Trie};
 
-#[derive(Debug, Clone, Copy, Error)]
+pub mod trie;
+
+include!(concat!(env!("OUT_DIR"), "/tries.rs"));
+
+pub const DEFAULT_TABLE_NAME: &str = "default";
+
+static TABLE: OnceLock<Table> = OnceLock::new();
+
+#[derive(Debug, Clone, Error)]
 pub enum TableInitError {
     #[error("Duplicated character code {} in table", .0)]
-    DuplicatedCode(&'static str),
+    DuplicatedCode(String),
     #[error("Duplicated character {} in table", .0)]
-    DuplicatedChar(&'static str),
+    DuplicatedChar(String),
+    #[error("Unknown table {}", .0)]
+    UnknownTable(String),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Error)]
+pub enum TableLoadError {
+    #[error("Failed to read table {}", .0)]
+    Io(String, #[source] io::Error),
+    #[error("Failed to parse table {}", .0)]
+    Csv(String, #[source] csv::Error),
+    #[error("Failed to parse table {}", .0)]
+    Toml(String, #[source] toml::de::Error),
+    #[error("Table {} line {} must have a code and a character", .0, .1)]
+    MalformedRow(String, u64),
+    #[error("Table {} has unsupported extension, use .toml, .csv or .tsv", .0)]
+    UnsupportedFormat(String),
+    #[error("Conflicting entries in table {}", .0)]
+    Conflict(String, #[source] TableInitError),
+    #[error("A table named {} was already registered", .0)]
+    AlreadyRegistered(String),
+}
+
+#[derive(Debug, Clone)]
 pub struct Table {
-    max_code_len: usize,
-    code_to_char: HashMap<&'static str, &'static str>,
-    char_to_code: HashMap<&'static str, &'static str>,
+    codes: Trie,
+    chars: Trie,
     _priv: (),
 }
 
 impl Table {
-    pub fn max_code_len(&self) -> usize {
-        self.max_code_len
+    pub fn codes(&self) -> &Trie {
+        &self.codes
     }
 
-    pub fn code_to_char(&self, input: &str) -> Option<&'static str> {
-        self.code_to_char.get(input).copied()
+    pub fn chars(&self) -> &Trie {
+        &self.chars
     }
 
-    pub fn char_to_code(&self, input: &'static str) -> Option<&'static str> {
-        self.char_to_code.get(&input).copied()
+    pub fn code_to_char(&self, input: &str) -> Option<&str> {
+        self.codes.get(input)
     }
 
-    pub fn load() -> Result<&'static Self, TableInitError> {
-        static TABLE: OnceLock<Result<Table, TableInitError>> = OnceLock::new();
-        TABLE
-            .get_or_init(|| {
-                let mut table = Table {
-                    max_code_len: 0,
-                    code_to_char: HashMap::new(),
-                    char_to_code: HashMap::new(),
-                    _priv: (),
-                };
-                for (code, ch) in raw::TABLE {
-                    table.max_code_len = table.max_code_len.max(code.len());
-                    match table.code_to_char.entry(code) {
-                        hash_map::Entry::Occupied(_) => {
-                            Err(TableInitError::DuplicatedCode(code))?
-                        },
-                        hash_map::Entry::Vacant(entry) => {
-                            entry.insert(*ch);
-                        },
-                    }
-                    match table.char_to_code.entry(*ch) {
-                        hash_map::Entry::Occupied(_) => {
-                            Err(TableInitError::DuplicatedChar(*ch))?
-                        },
-                        hash_map::Entry::Vacant(entry) => {
-                            entry.insert(code);
-                        },
-                    }
+    pub fn char_to_code(&self, input: &str) -> Option<&str> {
+        self.chars.get(input)
+    }
+
+    pub fn suggest(&self, code: &str, limit: usize) -> Vec<String> {
+        let max_distance = (code.chars().count() / 2).max(1);
+        let mut candidates: Vec<_> = self
+            .codes
+            .keys()
+            .filter_map(|candidate| {
+                let distance = edit_distance(code, &candidate);
+                let shares_prefix = !code.is_empty()
+                    && candidate.len() > code.len()
+                    && candidate.starts_with(code);
+                if distance <= max_distance || shares_prefix {
+                    Some((distance, candidate))
+                } else {
+                    None
                 }
-                Ok(table)
             })
-            .as_ref()
-            .map_err(|err| *err)
+            .collect();
+        candidates.sort();
+        candidates.into_iter().take(limit).map(|(_, code)| code).collect()
+    }
+
+    pub fn load() -> Result<&'static Self, TableInitError> {
+        Ok(TABLE.get_or_init(Self::builtin))
+    }
+
+    // The builtin table merged with the codes in `path`. It is not shared,
+    // so the caller hands it to the encoders that should use it.
+    pub fn load_with(path: &Path) -> Result<Self, TableLoadError> {
+        Self::builtin().with_file(path)
+    }
+
+    pub fn empty() -> Self {
+        Table { codes: Trie::new(), chars: Trie::new(), _priv: () }
+    }
+
+    pub fn builtin() -> Self {
+        Table {
+            codes: BUILTIN_CODES.clone(),
+            chars: BUILTIN_CHARS.clone(),
+            _priv: (),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, TableLoadError> {
+        Self::empty().with_file(path)
+    }
+
+    pub fn with_file(self, path: &Path) -> Result<Self, TableLoadError> {
+        let source = fs::read(path).map_err(|error| {
+            TableLoadError::Io(path.display().to_string(), error)
+        })?;
+        self.with_source(path, &source)
+    }
+
+    // The path picks the format and names the table in errors, but the
+    // entries come from `source`.
+    pub fn with_source(
+        mut self,
+        path: &Path,
+        source: &[u8],
+    ) -> Result<Self, TableLoadError> {
+        let display = path.display().to_string();
+        for (code, ch) in read_entries(path, source)? {
+            self.insert(code, ch).map_err(|error| {
+                TableLoadError::Conflict(display.clone(), error)
+            })?;
+        }
+        Ok(self)
+    }
+
+    pub fn insert(
+        &mut self,
+        code: impl Into<Cow<'static, str>>,
+        ch: impl Into<Cow<'static, str>>,
+    ) -> Result<(), TableInitError> {
+        let code = code.into();
+        let ch = ch.into();
+        if self.codes.get(&code).is_some() {
+            return Err(TableInitError::DuplicatedCode(code.into_owned()));
+        }
+        if self.chars.get(&ch).is_some() {
+            return Err(TableInitError::DuplicatedChar(ch.into_owned()));
+        }
+        self.codes
+            .insert(&code, ch.clone())
+            .map_err(TableInitError::DuplicatedCode)?;
+        self.chars.insert(&ch, code).map_err(TableInitError::DuplicatedChar)?;
+        Ok(())
+    }
+}
+
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<_> = right.chars().collect();
+    let mut previous: Vec<_> = (0 ..= right.len()).collect();
+    let mut current = vec![0; right.len() + 1];
+    for (i, left_ch) in left.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, right_ch) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left_ch != *right_ch);
+            current[j + 1] =
+                substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[right.len()]
+}
+
+fn read_entries(
+    path: &Path,
+    source: &[u8],
+) -> Result<Vec<(String, String)>, TableLoadError> {
+    let display = path.display().to_string();
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    match extension {
+        Some("toml") => {
+            let code = str::from_utf8(source).map_err(|error| {
+                TableLoadError::Io(
+                    display.clone(),
+                    io::Error::new(io::ErrorKind::InvalidData, error),
+                )
+            })?;
+            let entries: BTreeMap<String, String> = toml::from_str(code)
+                .map_err(|error| TableLoadError::Toml(display, error))?;
+            Ok(entries.into_iter().collect())
+        },
+        Some(ext @ ("csv" | "tsv")) => {
+            let mut reader = csv::ReaderBuilder::new()
+                .delimiter(if ext == "tsv" { b'\t' } else { b',' })
+                .has_headers(false)
+                .from_reader(source);
+            let mut entries = Vec::new();
+            for result in reader.records() {
+                let record = result.map_err(|error| {
+                    TableLoadError::Csv(display.clone(), error)
+                })?;
+                let line = record.position().map_or(0, |pos| pos.line());
+                let (Some(code), Some(ch), None) =
+                    (record.get(0), record.get(1), record.get(2))
+                else {
+                    Err(TableLoadError::MalformedRow(display.clone(), line))?
+                };
+                entries.push((code.to_owned(), ch.to_owned()));
+            }
+            Ok(entries)
+        },
+        _ => Err(TableLoadError::UnsupportedFormat(display)),
     }
 }
@@ -1,10 +1,11 @@
 type Code = &'static str;
 type Char = &'static str;
 
-pub const TABLE: &'static [(Code, Char)] = &[
+pub const TABLE: &[(Code, Char)] = &[
     /* Signs and Punctuation */
     ("<", "⟨"),
     (">", "⟩"),
+    ("//", "⫽"),
     /* Generic Notational */
     ("_ 0", "₀"),
     ("_ 1", "₁"),
@@ -153,15 +154,19 @@ pub const TABLE: &'static [(Code, Char)] = &[
     ("hs", "ɧ"),
     ("L", "ʟ"),
     ("G", "ɢ"),
+    ("gs", "ɡ"),
     ("N", "ɴ"),
     ("R", "ʀ"),
+    ("B", "ʙ"),
     ("Rh", "ʁ"),
     ("?", "ʔ"),
     ("??", "ʕ"),
     ("d'", "ɗ"),
+    ("b'", "ɓ"),
     ("dr'", "ᶑ"),
     ("g'", "ɠ"),
     ("G'", "ʛ"),
+    ("q'", "ʠ"),
     ("J'", "ʄ"),
     ("h", "ɦ"),
     ("ha", "ħ"),
@@ -235,6 +240,8 @@ pub const TABLE: &'static [(Code, Char)] = &[
     ("^vw", "ᶹ"),
     ("^oe", "ꟹ"),
     ("^l", "ˡ"),
+    ("^n", "ⁿ"),
+    ("^ea", "ᵊ"),
     ("^-b", "ᵝ"),
     /* IPA Tone */
     ("#1", "\u{30f}"),
@@ -249,4 +256,7 @@ pub const TABLE: &'static [(Code, Char)] = &[
     ("#242", "\u{1dc8}"),
     /* IPA Entonation */
     ("||", "‖"),
+    ("__", "‿"),
+    ("|/", "↗"),
+    ("|\\", "↘"),
 ];
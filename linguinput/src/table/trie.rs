@@ -0,0 +1,138 @@
+use std::borrow::Cow;
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone)]
+pub struct TrieNode {
+    parent: NodeId,
+    label: char,
+    value: Option<Cow<'static, str>>,
+    edges: Cow<'static, [(char, NodeId)]>,
+}
+
+impl TrieNode {
+    pub const fn new_static(
+        parent: NodeId,
+        label: char,
+        value: Option<&'static str>,
+        edges: &'static [(char, NodeId)],
+    ) -> Self {
+        let value = match value {
+            Some(value) => Some(Cow::Borrowed(value)),
+            None => None,
+        };
+        Self { parent, label, value, edges: Cow::Borrowed(edges) }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Trie {
+    nodes: Cow<'static, [TrieNode]>,
+}
+
+impl Trie {
+    pub const ROOT: NodeId = 0;
+
+    pub fn new() -> Self {
+        Self {
+            nodes: Cow::Owned(vec![TrieNode::new_static(
+                Self::ROOT,
+                '\0',
+                None,
+                &[],
+            )]),
+        }
+    }
+
+    pub const fn from_static(nodes: &'static [TrieNode]) -> Self {
+        Self { nodes: Cow::Borrowed(nodes) }
+    }
+
+    pub fn step(&self, node: NodeId, ch: char) -> Option<NodeId> {
+        let edges = &self.nodes[node].edges;
+        let index =
+            edges.binary_search_by_key(&ch, |(label, _)| *label).ok()?;
+        Some(edges[index].1)
+    }
+
+    pub fn walk(&self, node: NodeId, input: &str) -> Option<NodeId> {
+        input.chars().try_fold(node, |node, ch| self.step(node, ch))
+    }
+
+    pub fn value(&self, node: NodeId) -> Option<&str> {
+        self.nodes[node].value.as_deref()
+    }
+
+    pub fn is_leaf(&self, node: NodeId) -> bool {
+        self.nodes[node].edges.is_empty()
+    }
+
+    pub fn key(&self, mut node: NodeId) -> String {
+        let mut key = Vec::new();
+        while node != Self::ROOT {
+            key.push(self.nodes[node].label);
+            node = self.nodes[node].parent;
+        }
+        key.into_iter().rev().collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.value(self.walk(Self::ROOT, key)?)
+    }
+
+    pub fn longest_prefix(&self, input: &str) -> Option<(usize, &str)> {
+        let mut node = Self::ROOT;
+        let mut longest = None;
+        for (i, ch) in input.char_indices() {
+            let Some(next) = self.step(node, ch) else { break };
+            node = next;
+            if let Some(value) = self.value(node) {
+                longest = Some((i + ch.len_utf8(), value));
+            }
+        }
+        longest
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = String> + '_ {
+        (0 .. self.nodes.len())
+            .filter(|&node| self.value(node).is_some())
+            .map(|node| self.key(node))
+    }
+
+    pub fn insert(
+        &mut self,
+        key: &str,
+        value: impl Into<Cow<'static, str>>,
+    ) -> Result<(), String> {
+        let mut node = Self::ROOT;
+        for ch in key.chars() {
+            node = match self.step(node, ch) {
+                Some(next) => next,
+                None => {
+                    let next = self.nodes.len();
+                    let nodes = self.nodes.to_mut();
+                    nodes.push(TrieNode::new_static(node, ch, None, &[]));
+                    let edges = nodes[node].edges.to_mut();
+                    let index = edges
+                        .binary_search_by_key(&ch, |(label, _)| *label)
+                        .unwrap_or_else(|index| index);
+                    edges.insert(index, (ch, next));
+                    next
+                },
+            };
+        }
+        match &mut self.nodes.to_mut()[node].value {
+            Some(_) => Err(key.to_owned()),
+            slot @ None => {
+                *slot = Some(value.into());
+                Ok(())
+            },
+        }
+    }
+}
+
+impl Default for Trie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
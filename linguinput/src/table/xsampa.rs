@@ -0,0 +1,149 @@
+type Code = &'static str;
+type Char = &'static str;
+
+pub const TABLE: &[(Code, Char)] = &[
+    /* Letters */
+    ("b_<", "ɓ"),
+    ("d`", "ɖ"),
+    ("d_<", "ɗ"),
+    ("g", "ɡ"),
+    ("g_<", "ɠ"),
+    ("h\\", "ɦ"),
+    ("j\\", "ʝ"),
+    ("l`", "ɭ"),
+    ("l\\", "ɺ"),
+    ("n`", "ɳ"),
+    ("p\\", "ɸ"),
+    ("r`", "ɽ"),
+    ("r\\", "ɹ"),
+    ("r\\`", "ɻ"),
+    ("s`", "ʂ"),
+    ("s\\", "ɕ"),
+    ("t`", "ʈ"),
+    ("v\\", "ʋ"),
+    ("x\\", "ɧ"),
+    ("z`", "ʐ"),
+    ("z\\", "ʑ"),
+    ("A", "ɑ"),
+    ("B", "β"),
+    ("B\\", "ʙ"),
+    ("C", "ç"),
+    ("D", "ð"),
+    ("E", "ɛ"),
+    ("F", "ɱ"),
+    ("G", "ɣ"),
+    ("G\\", "ɢ"),
+    ("G\\_<", "ʛ"),
+    ("H", "ɥ"),
+    ("H\\", "ʜ"),
+    ("I", "ɪ"),
+    ("I\\", "ᵻ"),
+    ("J", "ɲ"),
+    ("J\\", "ɟ"),
+    ("J\\_<", "ʄ"),
+    ("K", "ɬ"),
+    ("K\\", "ɮ"),
+    ("L", "ʎ"),
+    ("L\\", "ʟ"),
+    ("M", "ɯ"),
+    ("M\\", "ɰ"),
+    ("N", "ŋ"),
+    ("N\\", "ɴ"),
+    ("O", "ɔ"),
+    ("O\\", "ʘ"),
+    ("P", "ʋ"),
+    ("Q", "ɒ"),
+    ("R", "ʁ"),
+    ("R\\", "ʀ"),
+    ("S", "ʃ"),
+    ("T", "θ"),
+    ("U", "ʊ"),
+    ("U\\", "ᵿ"),
+    ("V", "ʌ"),
+    ("W", "ʍ"),
+    ("X", "χ"),
+    ("X\\", "ħ"),
+    ("Y", "ʏ"),
+    ("Z", "ʒ"),
+    ("\"", "ˈ"),
+    ("%", "ˌ"),
+    ("'", "ʲ"),
+    (":", "ː"),
+    (":\\", "ˑ"),
+    ("@", "ə"),
+    ("@\\", "ɘ"),
+    ("@`", "ɚ"),
+    ("{", "æ"),
+    ("}", "ʉ"),
+    ("1", "ɨ"),
+    ("2", "ø"),
+    ("3", "ɜ"),
+    ("3\\", "ɞ"),
+    ("4", "ɾ"),
+    ("5", "ɫ"),
+    ("6", "ɐ"),
+    ("7", "ɤ"),
+    ("8", "ɵ"),
+    ("9", "œ"),
+    ("&", "ɶ"),
+    ("?", "ʔ"),
+    ("?\\", "ʕ"),
+    ("<\\", "ʢ"),
+    (">\\", "ʡ"),
+    ("^", "ꜛ"),
+    ("!", "ꜜ"),
+    ("!\\", "ǃ"),
+    ("|\\", "ǀ"),
+    ("||", "‖"),
+    ("|\\|\\", "ǁ"),
+    ("=\\", "ǂ"),
+    ("-\\", "‿"),
+    /* Diacritics and Suprasegmentals */
+    ("_\"", "\u{308}"),
+    ("_+", "\u{31f}"),
+    ("_-", "\u{320}"),
+    ("_/", "\u{30c}"),
+    ("_0", "\u{325}"),
+    ("=", "\u{329}"),
+    ("_=", "\u{329}"),
+    ("_>", "ʼ"),
+    ("_?\\", "ˤ"),
+    ("_\\", "\u{302}"),
+    ("_^", "\u{32f}"),
+    ("_}", "\u{31a}"),
+    ("`", "˞"),
+    ("~", "\u{303}"),
+    ("_~", "\u{303}"),
+    ("_A", "\u{318}"),
+    ("_a", "\u{33a}"),
+    ("_B", "\u{30f}"),
+    ("_B_L", "\u{1dc5}"),
+    ("_c", "\u{31c}"),
+    ("_d", "\u{32a}"),
+    ("_e", "\u{334}"),
+    ("_F", "\u{302}"),
+    ("_G", "ˠ"),
+    ("_H", "\u{301}"),
+    ("_H_T", "\u{1dc4}"),
+    ("_h", "ʰ"),
+    ("_j", "ʲ"),
+    ("_k", "\u{330}"),
+    ("_L", "\u{300}"),
+    ("_l", "ˡ"),
+    ("_M", "\u{304}"),
+    ("_m", "\u{33b}"),
+    ("_N", "\u{33c}"),
+    ("_n", "ⁿ"),
+    ("_O", "\u{339}"),
+    ("_o", "\u{31e}"),
+    ("_q", "\u{319}"),
+    ("_R", "\u{30c}"),
+    ("_R_F", "\u{1dc8}"),
+    ("_r", "\u{31d}"),
+    ("_T", "\u{30b}"),
+    ("_t", "\u{324}"),
+    ("_v", "\u{32c}"),
+    ("_w", "ʷ"),
+    ("_X", "\u{306}"),
+    ("_x", "\u{33d}"),
+];
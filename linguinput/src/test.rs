@@ -1,4 +1,26 @@
-use crate::encode;
+use proptest::prelude::*;
+
+use crate::{
+    decode,
+    decode_io,
+    decode_with,
+    encode,
+    encode_io,
+    encode_with,
+    encode_xsampa,
+    EncodingError,
+    Position,
+    Table,
+};
+
+fn code_token() -> impl Strategy<Value = String> {
+    let codes: Vec<_> = Table::builtin().codes().keys().collect();
+    prop::sample::select(codes).prop_map(|code| format!("{{{code}}}"))
+}
+
+fn plain_token() -> impl Strategy<Value = String> {
+    prop_oneof!["[a-zA-Z0-9 .]", "\\{'\\{'\\}[a-z]", Just("}}".to_owned())]
+}
 
 #[test]
 fn no_code() {
@@ -15,3 +37,149 @@ fn simple_hello() {
     let actual = encode(input).unwrap();
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn simple_hello_decode() {
+    let input = "hɛl.oʊ ˦˨˧˨ {x}";
+    let expected = "h{e}l.o{U} {4}{2}{3}{2} {'{'}x}}";
+    let actual = decode(input).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn decode_combining() {
+    let input = "ɛ̃ːŋ̊";
+    let expected = "{e}{#~}{:}{n}{#^0}";
+    let actual = decode(input).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn builtin_round_trip() {
+    let table = Table::builtin();
+    for code in table.codes().keys() {
+        let ch = table.code_to_char(&code).unwrap();
+        let encoded = encode(&format!("{{{code}}}")).unwrap();
+        assert_eq!(encoded, ch, "encoding code {code:?}");
+        let decoded = decode(ch).unwrap();
+        assert_eq!(decoded, format!("{{{code}}}"), "decoding char {ch:?}");
+    }
+}
+
+proptest! {
+    #[test]
+    fn encode_decode_round_trip(input in "\\PC*") {
+        let decoded = decode(&input).unwrap();
+        prop_assert_eq!(encode(&decoded).unwrap(), input);
+    }
+
+    #[test]
+    fn decode_encode_round_trip(
+        tokens in prop::collection::vec(
+            prop_oneof![code_token(), plain_token()],
+            0 .. 32,
+        ),
+    ) {
+        let input = tokens.concat();
+        let encoded = encode(&input).unwrap();
+        prop_assert_eq!(decode(&encoded).unwrap(), input);
+    }
+}
+
+#[test]
+fn custom_table() {
+    let mut table = Table::empty();
+    table.insert("th", "ᚦ").unwrap();
+    table.insert("ng", "ᛜ").unwrap();
+    let input = "{th}i{ng}";
+    let expected = "ᚦiᛜ";
+    let actual = encode_with(input, &table).unwrap();
+    assert_eq!(actual, expected);
+    assert_eq!(decode_with(&actual, &table).unwrap(), input);
+}
+
+#[test]
+fn table_conflict() {
+    let mut table = Table::empty();
+    table.insert("a", "ᚨ").unwrap();
+    assert!(table.insert("a", "ᚫ").is_err());
+    assert!(table.insert("aa", "ᚨ").is_err());
+}
+
+#[test]
+fn io_round_trip() {
+    let input = "h{e}l.o{U}\n{4}{2}\n{3}{2}\n";
+    let mut encoded = Vec::new();
+    encode_io(input.as_bytes(), &mut encoded).unwrap();
+    assert_eq!(String::from_utf8(encoded.clone()).unwrap(), "hɛl.oʊ\n˦˨\n˧˨\n");
+    let mut decoded = Vec::new();
+    decode_io(&encoded[..], &mut decoded).unwrap();
+    assert_eq!(String::from_utf8(decoded).unwrap(), input);
+}
+
+#[test]
+fn unknown_code_position() {
+    let input = "h{e}l\no{U} {zz}o";
+    let error = encode(input).unwrap_err();
+    let EncodingError::UnknownCode { code, position, snippet, .. } = error
+    else {
+        panic!("unexpected error {error:?}")
+    };
+    assert_eq!(code, "zz");
+    assert_eq!(position, Position { line: 2, column: 6 });
+    assert_eq!(snippet, input[.. input.len() - 1]);
+}
+
+#[test]
+fn long_unknown_code_is_cut_to_the_snippet() {
+    let code = "q".repeat(40);
+    let error = encode(&format!("a{{{code}}}")).unwrap_err();
+    let EncodingError::UnknownCode { code, snippet, .. } = error else {
+        panic!("unexpected error {error:?}")
+    };
+    assert_eq!(code, format!("…{}", "q".repeat(23)));
+    assert_eq!(snippet, format!("{}}}", "q".repeat(23)));
+}
+
+#[test]
+fn unknown_code_suggestions() {
+    let error = encode("{aee}").unwrap_err();
+    let EncodingError::UnknownCode { suggestions, .. } = error else {
+        panic!("unexpected error {error:?}")
+    };
+    assert!(suggestions.0.iter().any(|code| code == "ae"));
+    assert!(suggestions.to_string().starts_with("; did you mean {"));
+}
+
+#[test]
+fn quoted_literal() {
+    let input = "{'{x}'}{'}{''}{'it's'}{{y}}";
+    let expected = "{x}ˈit's{y}";
+    let actual = encode(input).unwrap();
+    assert_eq!(actual, expected);
+    assert_eq!(decode(&actual).unwrap(), "{'{'}x}}{'}it's{'{'}y}}");
+}
+
+#[test]
+fn xsampa() {
+    let input = "h\\El@U r\\`a:t_h b_<A_~ G\\_<i";
+    let expected = "ɦɛləʊ ɻaːtʰ ɓɑ̃ ʛi";
+    let actual = encode_xsampa(input).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_encoded() {
+    use crate::Encoded;
+
+    let input = toml::Value::String("h{e}l.o{U}".to_owned());
+    let encoded: Encoded = input.try_into().unwrap();
+    assert_eq!(encoded.as_str(), "hɛl.oʊ");
+    let output = toml::Value::try_from(&encoded).unwrap();
+    assert_eq!(output.as_str(), Some("hɛl.oʊ"));
+    let input = toml::Value::String("{nope}".to_owned());
+    assert!(input.try_into::<Encoded>().is_err());
+    let input = toml::Value::String("{4}{2}".to_owned());
+    assert_eq!(crate::serde::deserialize(input).unwrap(), "˦˨");
+}
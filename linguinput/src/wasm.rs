@@ -0,0 +1,19 @@
+use wasm_bindgen::prelude::*;
+
+// The manifest only declares an rlib so native builds skip the cdylib. Build
+// the module with `cargo rustc -p lin-ssg-linguinput --lib --release --target
+// wasm32-unknown-unknown --features wasm --crate-type cdylib`, then run
+// `wasm-bindgen --target web` on the resulting `.wasm`.
+//
+// Only the default table is available, matching the encoder used at build
+// time by sites that do not configure their own.
+
+#[wasm_bindgen(js_name = encode)]
+pub fn encode_js(input: &str) -> Result<String, JsError> {
+    Ok(crate::encode(input)?)
+}
+
+#[wasm_bindgen(js_name = decode)]
+pub fn decode_js(input: &str) -> Result<String, JsError> {
+    Ok(crate::decode(input)?)
+}
@@ -0,0 +1,449 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error)]
+pub enum BibSyntaxError {
+    #[error("Line {}: expected {}", .0, .1)]
+    Expected(usize, &'static str),
+    #[error("Line {}: unterminated value", .0)]
+    UnterminatedValue(usize),
+    #[error("Entry {} is defined twice", .0)]
+    DuplicatedKey(String),
+    #[error("Invalid CSL-JSON: {}", .0)]
+    Csl(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    Article,
+    Book,
+    Chapter,
+    Thesis,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Name {
+    pub family: String,
+    pub given: Option<String>,
+}
+
+impl Name {
+    fn parse(name: &str) -> Self {
+        let name = name.trim();
+        if let Some((family, given)) = name.split_once(',') {
+            return Self {
+                family: family.trim().to_owned(),
+                given: Some(given.trim().to_owned())
+                    .filter(|given| !given.is_empty()),
+            };
+        }
+        match name.rsplit_once(char::is_whitespace) {
+            Some((given, family)) => Self {
+                family: family.to_owned(),
+                given: Some(given.trim().to_owned()),
+            },
+            None => Self { family: name.to_owned(), given: None },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub key: String,
+    pub kind: Kind,
+    pub authors: Vec<Name>,
+    pub editors: Vec<Name>,
+    pub year: Option<String>,
+    pub title: Option<String>,
+    pub container: Option<String>,
+    pub volume: Option<String>,
+    pub number: Option<String>,
+    pub pages: Option<String>,
+    pub publisher: Option<String>,
+    pub address: Option<String>,
+    pub doi: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bibliography {
+    references: HashMap<String, Reference>,
+}
+
+impl Bibliography {
+    pub fn parse_bibtex(code: &str) -> Result<Self, BibSyntaxError> {
+        let mut parser = BibtexParser::new(code);
+        let mut this = Self::default();
+        while let Some(reference) = parser.next_entry()? {
+            this.insert(reference)?;
+        }
+        Ok(this)
+    }
+
+    pub fn parse_csl_json(code: &str) -> Result<Self, BibSyntaxError> {
+        let json: Value = serde_json::from_str(code)
+            .map_err(|error| BibSyntaxError::Csl(error.to_string()))?;
+        let Value::Array(items) = json else {
+            Err(BibSyntaxError::Csl("expected an array of items".to_owned()))?
+        };
+        let mut this = Self::default();
+        for item in &items {
+            this.insert(csl_reference(item)?)?;
+        }
+        Ok(this)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Reference> {
+        self.references.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.references.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+
+    fn insert(&mut self, reference: Reference) -> Result<(), BibSyntaxError> {
+        if self.references.contains_key(&reference.key) {
+            Err(BibSyntaxError::DuplicatedKey(reference.key.clone()))?
+        }
+        self.references.insert(reference.key.clone(), reference);
+        Ok(())
+    }
+}
+
+struct BibtexParser<'a> {
+    code: &'a str,
+    pos: usize,
+    macros: HashMap<String, String>,
+}
+
+impl<'a> BibtexParser<'a> {
+    fn new(code: &'a str) -> Self {
+        let macros = [
+            "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep",
+            "oct", "nov", "dec",
+        ]
+        .into_iter()
+        .map(|month| (month.to_owned(), month.to_owned()))
+        .collect();
+        Self { code, pos: 0, macros }
+    }
+
+    fn line(&self) -> usize {
+        self.code[.. self.pos].matches('\n').count() + 1
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.code[self.pos ..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(ch) {
+            self.pos += ch.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(
+        &mut self,
+        ch: char,
+        what: &'static str,
+    ) -> Result<(), BibSyntaxError> {
+        if self.eat(ch) {
+            Ok(())
+        } else {
+            Err(BibSyntaxError::Expected(self.line(), what))
+        }
+    }
+
+    fn identifier(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|ch: char| {
+                ch.is_whitespace()
+                    || matches!(ch, '{' | '}' | '(' | ')')
+                    || matches!(ch, ',' | '=' | '#' | '"')
+            })
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[.. len]
+    }
+
+    fn next_entry(&mut self) -> Result<Option<Reference>, BibSyntaxError> {
+        loop {
+            let Some(start) = self.rest().find('@') else { return Ok(None) };
+            self.pos += start + 1;
+            let kind = self.identifier().to_lowercase();
+            let close = if self.eat('{') {
+                '}'
+            } else if self.eat('(') {
+                ')'
+            } else {
+                Err(BibSyntaxError::Expected(
+                    self.line(),
+                    "'{' after entry type",
+                ))?
+            };
+            match &kind[..] {
+                "comment" | "preamble" if close == '}' => {
+                    self.pos -= 1;
+                    self.braced_value()?;
+                },
+                "comment" | "preamble" => {
+                    let Some(end) = self.rest().find(close) else {
+                        Err(BibSyntaxError::UnterminatedValue(self.line()))?
+                    };
+                    self.pos += end + 1;
+                },
+                "string" => {
+                    let name = self.identifier().to_lowercase();
+                    self.expect('=', "'=' in @string")?;
+                    let value = self.value()?;
+                    self.macros.insert(name, value);
+                    self.expect(close, "end of @string")?;
+                },
+                _ => return self.entry(&kind, close).map(Some),
+            }
+        }
+    }
+
+    fn entry(
+        &mut self,
+        kind: &str,
+        close: char,
+    ) -> Result<Reference, BibSyntaxError> {
+        let key = self.identifier().to_owned();
+        if key.is_empty() {
+            Err(BibSyntaxError::Expected(self.line(), "citation key"))?
+        }
+        let mut fields = HashMap::new();
+        while self.eat(',') {
+            if self.eat(close) {
+                return Ok(bibtex_reference(key, kind, fields));
+            }
+            let name = self.identifier().to_lowercase();
+            if name.is_empty() {
+                Err(BibSyntaxError::Expected(self.line(), "field name"))?
+            }
+            self.expect('=', "'=' after field name")?;
+            fields.insert(name, self.value()?);
+        }
+        self.expect(close, "end of entry")?;
+        Ok(bibtex_reference(key, kind, fields))
+    }
+
+    fn value(&mut self) -> Result<String, BibSyntaxError> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with('{') {
+                value.push_str(&self.braced_value()?);
+            } else if self.eat('"') {
+                value.push_str(&self.quoted_value()?);
+            } else {
+                let word = self.identifier();
+                if word.is_empty() {
+                    Err(BibSyntaxError::Expected(self.line(), "field value"))?
+                }
+                match self.macros.get(&word.to_lowercase()) {
+                    Some(expansion) => value.push_str(expansion),
+                    None => value.push_str(word),
+                }
+            }
+            if !self.eat('#') {
+                break Ok(value);
+            }
+        }
+    }
+
+    fn braced_value(&mut self) -> Result<String, BibSyntaxError> {
+        let line = self.line();
+        let rest = self.rest();
+        let mut depth = 0usize;
+        for (i, ch) in rest.char_indices() {
+            match ch {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.pos += i + 1;
+                        return Ok(rest[1 .. i].to_owned());
+                    }
+                },
+                _ => (),
+            }
+        }
+        Err(BibSyntaxError::UnterminatedValue(line))
+    }
+
+    fn quoted_value(&mut self) -> Result<String, BibSyntaxError> {
+        let line = self.line();
+        let rest = self.rest();
+        let mut depth = 0usize;
+        for (i, ch) in rest.char_indices() {
+            match ch {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                '"' if depth == 0 => {
+                    self.pos += i + 1;
+                    return Ok(rest[.. i].to_owned());
+                },
+                _ => (),
+            }
+        }
+        Err(BibSyntaxError::UnterminatedValue(line))
+    }
+}
+
+fn bibtex_reference(
+    key: String,
+    kind: &str,
+    mut fields: HashMap<String, String>,
+) -> Reference {
+    let kind = match kind {
+        "article" => Kind::Article,
+        "book" => Kind::Book,
+        "incollection" | "inbook" | "inproceedings" => Kind::Chapter,
+        "phdthesis" | "mastersthesis" | "thesis" => Kind::Thesis,
+        _ => Kind::Other,
+    };
+    let mut take =
+        |name: &str| fields.remove(name).map(|value| delatex(&value));
+    let names = |value: Option<String>| {
+        value.map_or_else(Vec::new, |value| {
+            value.split(" and ").map(Name::parse).collect()
+        })
+    };
+    let authors = names(take("author"));
+    let editors = names(take("editor"));
+    let container = take("journal").or_else(|| take("booktitle"));
+    let publisher = take("publisher")
+        .or_else(|| take("school"))
+        .or_else(|| take("institution"));
+    Reference {
+        key,
+        kind,
+        authors,
+        editors,
+        year: take("year"),
+        title: take("title"),
+        container,
+        volume: take("volume"),
+        number: take("number"),
+        pages: take("pages").map(|pages| pages.replace("--", "–")),
+        publisher,
+        address: take("address"),
+        doi: take("doi"),
+        url: take("url"),
+    }
+}
+
+fn delatex(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' | '}' => (),
+            '\\' => match chars.peek() {
+                Some(&escaped @ ('&' | '%' | '_' | '$' | '#' | '{' | '}')) => {
+                    output.push(escaped);
+                    chars.next();
+                },
+                _ => output.push(ch),
+            },
+            '~' => output.push('\u{a0}'),
+            ch if ch.is_whitespace() => {
+                if !output.ends_with(' ') {
+                    output.push(' ');
+                }
+            },
+            _ => output.push(ch),
+        }
+    }
+    output.trim().to_owned()
+}
+
+fn csl_reference(item: &Value) -> Result<Reference, BibSyntaxError> {
+    let string = |name: &str| match item.get(name)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    };
+    let names = |name: &str| {
+        item.get(name).and_then(Value::as_array).map_or_else(
+            Vec::new,
+            |names| {
+                names
+                    .iter()
+                    .filter_map(|name| {
+                        if let Some(literal) = name.get("literal") {
+                            return Some(Name {
+                                family: literal.as_str()?.to_owned(),
+                                given: None,
+                            });
+                        }
+                        Some(Name {
+                            family: name.get("family")?.as_str()?.to_owned(),
+                            given: name
+                                .get("given")
+                                .and_then(Value::as_str)
+                                .map(ToOwned::to_owned),
+                        })
+                    })
+                    .collect()
+            },
+        )
+    };
+    let Some(key) = string("id") else {
+        Err(BibSyntaxError::Csl("item without id".to_owned()))?
+    };
+    let kind = match string("type").as_deref() {
+        Some("article-journal" | "article-magazine" | "article-newspaper") => {
+            Kind::Article
+        },
+        Some("book") => Kind::Book,
+        Some("chapter" | "paper-conference" | "entry-encyclopedia") => {
+            Kind::Chapter
+        },
+        Some("thesis") => Kind::Thesis,
+        _ => Kind::Other,
+    };
+    let year = item
+        .pointer("/issued/date-parts/0/0")
+        .map(|year| match year {
+            Value::String(year) => year.clone(),
+            year => year.to_string(),
+        })
+        .or_else(|| string("year"));
+    Ok(Reference {
+        key,
+        kind,
+        authors: names("author"),
+        editors: names("editor"),
+        year,
+        title: string("title"),
+        container: string("container-title"),
+        volume: string("volume"),
+        number: string("issue"),
+        pages: string("page").map(|pages| pages.replace('-', "–")),
+        publisher: string("publisher"),
+        address: string("publisher-place"),
+        doi: string("DOI"),
+        url: string("URL"),
+    })
+}
@@ -0,0 +1,192 @@
+use std::fmt::{self, Write as _};
+
+use lin_ssg_core::escape_html;
+
+use crate::bib::{Kind, Name, Reference};
+
+pub const ANCHOR_PREFIX: &str = "ref-";
+
+pub fn anchor(key: &str) -> String {
+    format!("{ANCHOR_PREFIX}{}", escape_html(key))
+}
+
+pub fn short_authors(reference: &Reference) -> String {
+    let names = if reference.authors.is_empty() {
+        &reference.editors
+    } else {
+        &reference.authors
+    };
+    match &names[..] {
+        [] => reference.title.clone().unwrap_or_else(|| reference.key.clone()),
+        [single] => single.family.clone(),
+        [first, second] => format!("{} & {}", first.family, second.family),
+        [first, ..] => format!("{} et al.", first.family),
+    }
+}
+
+pub fn year(reference: &Reference) -> &str {
+    reference.year.as_deref().unwrap_or("n.d.")
+}
+
+pub fn citation(
+    buf: &mut String,
+    reference: &Reference,
+    loc: Option<&str>,
+    paren: bool,
+) -> fmt::Result {
+    let authors = escape_html(&short_authors(reference));
+    let year = escape_html(year(reference));
+    let loc =
+        loc.map(|loc| format!(": {}", escape_html(loc))).unwrap_or_default();
+    write!(buf, "<a class=\"citation\" href=\"#{}\">", anchor(&reference.key))?;
+    if paren {
+        write!(buf, "({authors} {year}{loc})")?;
+    } else {
+        write!(buf, "{authors} ({year}{loc})")?;
+    }
+    write!(buf, "</a>")
+}
+
+fn full_names(
+    buf: &mut String,
+    names: &[Name],
+    invert_first: bool,
+) -> fmt::Result {
+    for (i, name) in names.iter().enumerate() {
+        if i + 1 == names.len() && i > 0 {
+            write!(buf, " & ")?;
+        } else if i > 0 {
+            write!(buf, ", ")?;
+        }
+        let family = escape_html(&name.family);
+        match &name.given {
+            Some(given) if i == 0 && invert_first => {
+                write!(buf, "{family}, {}", escape_html(given))?
+            },
+            Some(given) => write!(buf, "{} {family}", escape_html(given))?,
+            None => write!(buf, "{family}")?,
+        }
+    }
+    Ok(())
+}
+
+fn sentence(buf: &mut String, text: &str) -> fmt::Result {
+    write!(buf, "{}", escape_html(text))?;
+    if !text.ends_with(['.', '?', '!']) {
+        write!(buf, ".")?;
+    }
+    Ok(())
+}
+
+pub fn reference(buf: &mut String, reference: &Reference) -> fmt::Result {
+    write!(buf, "<li id=\"{}\" class=\"reference\">", anchor(&reference.key))?;
+    if !reference.authors.is_empty() {
+        full_names(buf, &reference.authors, true)?;
+        if !buf.ends_with('.') {
+            write!(buf, ".")?;
+        }
+        write!(buf, " ")?;
+    } else if !reference.editors.is_empty() {
+        full_names(buf, &reference.editors, true)?;
+        let ed = if reference.editors.len() == 1 { "ed." } else { "eds." };
+        write!(buf, " ({ed}). ")?;
+    }
+    write!(buf, "{}. ", escape_html(year(reference)))?;
+
+    let italic_title = matches!(reference.kind, Kind::Book | Kind::Thesis);
+    if let Some(title) = &reference.title {
+        if italic_title {
+            write!(buf, "<i>")?;
+            sentence(buf, title)?;
+            write!(buf, "</i>")?;
+        } else {
+            sentence(buf, title)?;
+        }
+    }
+
+    match reference.kind {
+        Kind::Article => {
+            if let Some(container) = &reference.container {
+                write!(buf, " <i>{}</i>", escape_html(container))?;
+            }
+            if let Some(volume) = &reference.volume {
+                write!(buf, " {}", escape_html(volume))?;
+            }
+            if let Some(number) = &reference.number {
+                write!(buf, "({})", escape_html(number))?;
+            }
+            if let Some(pages) = &reference.pages {
+                write!(buf, ". {}", escape_html(pages))?;
+            }
+            if reference.container.is_some() || reference.pages.is_some() {
+                write!(buf, ".")?;
+            }
+        },
+        Kind::Chapter => {
+            write!(buf, " In ")?;
+            if !reference.editors.is_empty() && !reference.authors.is_empty() {
+                full_names(buf, &reference.editors, false)?;
+                let ed =
+                    if reference.editors.len() == 1 { "ed." } else { "eds." };
+                write!(buf, " ({ed}), ")?;
+            }
+            if let Some(container) = &reference.container {
+                write!(buf, "<i>{}</i>", escape_html(container))?;
+            }
+            if let Some(pages) = &reference.pages {
+                write!(buf, ", {}", escape_html(pages))?;
+            }
+            write!(buf, ".")?;
+        },
+        Kind::Book | Kind::Thesis | Kind::Other => {
+            if let Some(container) = &reference.container {
+                write!(buf, " <i>{}</i>.", escape_html(container))?;
+            }
+        },
+    }
+
+    match (&reference.address, &reference.publisher) {
+        (Some(address), Some(publisher)) => write!(
+            buf,
+            " {}: {}.",
+            escape_html(address),
+            escape_html(publisher)
+        )?,
+        (None, Some(publisher)) => write!(buf, " {}.", escape_html(publisher))?,
+        (Some(address), None) => write!(buf, " {}.", escape_html(address))?,
+        (None, None) => (),
+    }
+
+    let link = match (&reference.doi, &reference.url) {
+        (Some(doi), _) => Some(format!("https://doi.org/{doi}")),
+        (None, Some(url)) => Some(url.clone()),
+        (None, None) => None,
+    };
+    if let Some(link) = link {
+        let link = escape_html(&link);
+        write!(buf, " <a href=\"{link}\">{link}</a>")?;
+    }
+    write!(buf, "</li>")
+}
+
+pub fn references<'a, I>(buf: &mut String, references: I) -> fmt::Result
+where
+    I: IntoIterator<Item = &'a Reference>,
+{
+    let mut references: Vec<_> = references.into_iter().collect();
+    if references.is_empty() {
+        return Ok(());
+    }
+    references.sort_by(|a, b| {
+        (short_authors(a), year(a), &a.title).cmp(&(
+            short_authors(b),
+            year(b),
+            &b.title,
+        ))
+    });
+    write!(buf, "<ol class=\"references\">")?;
+    for entry in references {
+        reference(buf, entry)?;
+    }
+    write!(buf, "</ol>")
+}
@@ -0,0 +1,199 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use lin_ssg_core::{args, Args, Function, PageCtx, SiteFs};
+use thiserror::Error;
+
+use crate::{
+    bib::{BibSyntaxError, Bibliography},
+    format,
+};
+
+#[derive(Debug, Error)]
+pub enum CitationError {
+    #[error("Could not read bibliography {}", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("Invalid bibliography {}", .0.display())]
+    Syntax(PathBuf, #[source] BibSyntaxError),
+    #[error("Reference {} not found in the bibliography", .0)]
+    UnknownKey(String),
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    bibliography: Option<Arc<Bibliography>>,
+    cited: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Citations {
+    fs: Arc<dyn SiteFs>,
+    path: PathBuf,
+    state: Arc<RwLock<State>>,
+}
+
+impl Citations {
+    pub fn new(fs: Arc<dyn SiteFs>, path: PathBuf) -> Self {
+        Self { fs, path, state: Arc::default() }
+    }
+
+    fn load(&self) -> Result<Arc<Bibliography>, CitationError> {
+        if let Some(bibliography) = &self
+            .state
+            .read()
+            .unwrap_or_else(|error| error.into_inner())
+            .bibliography
+        {
+            return Ok(bibliography.clone());
+        }
+        let code = self
+            .fs
+            .read_to_string(&self.path)
+            .map_err(|error| CitationError::Io(self.path.clone(), error))?;
+        let bibliography =
+            if self.path.extension().is_some_and(|ext| ext == "json") {
+                Bibliography::parse_csl_json(&code)
+            } else {
+                Bibliography::parse_bibtex(&code)
+            }
+            .map_err(|error| CitationError::Syntax(self.path.clone(), error))?;
+        let bibliography = Arc::new(bibliography);
+        self.state
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .bibliography = Some(bibliography.clone());
+        Ok(bibliography)
+    }
+
+    fn record(&self, ctx: &PageCtx, key: &str) {
+        let page = ctx.url().unwrap_or_default().to_owned();
+        let mut state =
+            self.state.write().unwrap_or_else(|error| error.into_inner());
+        let cited = state.cited.entry(page).or_default();
+        if !cited.iter().any(|cited| cited == key) {
+            cited.push(key.to_owned());
+        }
+    }
+
+    fn cited(&self, ctx: &PageCtx) -> Vec<String> {
+        let page = ctx.url().unwrap_or_default();
+        self.state
+            .read()
+            .unwrap_or_else(|error| error.into_inner())
+            .cited
+            .get(page)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+args! {
+    positional ["key"];
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct CiteArgs<'a> {
+        /// key of the bibliography entry
+        key: &'a str,
+        /// page numbers or other locator, e.g. "12-14"
+        loc: Option<&'a str> = None,
+        /// parenthetical (true, default), e.g. (Ringe 2006), or narrative
+        /// (false), e.g. Ringe (2006)
+        paren: bool = true,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CiteFn {
+    citations: Citations,
+}
+
+impl CiteFn {
+    pub fn new(citations: Citations) -> Self {
+        Self { citations }
+    }
+}
+
+impl Function for CiteFn {
+    type Args<'a> = CiteArgs<'a>;
+    type Output = String;
+    type Error = CitationError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let bibliography = self.citations.load()?;
+        let reference = bibliography
+            .get(args.key)
+            .ok_or_else(|| CitationError::UnknownKey(args.key.to_owned()))?;
+        self.citations.record(ctx, args.key);
+        let mut buf = String::new();
+        format::citation(&mut buf, reference, args.loc, args.paren)?;
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "{{# inline author-year citation linking to the references \
+             #}}\ncite(\n{}\n) -> String",
+            CiteArgs::doc()
+        )
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BibliographyFn {
+    citations: Citations,
+}
+
+impl BibliographyFn {
+    pub fn new(citations: Citations) -> Self {
+        Self { citations }
+    }
+}
+
+impl Function for BibliographyFn {
+    type Args<'a> = ();
+    type Output = String;
+    type Error = CitationError;
+
+    fn call<'a>(
+        &self,
+        _args: Self::Args<'a>,
+        ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let bibliography = self.citations.load()?;
+        let cited = self.citations.cited(ctx);
+        let mut buf = String::new();
+        format::references(
+            &mut buf,
+            cited.iter().filter_map(|key| bibliography.get(key)),
+        )?;
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        "{# references cited so far on the current page #}
+        bibliography() -> String "
+            .to_owned()
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+pub use bib::{BibSyntaxError, Bibliography, Kind, Name, Reference};
+pub use function::CitationError;
+use function::{BibliographyFn, Citations, CiteFn};
+use lin_ssg_core::{LinSsg, Pack};
+
+mod bib;
+mod format;
+mod function;
+
+#[cfg(test)]
+mod test;
+
+pub const DEFAULT_BIBLIOGRAPHY_PATH: &str = "data/references.bib";
+
+pub const PACK_NAME: &str = "citations";
+
+#[derive(Debug, Clone)]
+pub struct CitationsPack {
+    bibliography_path: PathBuf,
+}
+
+impl CitationsPack {
+    pub fn new(bibliography_path: impl Into<PathBuf>) -> Self {
+        Self { bibliography_path: bibliography_path.into() }
+    }
+}
+
+impl Default for CitationsPack {
+    fn default() -> Self {
+        Self::new(DEFAULT_BIBLIOGRAPHY_PATH)
+    }
+}
+
+impl Pack for CitationsPack {
+    fn name(&self) -> &str {
+        PACK_NAME
+    }
+
+    fn install(&self, ssg: &mut LinSsg) {
+        install_with(ssg, self.bibliography_path.clone());
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "author-year citations and per-page references, read from {} \
+             (BibTeX, or CSL-JSON if the extension is .json)",
+            self.bibliography_path.display()
+        )
+    }
+}
+
+pub fn install(ssg: &mut LinSsg) {
+    install_with(ssg, DEFAULT_BIBLIOGRAPHY_PATH);
+}
+
+pub fn install_with(ssg: &mut LinSsg, bibliography_path: impl Into<PathBuf>) {
+    let citations =
+        Citations::new(ssg.config().shared_fs(), bibliography_path.into());
+    ssg.register_fn("cite", CiteFn::new(citations.clone()));
+    ssg.register_fn("bibliography", BibliographyFn::new(citations));
+}
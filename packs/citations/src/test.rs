@@ -0,0 +1,84 @@
+use crate::{format, Bibliography, Kind};
+
+const BIBTEX: &str = r#"
+@string{oup = "Oxford University Press"}
+
+@comment{ not an entry }
+
+@book{ringe2006,
+  author = {Ringe, Donald},
+  title = {From {Proto-Indo-European} to {Proto-Germanic}},
+  publisher = oup,
+  address = {Oxford},
+  year = 2006,
+}
+
+@article{kroonen2011,
+  author = "Guus Kroonen and Joseph F. Eska and Alwin Kloekhorst",
+  title = {On the origin of the {Proto-Germanic} \& Celtic n-stems},
+  journal = {Journal of Germanic Linguistics},
+  volume = {23},
+  number = {3},
+  pages = {1--20},
+  year = {2011}
+}
+"#;
+
+#[test]
+fn parse_bibtex_entries() {
+    let bibliography = Bibliography::parse_bibtex(BIBTEX).unwrap();
+    assert_eq!(bibliography.len(), 2);
+
+    let book = bibliography.get("ringe2006").unwrap();
+    assert_eq!(book.kind, Kind::Book);
+    assert_eq!(book.authors[0].family, "Ringe");
+    assert_eq!(book.authors[0].given.as_deref(), Some("Donald"));
+    assert_eq!(
+        book.title.as_deref(),
+        Some("From Proto-Indo-European to Proto-Germanic")
+    );
+    assert_eq!(book.publisher.as_deref(), Some("Oxford University Press"));
+    assert_eq!(book.year.as_deref(), Some("2006"));
+
+    let article = bibliography.get("kroonen2011").unwrap();
+    assert_eq!(article.authors.len(), 3);
+    assert_eq!(article.authors[1].family, "Eska");
+    assert_eq!(article.pages.as_deref(), Some("1–20"));
+    assert!(article.title.as_deref().unwrap().contains("Germanic & Celtic"));
+}
+
+#[test]
+fn parse_csl_json_items() {
+    let code = r#"[{
+        "id": "ringe2006",
+        "type": "book",
+        "author": [{"family": "Ringe", "given": "Donald"}],
+        "title": "From Proto-Indo-European to Proto-Germanic",
+        "issued": {"date-parts": [[2006]]}
+    }]"#;
+    let bibliography = Bibliography::parse_csl_json(code).unwrap();
+    let book = bibliography.get("ringe2006").unwrap();
+    assert_eq!(book.kind, Kind::Book);
+    assert_eq!(book.year.as_deref(), Some("2006"));
+}
+
+#[test]
+fn duplicated_key_is_rejected() {
+    let code = "@misc{a, title = {x}} @misc{a, title = {y}}";
+    assert!(Bibliography::parse_bibtex(code).is_err());
+}
+
+#[test]
+fn author_year_citations() {
+    let bibliography = Bibliography::parse_bibtex(BIBTEX).unwrap();
+    let mut buf = String::new();
+    let book = bibliography.get("ringe2006").unwrap();
+    format::citation(&mut buf, book, Some("12"), false).unwrap();
+    assert_eq!(
+        buf,
+        "<a class=\"citation\" href=\"#ref-ringe2006\">Ringe (2006: 12)</a>"
+    );
+
+    let article = bibliography.get("kroonen2011").unwrap();
+    assert_eq!(format::short_authors(article), "Kroonen et al.");
+}
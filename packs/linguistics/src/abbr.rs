@@ -0,0 +1,346 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write as _},
+    io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use lin_ssg_core::{
+    escape_html,
+    ArgError,
+    ArgParser,
+    Args,
+    Function,
+    PageCtx,
+    SiteFs,
+};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::gloss::render_gloss_word;
+
+pub const DEFAULT_ABBREVIATIONS_PATH: &str = "abbreviations.toml";
+
+pub const LEIPZIG: &[(&str, &str)] = &[
+    ("1", "first person"),
+    ("2", "second person"),
+    ("3", "third person"),
+    ("A", "agent-like argument of canonical transitive verb"),
+    ("ABL", "ablative"),
+    ("ABS", "absolutive"),
+    ("ACC", "accusative"),
+    ("ADJ", "adjective"),
+    ("ADV", "adverb(ial)"),
+    ("AGR", "agreement"),
+    ("ALL", "allative"),
+    ("ANTIP", "antipassive"),
+    ("APPL", "applicative"),
+    ("ART", "article"),
+    ("AUX", "auxiliary"),
+    ("BEN", "benefactive"),
+    ("CAUS", "causative"),
+    ("CLF", "classifier"),
+    ("COM", "comitative"),
+    ("COMP", "complementizer"),
+    ("COMPL", "completive"),
+    ("COND", "conditional"),
+    ("COP", "copula"),
+    ("CVB", "converb"),
+    ("DAT", "dative"),
+    ("DECL", "declarative"),
+    ("DEF", "definite"),
+    ("DEM", "demonstrative"),
+    ("DET", "determiner"),
+    ("DIST", "distal"),
+    ("DISTR", "distributive"),
+    ("DU", "dual"),
+    ("DUR", "durative"),
+    ("ERG", "ergative"),
+    ("EXCL", "exclusive"),
+    ("F", "feminine"),
+    ("FOC", "focus"),
+    ("FUT", "future"),
+    ("GEN", "genitive"),
+    ("IMP", "imperative"),
+    ("INCL", "inclusive"),
+    ("IND", "indicative"),
+    ("INDF", "indefinite"),
+    ("INF", "infinitive"),
+    ("INS", "instrumental"),
+    ("INTR", "intransitive"),
+    ("IPFV", "imperfective"),
+    ("IRR", "irrealis"),
+    ("LOC", "locative"),
+    ("M", "masculine"),
+    ("N", "neuter"),
+    ("NEG", "negation, negative"),
+    ("NMLZ", "nominalizer/nominalization"),
+    ("NOM", "nominative"),
+    ("OBJ", "object"),
+    ("OBL", "oblique"),
+    ("P", "patient-like argument of canonical transitive verb"),
+    ("PASS", "passive"),
+    ("PFV", "perfective"),
+    ("PL", "plural"),
+    ("POSS", "possessive"),
+    ("PRED", "predicative"),
+    ("PRF", "perfect"),
+    ("PRS", "present"),
+    ("PROG", "progressive"),
+    ("PROH", "prohibitive"),
+    ("PROX", "proximal/proximate"),
+    ("PST", "past"),
+    ("PTCP", "participle"),
+    ("PURP", "purposive"),
+    ("Q", "question particle/marker"),
+    ("QUOT", "quotative"),
+    ("RECP", "reciprocal"),
+    ("REFL", "reflexive"),
+    ("REL", "relative"),
+    ("RES", "resultative"),
+    ("S", "single argument of canonical intransitive verb"),
+    ("SBJ", "subject"),
+    ("SBJV", "subjunctive"),
+    ("SG", "singular"),
+    ("TOP", "topic"),
+    ("TR", "transitive"),
+    ("VOC", "vocative"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GlossAbbreviations {
+    table: BTreeMap<String, String>,
+}
+
+impl GlossAbbreviations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn leipzig() -> Self {
+        let mut this = Self::new();
+        for (abbr, expansion) in LEIPZIG {
+            this.insert(*abbr, *expansion);
+        }
+        this
+    }
+
+    pub fn insert(
+        &mut self,
+        abbr: impl Into<String>,
+        expansion: impl Into<String>,
+    ) -> &mut Self {
+        self.table.insert(abbr.into(), expansion.into());
+        self
+    }
+
+    pub fn with(
+        mut self,
+        abbr: impl Into<String>,
+        expansion: impl Into<String>,
+    ) -> Self {
+        self.insert(abbr, expansion);
+        self
+    }
+
+    pub fn table(&self) -> &BTreeMap<String, String> {
+        &self.table
+    }
+
+    pub fn expand(&self, abbr: &str) -> Option<String> {
+        if let Some(expansion) = self.table.get(abbr) {
+            return Some(expansion.clone());
+        }
+        let person_len = abbr.find(|ch: char| !ch.is_ascii_digit())?;
+        if person_len == 0 {
+            return None;
+        }
+        let person = self.table.get(&abbr[.. person_len])?;
+        let rest = self.table.get(&abbr[person_len ..])?;
+        Some(format!("{person} {rest}"))
+    }
+
+    pub fn render_label(&self, buf: &mut String, label: &str) -> fmt::Result {
+        let lowered = escape_html(&label.to_lowercase());
+        match self.expand(label) {
+            Some(expansion) => write!(
+                buf,
+                "<abbr class=\"gloss-abbr\" title=\"{}\">{}</abbr>",
+                escape_html(&expansion),
+                lowered
+            ),
+            None => {
+                write!(buf, "<span class=\"gloss-abbr\">{}</span>", lowered)
+            },
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AbbreviationsError {
+    #[error("Could not read gloss abbreviations {}", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("Invalid gloss abbreviations {}", .0.display())]
+    Toml(PathBuf, #[source] toml::de::Error),
+}
+
+// The pack's table, extended or overridden by the site's own file, e.g.
+// `PERT = "pertensive"`.
+#[derive(Debug, Clone)]
+pub struct SiteAbbreviations {
+    fs: Arc<dyn SiteFs>,
+    path: PathBuf,
+    base: Arc<GlossAbbreviations>,
+    merged: Arc<RwLock<Option<Arc<GlossAbbreviations>>>>,
+}
+
+impl SiteAbbreviations {
+    pub fn new(
+        fs: Arc<dyn SiteFs>,
+        path: PathBuf,
+        base: Arc<GlossAbbreviations>,
+    ) -> Self {
+        Self { fs, path, base, merged: Arc::default() }
+    }
+
+    pub fn load(&self) -> Result<Arc<GlossAbbreviations>, AbbreviationsError> {
+        if let Some(merged) =
+            &*self.merged.read().unwrap_or_else(|error| error.into_inner())
+        {
+            return Ok(merged.clone());
+        }
+        let site: BTreeMap<String, String> =
+            match self.fs.read_to_string(&self.path) {
+                Ok(code) => toml::from_str(&code).map_err(|error| {
+                    AbbreviationsError::Toml(self.path.clone(), error)
+                })?,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                    BTreeMap::new()
+                },
+                Err(error) => {
+                    Err(AbbreviationsError::Io(self.path.clone(), error))?
+                },
+            };
+        let mut merged = (*self.base).clone();
+        for (abbr, expansion) in site {
+            merged.insert(abbr, expansion);
+        }
+        let merged = Arc::new(merged);
+        *self.merged.write().unwrap_or_else(|error| error.into_inner()) =
+            Some(merged.clone());
+        Ok(merged)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GlossAbbrError {
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+    #[error(transparent)]
+    Abbreviations(#[from] AbbreviationsError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlossAbbrArgs<'a> {
+    input: &'a str,
+}
+
+impl<'a> Args<'a> for GlossAbbrArgs<'a> {
+    const POSITIONAL: &'static [&'static str] = &["in"];
+
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let input = args.retrive_arg("in")?;
+        Ok(Self { input })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GlossAbbrFn {
+    abbreviations: SiteAbbreviations,
+}
+
+impl GlossAbbrFn {
+    pub fn new(abbreviations: SiteAbbreviations) -> Self {
+        Self { abbreviations }
+    }
+}
+
+impl Function for GlossAbbrFn {
+    type Args<'a> = GlossAbbrArgs<'a>;
+    type Output = String;
+    type Error = GlossAbbrError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let abbreviations = self.abbreviations.load()?;
+        let mut buf = String::new();
+        for (i, word) in args.input.split_whitespace().enumerate() {
+            if i > 0 {
+                buf.push(' ');
+            }
+            render_gloss_word(&mut buf, word, &abbreviations)?;
+        }
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        "{# expand Leipzig abbreviations of a gloss into <abbr> tags #}
+        glossabbr(
+            {# gloss, e.g. dog-PL #}
+            in:string
+        ) -> String "
+            .to_owned()
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GlossAbbrTableFn {
+    abbreviations: SiteAbbreviations,
+}
+
+impl GlossAbbrTableFn {
+    pub fn new(abbreviations: SiteAbbreviations) -> Self {
+        Self { abbreviations }
+    }
+}
+
+impl Function for GlossAbbrTableFn {
+    type Args<'a> = ();
+    type Output = Map<String, Value>;
+    type Error = AbbreviationsError;
+
+    fn call<'a>(
+        &self,
+        _args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        Ok(self
+            .abbreviations
+            .load()?
+            .table()
+            .iter()
+            .map(|(abbr, expansion)| {
+                (abbr.clone(), Value::String(expansion.clone()))
+            })
+            .collect())
+    }
+
+    fn doc(&self) -> String {
+        "{# every gloss abbreviation and its expansion, including the site's \
+         own #}
+        gloss_abbreviations() -> Object "
+            .to_owned()
+    }
+}
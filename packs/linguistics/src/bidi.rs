@@ -0,0 +1,96 @@
+use std::{convert::Infallible, fmt};
+
+use lin_ssg_core::{args, escape_html, Arg, Args, Function, PageCtx};
+use serde::Deserialize;
+
+pub const VERTICAL_CLASS: &str = "script-vertical";
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Ltr,
+    Rtl,
+    Auto,
+}
+
+impl Direction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ltr => "ltr",
+            Self::Rtl => "rtl",
+            Self::Auto => "auto",
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.write_str(self.as_str())
+    }
+}
+
+impl<'a> Arg<'a> for Direction {
+    fn from_json_ref(json: &'a serde_json::Value) -> Option<Self> {
+        Some(match <&str>::from_json_ref(json)? {
+            "ltr" => Self::Ltr,
+            "rtl" => Self::Rtl,
+            "auto" => Self::Auto,
+            _ => None?,
+        })
+    }
+
+    fn json_type() -> String {
+        "direction".to_owned()
+    }
+}
+
+args! {
+    positional ["in"];
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct BidiArgs<'a> {
+        /// text to isolate from the surrounding direction
+        input: &'a str => "in",
+        /// text direction: ltr, rtl or auto (default, guessed from the text)
+        dir: Direction = Direction::Auto,
+        /// language code of the text
+        lang: Option<&'a str> => "lg" = None,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BidiFn;
+
+impl Function for BidiFn {
+    type Args<'a> = BidiArgs<'a>;
+    type Output = String;
+    type Error = Infallible;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let lang = args.lang.map_or(String::new(), |lang| {
+            format!(" lang=\"{}\"", escape_html(lang))
+        });
+        Ok(format!(
+            "<bdi dir=\"{}\"{lang}>{}</bdi>",
+            args.dir,
+            escape_html(args.input)
+        ))
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "{{# isolates text of another direction, e.g. Arabic inside an \
+             English sentence #}}\nbidi(\n{}\n) -> String",
+            BidiArgs::doc()
+        )
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write as _},
+};
+
+use lin_ssg_core::{
+    escape_html,
+    invoke_fn,
+    ArgError,
+    ArgParser,
+    Args,
+    Function,
+    InvokeError,
+    PageCtx,
+};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::{
+    transc::{TranscFn, TranscriptionError, RECONSTRUCTED_MARKER},
+    tree::{self, DepthError, Tree},
+};
+
+pub const STAGE_LANG_KEY: &str = "lang";
+pub const STAGE_MEANING_KEY: &str = "mean";
+pub const STAGE_DEPTH_KEY: &str = "depth";
+pub const STAGE_FIELD_SEPARATOR: char = '|';
+
+#[derive(Debug, Error)]
+pub enum EtymError {
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+    #[error("Etymology stage {} must be an object or a string", .0)]
+    BadStage(usize),
+    #[error("Etymology stage {} has invalid {}", .0, .1)]
+    BadStageField(usize, &'static str),
+    #[error("Etymology stage {} is misplaced", .0)]
+    BadDepth(usize, #[source] DepthError),
+    #[error("Failed to transcribe etymology stage {}", .0)]
+    Transcription(usize, #[source] Box<InvokeError<TranscriptionError>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EtymArgs<'a> {
+    stages: &'a [Value],
+}
+
+impl<'a> Args<'a> for EtymArgs<'a> {
+    const POSITIONAL: &'static [&'static str] = &["stages"];
+
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let stages = args.retrive_arg("stages")?;
+        Ok(Self { stages })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EtymFn {
+    transc: TranscFn,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Stage {
+    number: usize,
+    fields: Map<String, Value>,
+}
+
+pub fn parse_stage(stage: &str) -> Map<String, Value> {
+    let (depth, trimmed) = tree::split_depth(stage);
+    let mut fields = trimmed.split(STAGE_FIELD_SEPARATOR).map(str::trim);
+    let mut map = Map::new();
+    if depth > 0 {
+        map.insert(STAGE_DEPTH_KEY.to_owned(), depth.into());
+    }
+    if let Some(lang) = fields.next().filter(|lang| !lang.is_empty()) {
+        map.insert(STAGE_LANG_KEY.to_owned(), lang.into());
+    }
+    let form = fields.next().unwrap_or_default();
+    match form.strip_prefix(RECONSTRUCTED_MARKER) {
+        Some(form) => {
+            map.insert("in".to_owned(), form.into());
+            map.insert("att".to_owned(), false.into());
+        },
+        None => {
+            map.insert("in".to_owned(), form.into());
+        },
+    }
+    if let Some(ty) = fields.next().filter(|ty| !ty.is_empty()) {
+        map.insert("ty".to_owned(), ty.into());
+    }
+    if let Some(meaning) = fields.next().filter(|meaning| !meaning.is_empty()) {
+        map.insert(STAGE_MEANING_KEY.to_owned(), meaning.into());
+    }
+    map
+}
+
+fn build_tree(stages: &[Value]) -> Result<Tree<Stage>, EtymError> {
+    let mut tree = Tree::new();
+    for (i, stage) in stages.iter().enumerate() {
+        let number = i + 1;
+        let fields = match stage {
+            Value::Object(fields) => fields.clone(),
+            Value::String(stage) => parse_stage(stage),
+            _ => Err(EtymError::BadStage(number))?,
+        };
+        let depth = match fields.get(STAGE_DEPTH_KEY) {
+            None | Some(Value::Null) => tree.next_depth(),
+            Some(depth) => depth
+                .as_u64()
+                .and_then(|depth| usize::try_from(depth).ok())
+                .ok_or(EtymError::BadStageField(number, STAGE_DEPTH_KEY))?,
+        };
+        tree.push(depth, Stage { number, fields })
+            .map_err(|error| EtymError::BadDepth(number, error))?;
+    }
+    Ok(tree)
+}
+
+impl EtymFn {
+    pub fn new(transc: TranscFn) -> Self {
+        Self { transc }
+    }
+
+    fn render_chains(
+        &self,
+        buf: &mut String,
+        tree: &Tree<Stage>,
+        starts: &[usize],
+        ctx: &PageCtx,
+    ) -> Result<(), EtymError> {
+        if let [start] = starts {
+            return self.render_chain(buf, tree, *start, ctx);
+        }
+        write!(buf, "<ul class=\"etym-branches\">")?;
+        for &start in starts {
+            write!(buf, "<li class=\"etym-branch\">")?;
+            self.render_chain(buf, tree, start, ctx)?;
+            write!(buf, "</li>")?;
+        }
+        write!(buf, "</ul>")?;
+        Ok(())
+    }
+
+    fn render_chain(
+        &self,
+        buf: &mut String,
+        tree: &Tree<Stage>,
+        start: usize,
+        ctx: &PageCtx,
+    ) -> Result<(), EtymError> {
+        write!(buf, "<ol class=\"etym\">")?;
+        let mut current = start;
+        loop {
+            let node = tree.node(current);
+            write!(buf, "<li class=\"etym-stage\">")?;
+            if current != start {
+                write!(
+                    buf,
+                    "<span class=\"etym-arrow\" \
+                     aria-hidden=\"true\">&gt;</span>"
+                )?;
+            }
+            self.render_stage(buf, &node.value, ctx)?;
+            match node.children[..] {
+                [next] => {
+                    write!(buf, "</li>")?;
+                    current = next;
+                },
+                [] => {
+                    write!(buf, "</li>")?;
+                    break;
+                },
+                _ => {
+                    self.render_chains(buf, tree, &node.children, ctx)?;
+                    write!(buf, "</li>")?;
+                    break;
+                },
+            }
+        }
+        write!(buf, "</ol>")?;
+        Ok(())
+    }
+
+    fn render_stage(
+        &self,
+        buf: &mut String,
+        stage: &Stage,
+        ctx: &PageCtx,
+    ) -> Result<(), EtymError> {
+        let field = |key: &'static str| match stage.fields.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value.as_str())),
+            Some(_) => Err(EtymError::BadStageField(stage.number, key)),
+        };
+
+        if let Some(lang) = field(STAGE_LANG_KEY)? {
+            write!(
+                buf,
+                "<span class=\"etym-lang\">{}</span> ",
+                escape_html(lang)
+            )?;
+        }
+
+        let mut transc_args: HashMap<_, _> = stage
+            .fields
+            .iter()
+            .filter(|(key, _)| {
+                ![STAGE_LANG_KEY, STAGE_MEANING_KEY, STAGE_DEPTH_KEY]
+                    .contains(&key.as_str())
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        transc_args.insert("html".to_owned(), false.into());
+        let form = invoke_fn("transc", &self.transc, &transc_args, ctx)
+            .map_err(|error| {
+                EtymError::Transcription(stage.number, Box::new(error))
+            })?;
+        write!(buf, "<span class=\"etym-form\"")?;
+        if let Some(lang) = field("lg")? {
+            write!(buf, " lang=\"{}\"", escape_html(lang))?;
+        }
+        // transc() already escapes its output.
+        write!(buf, ">{}</span>", form)?;
+
+        if let Some(meaning) = field(STAGE_MEANING_KEY)? {
+            write!(
+                buf,
+                " <span class=\"etym-mean\">‘{}’</span>",
+                escape_html(meaning)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Function for EtymFn {
+    type Args<'a> = EtymArgs<'a>;
+    type Output = String;
+    type Error = EtymError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let tree = build_tree(args.stages)?;
+        let mut buf = String::new();
+        self.render_chains(&mut buf, &tree, tree.roots(), ctx)?;
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        "{# etymological chain, optionally branching into a tree #}
+        etym(
+            {# stages from oldest to newest, each one either:
+                - a string \"lang|form|ty|meaning\", trailing fields
+                  optional, form prefixed with * when reconstructed,
+                  e.g. \"Proto-Germanic|*wulfaz||wolf\"
+                - an object with the arguments of transc (in, lg, ty, att,
+                  chk) plus lang, mean and depth
+                each stage descends from the previous one; to branch, give
+                the stage an explicit depth (number of leading > in the
+                string form), so it descends from the last stage one level
+                above it
+            #}
+            stages:array
+        ) -> String "
+            .to_owned()
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,263 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write as _},
+    io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use lin_ssg_core::{
+    args,
+    escape_html,
+    verbatim,
+    Args,
+    Function,
+    PageCtx,
+    SiteFs,
+};
+use lin_ssg_linguinput::{encode_with, EncodingError, Table};
+use thiserror::Error;
+
+pub const DEFAULT_FEATURES_PATH: &str = "data/features.csv";
+
+pub const STANDARD_FEATURES: &str = include_str!("features.csv");
+
+const FEATURE_VALUES: &[(&str, &str, &str)] = &[
+    ("+", "+", "feature-plus"),
+    ("-", "\u{2212}", "feature-minus"),
+    ("0", "0", "feature-zero"),
+];
+
+#[derive(Debug, Error)]
+pub enum FeaturesError {
+    #[error("Could not read feature table {}", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("Feature table {}, line {}: {}", .path.display(), .line, .message)]
+    Syntax { path: PathBuf, line: usize, message: String },
+}
+
+#[derive(Debug, Error)]
+pub enum FeatureMatrixError {
+    #[error(transparent)]
+    Features(#[from] FeaturesError),
+    #[error("Could not encode to unicode: {}", .0)]
+    Encoding(
+        #[from]
+        #[source]
+        EncodingError,
+    ),
+    #[error("Segment {} has no features defined", .0)]
+    UnknownSegment(String),
+    #[error("Feature {} is not defined", .0)]
+    UnknownFeature(String),
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureTable {
+    features: Vec<String>,
+    segments: HashMap<String, HashMap<String, String>>,
+}
+
+impl FeatureTable {
+    pub fn merge(
+        &mut self,
+        path: PathBuf,
+        code: &str,
+    ) -> Result<(), FeaturesError> {
+        let syntax_error = |line, message: &str| FeaturesError::Syntax {
+            path: path.clone(),
+            line,
+            message: message.to_owned(),
+        };
+        let mut lines = code
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        let Some((_, header)) = lines.next() else { return Ok(()) };
+        let features: Vec<_> =
+            header.split(',').skip(1).map(|name| name.trim()).collect();
+        for feature in &features {
+            if !self.features.iter().any(|known| known == feature) {
+                self.features.push((*feature).to_owned());
+            }
+        }
+        for (line_number, line) in lines {
+            let mut cells = line.split(',').map(str::trim);
+            let segment = cells.next().unwrap_or_default();
+            let values: Vec<_> = cells.collect();
+            if values.len() != features.len() {
+                Err(syntax_error(
+                    line_number,
+                    &format!(
+                        "expected {} values, found {}",
+                        features.len(),
+                        values.len()
+                    ),
+                ))?;
+            }
+            let row = self.segments.entry(segment.to_owned()).or_default();
+            for (feature, value) in features.iter().zip(values) {
+                if !FEATURE_VALUES.iter().any(|(known, ..)| *known == value) {
+                    Err(syntax_error(
+                        line_number,
+                        &format!("value of {feature} must be +, - or 0"),
+                    ))?;
+                }
+                row.insert((*feature).to_owned(), value.to_owned());
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&self, segment: &str, feature: &str) -> &str {
+        self.segments[segment].get(feature).map_or("0", String::as_str)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Features {
+    fs: Arc<dyn SiteFs>,
+    path: PathBuf,
+    table: Arc<RwLock<Option<Arc<FeatureTable>>>>,
+}
+
+impl Features {
+    pub fn new(fs: Arc<dyn SiteFs>, path: PathBuf) -> Self {
+        Self { fs, path, table: Arc::default() }
+    }
+
+    fn load(&self) -> Result<Arc<FeatureTable>, FeaturesError> {
+        if let Some(table) =
+            &*self.table.read().unwrap_or_else(|error| error.into_inner())
+        {
+            return Ok(table.clone());
+        }
+        let mut table = FeatureTable::default();
+        table.merge(PathBuf::from("features.csv"), STANDARD_FEATURES)?;
+        match self.fs.read_to_string(&self.path) {
+            Ok(code) => table.merge(self.path.clone(), &code)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+            Err(error) => Err(FeaturesError::Io(self.path.clone(), error))?,
+        }
+        let table = Arc::new(table);
+        *self.table.write().unwrap_or_else(|error| error.into_inner()) =
+            Some(table.clone());
+        Ok(table)
+    }
+}
+
+args! {
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct FeaturesArgs<'a> {
+        /// segments, one column each, linguinput codes allowed
+        segments: Vec<&'a str> => "segs",
+        /// features, one row each, in order; default all of them
+        features: Option<Vec<&'a str>> => "feats" = None,
+        /// only show features whose values differ among the segments?
+        /// default false
+        contrastive: bool = false,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeaturesFn {
+    features: Features,
+    table: Arc<Table>,
+}
+
+impl FeaturesFn {
+    pub fn new(features: Features, table: Arc<Table>) -> Self {
+        Self { features, table }
+    }
+}
+
+impl Function for FeaturesFn {
+    type Args<'a> = FeaturesArgs<'a>;
+    type Output = String;
+    type Error = FeatureMatrixError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let table = self.features.load()?;
+        let segments = args
+            .segments
+            .iter()
+            .map(|segment| {
+                let segment = encode_with(segment, &self.table)?;
+                if !table.segments.contains_key(&segment) {
+                    Err(FeatureMatrixError::UnknownSegment(segment.clone()))?;
+                }
+                Ok(segment)
+            })
+            .collect::<Result<Vec<_>, FeatureMatrixError>>()?;
+        let mut features = match &args.features {
+            Some(features) => features
+                .iter()
+                .map(|feature| {
+                    table
+                        .features
+                        .iter()
+                        .find(|known| known == feature)
+                        .ok_or_else(|| {
+                            FeatureMatrixError::UnknownFeature(
+                                (*feature).to_owned(),
+                            )
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => table.features.iter().collect(),
+        };
+        if args.contrastive {
+            features.retain(|feature| {
+                segments.iter().any(|segment| {
+                    table.value(segment, feature)
+                        != table.value(&segments[0], feature)
+                })
+            });
+        }
+
+        let mut buf = String::new();
+        write!(buf, "<table class=\"features\"><thead><tr><th></th>")?;
+        for segment in &segments {
+            write!(buf, "<th>{}</th>", escape_html(segment))?;
+        }
+        write!(buf, "</tr></thead><tbody>")?;
+        for feature in features {
+            write!(buf, "<tr><th>{}</th>", escape_html(feature))?;
+            for segment in &segments {
+                let value = table.value(segment, feature);
+                let (_, symbol, class) = FEATURE_VALUES
+                    .iter()
+                    .find(|(known, ..)| *known == value)
+                    .unwrap_or(&FEATURE_VALUES[2]);
+                write!(buf, "<td class=\"{class}\">{symbol}</td>")?;
+            }
+            write!(buf, "</tr>")?;
+        }
+        write!(buf, "</tbody></table>")?;
+        Ok(verbatim(&buf))
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "{{# distinctive feature matrix of IPA segments, with the \
+             standard definitions overridable by the site \
+             #}}\nfeatures(\n{}\n) -> String",
+            FeaturesArgs::doc()
+        )
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
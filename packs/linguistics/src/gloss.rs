@@ -0,0 +1,359 @@
+use std::fmt::{self, Write as _};
+
+use lin_ssg_core::{
+    args,
+    escape_html,
+    escape_latex,
+    verbatim,
+    Args,
+    Function,
+    PageCtx,
+};
+use lin_ssg_linguinput::{
+    encode_with,
+    EncodingError,
+    TableInitError,
+    DEFAULT_TABLE_NAME,
+};
+use thiserror::Error;
+
+use crate::{
+    abbr::{AbbreviationsError, GlossAbbreviations, SiteAbbreviations},
+    bidi::{Direction, VERTICAL_CLASS},
+    lang::{Language, Languages, LanguagesError},
+    transc::SiteTables,
+};
+
+pub const MORPHEME_SEPARATORS: &[char] = &['-', '=', '.', ':', '~'];
+
+#[derive(Debug, Error)]
+pub enum GlossError {
+    #[error("Could not encode to unicode: {}", .0)]
+    Encoding(
+        #[from]
+        #[source]
+        EncodingError,
+    ),
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+    #[error(
+        "Gloss line {} has {} words but the source line has {}",
+        .line,
+        .found,
+        .expected
+    )]
+    Misaligned { line: &'static str, expected: usize, found: usize },
+    #[error("Could not select linguinput table")]
+    Table(
+        #[from]
+        #[source]
+        TableInitError,
+    ),
+    #[error(transparent)]
+    Languages(#[from] LanguagesError),
+    #[error(transparent)]
+    Abbreviations(#[from] AbbreviationsError),
+}
+
+args! {
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct GlossArgs<'a> {
+        /// source line, words separated by spaces
+        source: &'a str => "src",
+        /// morpheme breakdown, aligned word by word with src
+        morphemes: Option<&'a str> => "morph" = None,
+        /// gloss line, aligned word by word with src
+        gloss: &'a str => "gl",
+        /// free translation
+        translation: Option<&'a str> => "tr" = None,
+        /// language code of the source line
+        lang: Option<&'a str> => "lg" = None,
+        /// name of the linguinput table for src and morph, default
+        /// "default" unless the language sets one
+        table: Option<&'a str> => "tbl" = None,
+        /// direction of the source line: ltr, rtl or auto, by default the
+        /// language's; unused in LaTeX
+        dir: Option<Direction> = None,
+    }
+}
+
+pub fn render_gloss_word(
+    buf: &mut String,
+    word: &str,
+    abbreviations: &GlossAbbreviations,
+) -> fmt::Result {
+    let mut rest = word;
+    while !rest.is_empty() {
+        let end = rest.find(MORPHEME_SEPARATORS).unwrap_or(rest.len());
+        let token = &rest[.. end];
+        if is_category_label(token) {
+            abbreviations.render_label(buf, token)?;
+        } else {
+            write!(buf, "{}", escape_html(token))?;
+        }
+        rest = &rest[end ..];
+        if let Some(separator) = rest.chars().next() {
+            write!(buf, "{}", escape_html(&separator.to_string()))?;
+            rest = &rest[separator.len_utf8() ..];
+        }
+    }
+    Ok(())
+}
+
+// Category labels go in small capitals, as the Leipzig rules ask for.
+pub fn render_gloss_word_latex(buf: &mut String, word: &str) -> fmt::Result {
+    let mut rest = word;
+    while !rest.is_empty() {
+        let end = rest.find(MORPHEME_SEPARATORS).unwrap_or(rest.len());
+        let token = &rest[.. end];
+        if is_category_label(token) {
+            write!(buf, "\\textsc{{{}}}", escape_latex(&token.to_lowercase()))?;
+        } else {
+            write!(buf, "{}", escape_latex(token))?;
+        }
+        rest = &rest[end ..];
+        if let Some(separator) = rest.chars().next() {
+            write!(buf, "{}", escape_latex(&separator.to_string()))?;
+            rest = &rest[separator.len_utf8() ..];
+        }
+    }
+    Ok(())
+}
+
+pub fn is_category_label(token: &str) -> bool {
+    token.chars().any(|ch| ch.is_uppercase())
+        && token.chars().all(|ch| ch.is_uppercase() || ch.is_ascii_digit())
+}
+
+fn split_line(
+    line: &'static str,
+    content: &str,
+    expected: usize,
+) -> Result<Vec<String>, GlossError> {
+    let words: Vec<_> = content.split_whitespace().map(str::to_owned).collect();
+    if words.len() != expected {
+        Err(GlossError::Misaligned { line, expected, found: words.len() })?;
+    }
+    Ok(words)
+}
+
+struct Lines {
+    language: Option<Language>,
+    source: Vec<String>,
+    morphemes: Option<Vec<String>>,
+    gloss: Vec<String>,
+}
+
+impl Lines {
+    // The source and morpheme lines are linguinput, encoded with the table
+    // given in the call or else the language's, just like transcriptions.
+    fn split(
+        args: &GlossArgs,
+        languages: &Languages,
+        tables: &SiteTables,
+    ) -> Result<Self, GlossError> {
+        let language = match args.lang {
+            Some(code) => languages.get(code)?,
+            None => None,
+        };
+        let table = args
+            .table
+            .or(language
+                .as_ref()
+                .and_then(|language| language.table.as_deref()))
+            .unwrap_or(DEFAULT_TABLE_NAME);
+        let table = tables.get(table)?;
+        let source: Vec<_> = encode_with(args.source, table)?
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect();
+        let morphemes = args
+            .morphemes
+            .map(|morphemes| {
+                split_line(
+                    "morph",
+                    &encode_with(morphemes, table)?,
+                    source.len(),
+                )
+            })
+            .transpose()?;
+        let gloss = split_line("gl", args.gloss, source.len())?;
+        Ok(Self { language, source, morphemes, gloss })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GlossFn {
+    abbreviations: SiteAbbreviations,
+    languages: Languages,
+    tables: SiteTables,
+}
+
+impl GlossFn {
+    pub fn new(
+        abbreviations: SiteAbbreviations,
+        languages: Languages,
+        tables: SiteTables,
+    ) -> Self {
+        Self { abbreviations, languages, tables }
+    }
+}
+
+impl Function for GlossFn {
+    type Args<'a> = GlossArgs<'a>;
+    type Output = String;
+    type Error = GlossError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let Lines {
+            language,
+            source: source_words,
+            morphemes: morpheme_words,
+            gloss: gloss_words,
+        } = Lines::split(&args, &self.languages, &self.tables)?;
+        let abbreviations = self.abbreviations.load()?;
+        let dir =
+            args.dir.or(language.as_ref().and_then(|language| language.dir));
+        let vertical =
+            language.as_ref().is_some_and(|language| language.vertical);
+
+        // Words run in the direction of the source line, while morphemes and
+        // glosses within each word stay left to right.
+        let mut buf = String::new();
+        write!(buf, "<div class=\"gloss-words\"")?;
+        if let Some(dir) = dir {
+            write!(buf, " dir=\"{dir}\"")?;
+        }
+        write!(buf, ">")?;
+        for (i, source_word) in source_words.iter().enumerate() {
+            write!(buf, "<div class=\"gloss-word\">")?;
+            write!(buf, "<span class=\"gloss-src")?;
+            if vertical {
+                write!(buf, " {VERTICAL_CLASS}")?;
+            }
+            write!(buf, "\"")?;
+            if let Some(lang) = args.lang {
+                write!(buf, " lang=\"{}\"", escape_html(lang))?;
+            }
+            if let Some(dir) = dir {
+                write!(buf, " dir=\"{dir}\"")?;
+            }
+            write!(buf, ">{}</span>", escape_html(source_word))?;
+            if let Some(morpheme_words) = &morpheme_words {
+                write!(
+                    buf,
+                    "<span class=\"gloss-morph\">{}</span>",
+                    escape_html(&morpheme_words[i])
+                )?;
+            }
+            write!(buf, "<span class=\"gloss-gl\"")?;
+            if dir == Some(Direction::Rtl) {
+                write!(buf, " dir=\"ltr\"")?;
+            }
+            write!(buf, ">")?;
+            render_gloss_word(&mut buf, &gloss_words[i], &abbreviations)?;
+            write!(buf, "</span>")?;
+            write!(buf, "</div>")?;
+        }
+        write!(buf, "</div>")?;
+        // Apostrophes and colons in the interlinear lines are part of the
+        // forms and labels, only the free translation is prose.
+        let mut buf = format!("<div class=\"gloss\">{}", verbatim(&buf));
+        if let Some(translation) = args.translation {
+            write!(
+                buf,
+                "<p class=\"gloss-tr\">‘{}’</p>",
+                escape_html(translation)
+            )?;
+        }
+        write!(buf, "</div>")?;
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "{{# interlinear glossed example, Leipzig conventions \
+             #}}\ngloss(\n{}\n) -> String",
+            GlossArgs::doc()
+        )
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GlossLatexFn {
+    languages: Languages,
+    tables: SiteTables,
+}
+
+impl GlossLatexFn {
+    pub fn new(languages: Languages, tables: SiteTables) -> Self {
+        Self { languages, tables }
+    }
+}
+
+impl Function for GlossLatexFn {
+    type Args<'a> = GlossArgs<'a>;
+    type Output = String;
+    type Error = GlossError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let Lines {
+            source: source_words,
+            morphemes: morpheme_words,
+            gloss: gloss_words,
+            ..
+        } = Lines::split(&args, &self.languages, &self.tables)?;
+
+        // An expex example, one \gl line per tier of the gloss.
+        let mut buf = String::new();
+        writeln!(buf, "\\ex")?;
+        writeln!(buf, "\\begingl")?;
+        let words: Vec<_> =
+            source_words.iter().map(|word| escape_latex(word)).collect();
+        writeln!(buf, "\\gla {} //", words.join(" "))?;
+        let mut tiers = ["glb", "glc"].into_iter();
+        if let Some(morpheme_words) = &morpheme_words {
+            let words: Vec<_> =
+                morpheme_words.iter().map(|word| escape_latex(word)).collect();
+            let tier = tiers.next().unwrap_or_default();
+            writeln!(buf, "\\{tier} {} //", words.join(" "))?;
+        }
+        let tier = tiers.next().unwrap_or_default();
+        write!(buf, "\\{tier}")?;
+        for word in &gloss_words {
+            write!(buf, " ")?;
+            render_gloss_word_latex(&mut buf, word)?;
+        }
+        writeln!(buf, " //")?;
+        if let Some(translation) = args.translation {
+            writeln!(buf, "\\glft ‘{}’ //", escape_latex(translation))?;
+        }
+        writeln!(buf, "\\endgl")?;
+        writeln!(buf, "\\xe")?;
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "{{# interlinear glossed example as an expex environment, for the \
+             LaTeX export #}}\ngloss(\n{}\n) -> String",
+            GlossArgs::doc()
+        )
+    }
+}
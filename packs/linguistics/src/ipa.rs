@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use lin_ssg_core::{ArgError, ArgParser, Args, Function, PageCtx};
+use lin_ssg_linguinput::{encode_with, EncodingError, Table};
+use thiserror::Error;
+
+pub const EXTRA_IPA_CHARS: &[char] = &[
+    'æ', 'ç', 'ð', 'ø', 'ħ', 'ŋ', 'œ', 'θ', 'β', 'χ', 'ⱱ', 'ǀ', 'ǁ', 'ǂ', 'ǃ',
+    '‖', '|', '.', '‿', '↗', '↘', 'ꜛ', 'ꜜ', 'ⁿ', 'ꟹ', '¹', '²', '³',
+];
+
+pub const DELIMITERS: &[char] = &['/', '[', ']', '(', ')', '⟨', '⟩', '-', '*'];
+
+pub const HIGHLIGHT_START: &str = ">>";
+pub const HIGHLIGHT_END: &str = "<<";
+
+#[derive(Debug, Error)]
+pub enum IpaError {
+    #[error("Could not encode to unicode: {}", .0)]
+    Encoding(
+        #[from]
+        #[source]
+        EncodingError,
+    ),
+    #[error(
+        "Invalid IPA segment {:?} (U+{:04X}) in {}",
+        .segment,
+        u32::from(*.segment),
+        .highlighted
+    )]
+    InvalidSegment { segment: char, highlighted: String },
+}
+
+pub fn is_ipa_char(ch: char) -> bool {
+    match ch {
+        'g' => false,
+        'a' ..= 'z' => true,
+        '\u{250}' ..= '\u{2FF}' => true,
+        '\u{300}' ..= '\u{36F}' => true,
+        '\u{1D00}' ..= '\u{1DBF}' => true,
+        '\u{1DC0}' ..= '\u{1DFF}' => true,
+        '\u{2070}' ..= '\u{2079}' => true,
+        _ => {
+            ch.is_whitespace()
+                || EXTRA_IPA_CHARS.contains(&ch)
+                || DELIMITERS.contains(&ch)
+        },
+    }
+}
+
+pub fn validate(transcription: &str) -> Result<(), IpaError> {
+    let Some((position, segment)) =
+        transcription.char_indices().find(|(_, ch)| !is_ipa_char(*ch))
+    else {
+        return Ok(());
+    };
+    let end = position + segment.len_utf8();
+    let highlighted = format!(
+        "{}{HIGHLIGHT_START}{}{HIGHLIGHT_END}{}",
+        &transcription[.. position],
+        &transcription[position .. end],
+        &transcription[end ..],
+    );
+    Err(IpaError::InvalidSegment { segment, highlighted })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IpaCheckArgs<'a> {
+    input: &'a str,
+}
+
+impl<'a> Args<'a> for IpaCheckArgs<'a> {
+    const POSITIONAL: &'static [&'static str] = &["in"];
+
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let input = args.retrive_arg("in")?;
+        Ok(Self { input })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IpaCheckFn {
+    table: Arc<Table>,
+}
+
+impl IpaCheckFn {
+    pub fn new(table: Arc<Table>) -> Self {
+        Self { table }
+    }
+}
+
+impl Function for IpaCheckFn {
+    type Args<'a> = IpaCheckArgs<'a>;
+    type Output = String;
+    type Error = IpaError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let encoded = encode_with(args.input, &self.table)?;
+        validate(&encoded)?;
+        Ok(encoded)
+    }
+
+    fn doc(&self) -> String {
+        "{# encode with linguinput and fail if the result is not valid IPA #}
+        ipa_check(
+            {# input #}
+            in:string
+        ) -> String "
+            .to_owned()
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use lin_ssg_core::{args, Args, Function, PageCtx, SiteFs};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{bidi::Direction, transc::TranscriptionType};
+
+pub const DEFAULT_LANGUAGES_PATH: &str = "languages.toml";
+
+type Registry = BTreeMap<String, Language>;
+
+#[derive(Debug, Error)]
+pub enum LanguagesError {
+    #[error("Could not read language registry {}", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("Invalid language registry {}", .0.display())]
+    Toml(PathBuf, #[source] toml::de::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum LangNameError {
+    #[error(transparent)]
+    Languages(#[from] LanguagesError),
+    #[error("Language {} not found in the language registry", .0)]
+    UnknownLanguage(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Language {
+    pub name: String,
+    #[serde(default)]
+    pub script: Option<String>,
+    #[serde(default)]
+    pub dir: Option<Direction>,
+    #[serde(default)]
+    pub transcription: Option<TranscriptionType>,
+    #[serde(default)]
+    pub table: Option<String>,
+    #[serde(default)]
+    pub class: Option<String>,
+    #[serde(default)]
+    pub vertical: bool,
+}
+
+impl Language {
+    pub fn tag(&self, code: &str) -> String {
+        match &self.script {
+            Some(script) => format!("{code}-{script}"),
+            None => code.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Languages {
+    fs: Arc<dyn SiteFs>,
+    path: PathBuf,
+    registry: Arc<RwLock<Option<Arc<Registry>>>>,
+}
+
+impl Languages {
+    pub fn new(fs: Arc<dyn SiteFs>, path: PathBuf) -> Self {
+        Self { fs, path, registry: Arc::default() }
+    }
+
+    fn load(&self) -> Result<Arc<Registry>, LanguagesError> {
+        if let Some(registry) =
+            &*self.registry.read().unwrap_or_else(|error| error.into_inner())
+        {
+            return Ok(registry.clone());
+        }
+        let registry = match self.fs.read_to_string(&self.path) {
+            Ok(code) => toml::from_str(&code).map_err(|error| {
+                LanguagesError::Toml(self.path.clone(), error)
+            })?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                BTreeMap::new()
+            },
+            Err(error) => Err(LanguagesError::Io(self.path.clone(), error))?,
+        };
+        let registry = Arc::new(registry);
+        *self.registry.write().unwrap_or_else(|error| error.into_inner()) =
+            Some(registry.clone());
+        Ok(registry)
+    }
+
+    pub fn get(&self, code: &str) -> Result<Option<Language>, LanguagesError> {
+        Ok(self.load()?.get(code).cloned())
+    }
+}
+
+args! {
+    positional ["code"];
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct LangNameArgs<'a> {
+        /// language code, as in languages.toml
+        code: &'a str,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LangNameFn {
+    languages: Languages,
+}
+
+impl LangNameFn {
+    pub fn new(languages: Languages) -> Self {
+        Self { languages }
+    }
+}
+
+impl Function for LangNameFn {
+    type Args<'a> = LangNameArgs<'a>;
+    type Output = String;
+    type Error = LangNameError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let language = self
+            .languages
+            .get(args.code)?
+            .ok_or_else(|| LangNameError::UnknownLanguage(args.code.into()))?;
+        Ok(language.name)
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "{{# display name of a language in the language registry \
+             #}}\nlangname(\n{}\n) -> String",
+            LangNameArgs::doc()
+        )
+    }
+}
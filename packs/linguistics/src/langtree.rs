@@ -0,0 +1,113 @@
+use std::fmt::{self, Write as _};
+
+use lin_ssg_core::{escape_html, ArgError, ArgParser, Args, Function, PageCtx};
+use thiserror::Error;
+
+use crate::tree::{self, DepthError, Tree};
+
+pub const EXTINCT_MARKER: char = '†';
+
+#[derive(Debug, Error)]
+pub enum LangTreeError {
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+    #[error("Language tree node {} is misplaced", .0)]
+    BadDepth(usize, #[source] DepthError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LangTreeArgs<'a> {
+    nodes: Vec<&'a str>,
+    caption: Option<&'a str>,
+}
+
+impl<'a> Args<'a> for LangTreeArgs<'a> {
+    const POSITIONAL: &'static [&'static str] = &["nodes"];
+
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let nodes = args.retrive_arg("nodes")?;
+        let caption = args.retrive_arg_with_default("cap", || None)?;
+        Ok(Self { nodes, caption })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LangTreeFn;
+
+impl LangTreeFn {
+    fn render_children(
+        &self,
+        buf: &mut String,
+        tree: &Tree<&str>,
+        children: &[usize],
+    ) -> Result<(), LangTreeError> {
+        write!(buf, "<ul>")?;
+        for &child in children {
+            let node = tree.node(child);
+            let name = node.value.trim();
+            write!(buf, "<li><span class=\"langtree-node")?;
+            if name.starts_with(EXTINCT_MARKER) {
+                write!(buf, " langtree-extinct")?;
+            }
+            write!(buf, "\">{}</span>", escape_html(name))?;
+            if !node.children.is_empty() {
+                self.render_children(buf, tree, &node.children)?;
+            }
+            write!(buf, "</li>")?;
+        }
+        write!(buf, "</ul>")?;
+        Ok(())
+    }
+}
+
+impl Function for LangTreeFn {
+    type Args<'a> = LangTreeArgs<'a>;
+    type Output = String;
+    type Error = LangTreeError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let mut tree = Tree::new();
+        for (i, node) in args.nodes.iter().enumerate() {
+            let (depth, name) = tree::split_depth(node);
+            tree.push(depth, name)
+                .map_err(|error| LangTreeError::BadDepth(i + 1, error))?;
+        }
+
+        let mut buf = String::new();
+        write!(buf, "<figure class=\"langtree\">")?;
+        self.render_children(&mut buf, &tree, tree.roots())?;
+        if let Some(caption) = args.caption {
+            write!(buf, "<figcaption>{}</figcaption>", escape_html(caption))?;
+        }
+        write!(buf, "</figure>")?;
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        "{# genetic classification tree of languages #}
+        langtree(
+            {# language names in pre-order, each prefixed with one > per
+                level below the root, e.g.
+                [\"Indo-European\", \">Germanic\", \">>English\",
+                \">Italic\", \">>†Latin\"]; names starting with † are
+                marked as extinct
+            #}
+            nodes:array of string,
+            {# caption of the figure #}
+            cap:string?
+        ) -> String "
+            .to_owned()
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
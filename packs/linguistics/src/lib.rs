@@ -1,15 +1,186 @@
-use lin_ssg_core::LinSsg;
-use transc::TranscFn;
+use std::{path::PathBuf, sync::Arc};
 
+pub use abbr::{
+    AbbreviationsError,
+    GlossAbbreviations,
+    DEFAULT_ABBREVIATIONS_PATH,
+    LEIPZIG,
+};
+use abbr::{GlossAbbrFn, GlossAbbrTableFn, SiteAbbreviations};
+use bidi::BidiFn;
+pub use bidi::{Direction, VERTICAL_CLASS};
+use etym::EtymFn;
+use features::FeaturesFn;
+pub use features::{
+    FeatureMatrixError,
+    FeatureTable,
+    Features,
+    FeaturesError,
+    DEFAULT_FEATURES_PATH,
+};
+use gloss::{GlossFn, GlossLatexFn};
+use ipa::IpaCheckFn;
+use lang::LangNameFn;
+pub use lang::{
+    LangNameError,
+    Language,
+    Languages,
+    LanguagesError,
+    DEFAULT_LANGUAGES_PATH,
+};
+use langtree::LangTreeFn;
+use lin_ssg_core::{LinSsg, Pack};
+use ruby::RubyFn;
+pub use syntax_tree::SyntaxTreeError;
+use syntax_tree::SyntaxTreeFn;
+pub use tableau::TableauError;
+use tableau::TableauFn;
+pub use transc::TranscriptionType;
+use transc::{AttestedTester, SiteTables, TranscFn};
+
+mod abbr;
+mod bidi;
+mod etym;
+mod features;
+mod gloss;
+mod ipa;
+mod lang;
+mod langtree;
+mod ruby;
+mod syntax_tree;
+mod tableau;
 mod transc;
+mod tree;
+
+pub const PACK_NAME: &str = "linguistics";
+
+#[derive(Debug, Clone)]
+pub struct LinguisticsPack {
+    abbreviations: Arc<GlossAbbreviations>,
+    abbreviations_path: PathBuf,
+    languages_path: PathBuf,
+    features_path: PathBuf,
+}
+
+impl LinguisticsPack {
+    pub fn new(abbreviations: GlossAbbreviations) -> Self {
+        Self {
+            abbreviations: Arc::new(abbreviations),
+            abbreviations_path: PathBuf::from(DEFAULT_ABBREVIATIONS_PATH),
+            languages_path: PathBuf::from(DEFAULT_LANGUAGES_PATH),
+            features_path: PathBuf::from(DEFAULT_FEATURES_PATH),
+        }
+    }
+
+    pub fn with_abbreviations(
+        mut self,
+        abbreviations_path: impl Into<PathBuf>,
+    ) -> Self {
+        self.abbreviations_path = abbreviations_path.into();
+        self
+    }
+
+    pub fn with_languages(
+        mut self,
+        languages_path: impl Into<PathBuf>,
+    ) -> Self {
+        self.languages_path = languages_path.into();
+        self
+    }
+
+    pub fn with_features(mut self, features_path: impl Into<PathBuf>) -> Self {
+        self.features_path = features_path.into();
+        self
+    }
+}
+
+impl Default for LinguisticsPack {
+    fn default() -> Self {
+        Self::new(GlossAbbreviations::leipzig())
+    }
+}
+
+impl Pack for LinguisticsPack {
+    fn name(&self) -> &str {
+        PACK_NAME
+    }
+
+    fn install(&self, ssg: &mut LinSsg) {
+        let fs = ssg.config().shared_fs();
+        install_shared(
+            ssg,
+            SiteAbbreviations::new(
+                fs.clone(),
+                self.abbreviations_path.clone(),
+                self.abbreviations.clone(),
+            ),
+            Languages::new(fs.clone(), self.languages_path.clone()),
+            Features::new(fs, self.features_path.clone()),
+        );
+    }
+
+    fn doc(&self) -> String {
+        "transcriptions, interlinear glosses, etymologies, language trees, \
+         syntax trees, ruby annotations, feature matrices and OT tableaux"
+            .to_owned()
+    }
+}
 
 pub fn install(ssg: &mut LinSsg) {
+    install_with(ssg, GlossAbbreviations::leipzig());
+}
+
+pub fn install_with(ssg: &mut LinSsg, abbreviations: GlossAbbreviations) {
+    let fs = ssg.config().shared_fs();
+    install_shared(
+        ssg,
+        SiteAbbreviations::new(
+            fs.clone(),
+            PathBuf::from(DEFAULT_ABBREVIATIONS_PATH),
+            Arc::new(abbreviations),
+        ),
+        Languages::new(fs.clone(), PathBuf::from(DEFAULT_LANGUAGES_PATH)),
+        Features::new(fs, PathBuf::from(DEFAULT_FEATURES_PATH)),
+    );
+}
+
+fn install_shared(
+    ssg: &mut LinSsg,
+    abbreviations: SiteAbbreviations,
+    languages: Languages,
+    features: Features,
+) {
     ssg.register_symbol("Phonemic");
     ssg.register_symbol("Phonetic");
     ssg.register_symbol("Graphemic");
     ssg.register_symbol("GraphemicRaw");
     ssg.register_symbol("Morphophonemic");
-    ssg.register_const("GraRaw", "GraphemicRaw");
-    ssg.register_const("Morpho", "Morphophonemic");
-    ssg.register_fn("transc", TranscFn);
+    ssg.register_const("GraRaw", "GraphemicRaw")
+        .expect("string constants always serialize");
+    ssg.register_const("Morpho", "Morphophonemic")
+        .expect("string constants always serialize");
+    let table = ssg.linguinput_table();
+    let tables = SiteTables::new(table.clone(), ssg.named_linguinput_tables());
+    let transc = TranscFn::new(languages.clone(), tables.clone());
+    ssg.register_fn("transc", transc.clone());
+    ssg.register_tester("attested", AttestedTester);
+    ssg.register_fn("ipa_check", IpaCheckFn::new(table.clone()));
+    ssg.register_fn("etym", EtymFn::new(transc));
+    ssg.register_fn("langname", LangNameFn::new(languages.clone()));
+    ssg.register_fn("bidi", BidiFn);
+    ssg.register_fn("features", FeaturesFn::new(features, table.clone()));
+    ssg.register_fn("langtree", LangTreeFn);
+    ssg.register_fn("ruby", RubyFn::new(table.clone()));
+    ssg.register_fn("tableau", TableauFn::new(table.clone()));
+    ssg.register_fn("tree", SyntaxTreeFn::new(table.clone()));
+    ssg.register_fn(
+        "gloss_abbreviations",
+        GlossAbbrTableFn::new(abbreviations.clone()),
+    );
+    ssg.register_fn(
+        "gloss",
+        GlossFn::new(abbreviations.clone(), languages.clone(), tables.clone()),
+    );
+    ssg.register_latex_fn("gloss", GlossLatexFn::new(languages, tables));
+    ssg.register_fn("glossabbr", GlossAbbrFn::new(abbreviations));
 }
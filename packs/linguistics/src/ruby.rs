@@ -0,0 +1,111 @@
+use std::{
+    fmt::{self, Write as _},
+    sync::Arc,
+};
+
+use lin_ssg_core::{args, escape_html, Args, Function, PageCtx};
+use lin_ssg_linguinput::{encode_with, EncodingError, Table};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RubyError {
+    #[error("Could not encode to unicode: {}", .0)]
+    Encoding(
+        #[from]
+        #[source]
+        EncodingError,
+    ),
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+    #[error(
+        "Ruby annotation has {} segments but the base text has {}",
+        .found,
+        .expected
+    )]
+    Misaligned { expected: usize, found: usize },
+}
+
+args! {
+    positional ["in", "rt"];
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct RubyArgs<'a> {
+        /// base text; when split by spaces, each segment gets the matching
+        /// segment of rt
+        base: &'a str => "in",
+        /// reading or transliteration, with unicode input
+        annotation: &'a str => "rt",
+        /// language code of the base text
+        lang: Option<&'a str> => "lg" = None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RubyFn {
+    table: Arc<Table>,
+}
+
+impl RubyFn {
+    pub fn new(table: Arc<Table>) -> Self {
+        Self { table }
+    }
+}
+
+impl Function for RubyFn {
+    type Args<'a> = RubyArgs<'a>;
+    type Output = String;
+    type Error = RubyError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let annotation = encode_with(args.annotation, &self.table)?;
+        let base_segments: Vec<_> = args.base.split_whitespace().collect();
+        let annotation_segments: Vec<_> =
+            annotation.split_whitespace().collect();
+        let pairs = if base_segments.len() == 1 {
+            vec![(args.base.trim(), annotation.trim())]
+        } else if base_segments.len() == annotation_segments.len() {
+            base_segments.into_iter().zip(annotation_segments).collect()
+        } else {
+            Err(RubyError::Misaligned {
+                expected: base_segments.len(),
+                found: annotation_segments.len(),
+            })?
+        };
+
+        let mut buf = String::new();
+        write!(buf, "<ruby")?;
+        if let Some(lang) = args.lang {
+            write!(buf, " lang=\"{}\"", escape_html(lang))?;
+        }
+        write!(buf, ">")?;
+        for (base, annotation) in pairs {
+            write!(
+                buf,
+                "{}<rp>(</rp><rt>{}</rt><rp>)</rp>",
+                escape_html(base),
+                escape_html(annotation)
+            )?;
+        }
+        write!(buf, "</ruby>")?;
+        Ok(buf)
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "{{# base text annotated with ruby readings #}}\nruby(\n{}\n) -> \
+             String",
+            RubyArgs::doc()
+        )
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    fmt::{self, Write as _},
+    sync::Arc,
+};
+
+use lin_ssg_core::{args, escape_html, verbatim, Args, Function, PageCtx};
+use lin_ssg_linguinput::{encode_with, EncodingError, Table};
+use thiserror::Error;
+
+const CHAR_WIDTH: f64 = 8.0;
+const FONT_SIZE: f64 = 14.0;
+const LEVEL_HEIGHT: f64 = 44.0;
+const NODE_GAP: f64 = 16.0;
+const PADDING: f64 = 8.0;
+// Lines stop short of the labels so they do not cross the text.
+const LINE_MARGIN: f64 = 4.0;
+
+#[derive(Debug, Error)]
+pub enum SyntaxTreeError {
+    #[error("Could not encode to unicode: {}", .0)]
+    Encoding(
+        #[from]
+        #[source]
+        EncodingError,
+    ),
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+    #[error("Tree must start with '[' at offset {}", .0)]
+    ExpectedOpen(usize),
+    #[error("Unclosed '[' at offset {}", .0)]
+    Unclosed(usize),
+    #[error("Unexpected text after the tree at offset {}", .0)]
+    Trailing(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    label: String,
+    leaf: bool,
+    children: Vec<Node>,
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.position ..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn word(&mut self) -> &'a str {
+        let rest = &self.input[self.position ..];
+        let len = rest
+            .find(|ch: char| ch.is_whitespace() || ch == '[' || ch == ']')
+            .unwrap_or(rest.len());
+        self.position += len;
+        &rest[.. len]
+    }
+
+    fn node(&mut self) -> Result<Node, SyntaxTreeError> {
+        self.skip_whitespace();
+        let start = self.position;
+        if !self.input[self.position ..].starts_with('[') {
+            Err(SyntaxTreeError::ExpectedOpen(start))?;
+        }
+        self.position += 1;
+        self.skip_whitespace();
+        let label = self.word().to_owned();
+        let mut children = Vec::new();
+        let mut words = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.input[self.position ..].chars().next() {
+                None => Err(SyntaxTreeError::Unclosed(start))?,
+                Some(']') => {
+                    self.position += 1;
+                    break;
+                },
+                Some('[') => {
+                    Self::flush_words(&mut words, &mut children);
+                    children.push(self.node()?);
+                },
+                Some(_) => words.push(self.word()),
+            }
+        }
+        Self::flush_words(&mut words, &mut children);
+        Ok(Node { label, leaf: false, children })
+    }
+
+    fn flush_words(words: &mut Vec<&str>, children: &mut Vec<Node>) {
+        if !words.is_empty() {
+            children.push(Node {
+                label: words.join(" "),
+                leaf: true,
+                children: Vec::new(),
+            });
+            words.clear();
+        }
+    }
+
+    fn parse(input: &'a str) -> Result<Node, SyntaxTreeError> {
+        let mut this = Self { input, position: 0 };
+        let root = this.node()?;
+        this.skip_whitespace();
+        if this.position < input.len() {
+            Err(SyntaxTreeError::Trailing(this.position))?;
+        }
+        Ok(root)
+    }
+}
+
+fn label_width(label: &str) -> f64 {
+    label.chars().count() as f64 * CHAR_WIDTH
+}
+
+fn subtree_width(node: &Node) -> f64 {
+    let children = node.children.iter().map(subtree_width).sum::<f64>()
+        + NODE_GAP * node.children.len().saturating_sub(1) as f64;
+    children.max(label_width(&node.label))
+}
+
+struct Layout {
+    svg: String,
+    height: f64,
+}
+
+impl Layout {
+    // Returns the x of the node's center.
+    fn place(
+        &mut self,
+        node: &Node,
+        left: f64,
+        depth: usize,
+    ) -> Result<f64, fmt::Error> {
+        let width = subtree_width(node);
+        let children_width = width.min(
+            node.children.iter().map(subtree_width).sum::<f64>()
+                + NODE_GAP * node.children.len().saturating_sub(1) as f64,
+        );
+        let y = PADDING + FONT_SIZE + depth as f64 * LEVEL_HEIGHT;
+        self.height = self.height.max(y + PADDING);
+
+        let mut child_left = left + (width - children_width) / 2.0;
+        let mut centers = Vec::new();
+        for child in &node.children {
+            centers.push(self.place(child, child_left, depth + 1)?);
+            child_left += subtree_width(child) + NODE_GAP;
+        }
+        let x = match (centers.first(), centers.last()) {
+            (Some(first), Some(last)) => (first + last) / 2.0,
+            _ => left + width / 2.0,
+        };
+        for center in centers {
+            write!(
+                self.svg,
+                "<line x1=\"{x:.1}\" y1=\"{:.1}\" x2=\"{center:.1}\" \
+                 y2=\"{:.1}\" stroke=\"currentColor\"/>",
+                y + LINE_MARGIN,
+                y + LEVEL_HEIGHT - FONT_SIZE - LINE_MARGIN,
+            )?;
+        }
+        write!(
+            self.svg,
+            "<text x=\"{x:.1}\" y=\"{y:.1}\" class=\"{}\">{}</text>",
+            if node.leaf { "syntax-tree-leaf" } else { "syntax-tree-label" },
+            escape_html(&node.label)
+        )?;
+        Ok(x)
+    }
+}
+
+args! {
+    positional ["in"];
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct SyntaxTreeArgs<'a> {
+        /// labelled bracket notation, e.g.
+        /// "[S [NP Alice] [VP [V saw] [NP Bob]]]", linguinput codes allowed
+        input: &'a str => "in",
+        /// caption of the figure
+        caption: Option<&'a str> => "cap" = None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxTreeFn {
+    table: Arc<Table>,
+}
+
+impl SyntaxTreeFn {
+    pub fn new(table: Arc<Table>) -> Self {
+        Self { table }
+    }
+}
+
+impl Function for SyntaxTreeFn {
+    type Args<'a> = SyntaxTreeArgs<'a>;
+    type Output = String;
+    type Error = SyntaxTreeError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let input = encode_with(args.input, &self.table)?;
+        let root = Parser::parse(&input)?;
+        let mut layout = Layout { svg: String::new(), height: 0.0 };
+        layout.place(&root, PADDING, 0)?;
+        let width = subtree_width(&root) + 2.0 * PADDING;
+
+        let mut buf = String::new();
+        write!(buf, "<figure class=\"syntax-tree\">")?;
+        write!(
+            buf,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 \
+             {width:.1} {:.1}\" width=\"{width:.1}\" height=\"{:.1}\" \
+             role=\"img\" aria-label=\"{}\" font-size=\"{FONT_SIZE}\" \
+             text-anchor=\"middle\">",
+            layout.height,
+            layout.height,
+            escape_html(input.trim())
+        )?;
+        buf.push_str(&layout.svg);
+        write!(buf, "</svg>")?;
+        if let Some(caption) = args.caption {
+            write!(buf, "<figcaption>{}</figcaption>", escape_html(caption))?;
+        }
+        write!(buf, "</figure>")?;
+        Ok(verbatim(&buf))
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "{{# syntax tree drawn as SVG from labelled bracket notation; \
+             words directly inside a bracket become one leaf \
+             #}}\ntree(\n{}\n) -> String",
+            SyntaxTreeArgs::doc()
+        )
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
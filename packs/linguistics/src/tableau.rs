@@ -0,0 +1,272 @@
+use std::{
+    fmt::{self, Write as _},
+    sync::Arc,
+};
+
+use lin_ssg_core::{
+    escape_html,
+    verbatim,
+    ArgError,
+    ArgParser,
+    Args,
+    Function,
+    PageCtx,
+};
+use lin_ssg_linguinput::{encode_with, EncodingError, Table};
+use serde_json::Value;
+use thiserror::Error;
+
+pub const CANDIDATE_FIELD_SEPARATOR: char = '|';
+pub const WINNER_MARK: &str = "☞";
+pub const VIOLATION_MARK: char = '*';
+pub const FATAL_MARK: char = '!';
+
+#[derive(Debug, Error)]
+pub enum TableauError {
+    #[error("Could not encode to unicode: {}", .0)]
+    Encoding(
+        #[from]
+        #[source]
+        EncodingError,
+    ),
+    #[error("Formatting error")]
+    Fmt(
+        #[from]
+        #[source]
+        fmt::Error,
+    ),
+    #[error("Tableau has no candidates")]
+    NoCandidates,
+    #[error(
+        "Candidate {} must be a string \"form|violations...\" or an object \
+         with form and viol",
+        .0
+    )]
+    BadCandidate(usize),
+    #[error(
+        "Candidate {} has {} violation counts but there are {} constraints",
+        .candidate,
+        .found,
+        .expected
+    )]
+    Misaligned { candidate: usize, expected: usize, found: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableauArgs<'a> {
+    input: &'a str,
+    constraints: Vec<&'a str>,
+    candidates: &'a [Value],
+}
+
+impl<'a> Args<'a> for TableauArgs<'a> {
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let input = args.retrive_arg("in")?;
+        let constraints = args.retrive_arg("cons")?;
+        let candidates = args.retrive_arg("cands")?;
+        Ok(Self { input, constraints, candidates })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Candidate {
+    form: String,
+    violations: Vec<u64>,
+}
+
+impl Candidate {
+    fn parse(
+        number: usize,
+        value: &Value,
+        constraints: usize,
+        table: &Table,
+    ) -> Result<Self, TableauError> {
+        let bad = || TableauError::BadCandidate(number);
+        let (form, violations) = match value {
+            Value::String(candidate) => {
+                let mut fields =
+                    candidate.split(CANDIDATE_FIELD_SEPARATOR).map(str::trim);
+                let form = fields.next().unwrap_or_default().to_owned();
+                let violations = fields
+                    .map(|count| {
+                        if count.is_empty() {
+                            Ok(0)
+                        } else {
+                            count.parse().map_err(|_| bad())
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                (form, violations)
+            },
+            Value::Object(fields) => {
+                let form = fields
+                    .get("form")
+                    .and_then(Value::as_str)
+                    .ok_or_else(bad)?
+                    .to_owned();
+                let violations = fields
+                    .get("viol")
+                    .and_then(Value::as_array)
+                    .ok_or_else(bad)?
+                    .iter()
+                    .map(|count| count.as_u64().ok_or_else(bad))
+                    .collect::<Result<Vec<_>, _>>()?;
+                (form, violations)
+            },
+            _ => Err(bad())?,
+        };
+        if violations.len() != constraints {
+            Err(TableauError::Misaligned {
+                candidate: number,
+                expected: constraints,
+                found: violations.len(),
+            })?;
+        }
+        Ok(Self { form: encode_with(&form, table)?, violations })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TableauFn {
+    table: Arc<Table>,
+}
+
+impl TableauFn {
+    pub fn new(table: Arc<Table>) -> Self {
+        Self { table }
+    }
+
+    fn render_violations(
+        buf: &mut String,
+        count: u64,
+        fatal_at: Option<u64>,
+    ) -> fmt::Result {
+        let mut label = match count {
+            0 => String::new(),
+            1 => "1 violation".to_owned(),
+            _ => format!("{count} violations"),
+        };
+        if fatal_at.is_some() {
+            label.push_str(", fatal");
+        }
+        if label.is_empty() {
+            return Ok(());
+        }
+        write!(buf, "<span aria-label=\"{label}\">")?;
+        for i in 0 .. count {
+            buf.push(VIOLATION_MARK);
+            if Some(i + 1) == fatal_at {
+                buf.push(FATAL_MARK);
+            }
+        }
+        write!(buf, "</span>")
+    }
+}
+
+impl Function for TableauFn {
+    type Args<'a> = TableauArgs<'a>;
+    type Output = String;
+    type Error = TableauError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let candidates = args
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(i, candidate)| {
+                Candidate::parse(
+                    i + 1,
+                    candidate,
+                    args.constraints.len(),
+                    &self.table,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // Constraints are strictly ranked in the given order, so the winner
+        // is the candidate with the lexicographically least violations.
+        let winner = candidates
+            .iter()
+            .min_by(|a, b| a.violations.cmp(&b.violations))
+            .ok_or(TableauError::NoCandidates)?;
+        let fatal: Vec<_> = candidates
+            .iter()
+            .map(|candidate| {
+                candidate
+                    .violations
+                    .iter()
+                    .zip(&winner.violations)
+                    .position(|(count, best)| count > best)
+            })
+            .collect();
+        let decided = fatal.iter().flatten().copied().max();
+
+        let mut buf = String::new();
+        write!(buf, "<table class=\"tableau\"><thead><tr>")?;
+        write!(
+            buf,
+            "<th scope=\"col\" class=\"tableau-input\">{}</th>",
+            escape_html(&encode_with(args.input, &self.table)?)
+        )?;
+        for constraint in &args.constraints {
+            write!(buf, "<th scope=\"col\">{}</th>", escape_html(constraint))?;
+        }
+        write!(buf, "</tr></thead><tbody>")?;
+        for (candidate, fatal) in candidates.iter().zip(fatal) {
+            let is_winner = candidate.violations == winner.violations;
+            write!(
+                buf,
+                "<tr{}><th scope=\"row\">",
+                if is_winner { " class=\"tableau-winner\"" } else { "" }
+            )?;
+            if is_winner {
+                write!(
+                    buf,
+                    "<span class=\"tableau-finger\" role=\"img\" \
+                     aria-label=\"optimal\">{WINNER_MARK}</span> "
+                )?;
+            }
+            write!(buf, "{}</th>", escape_html(&candidate.form))?;
+            let shaded_after = if is_winner { decided } else { fatal };
+            for (i, &count) in candidate.violations.iter().enumerate() {
+                if shaded_after.is_some_and(|shaded_after| i > shaded_after) {
+                    write!(buf, "<td class=\"tableau-shaded\">")?;
+                } else {
+                    write!(buf, "<td>")?;
+                }
+                let fatal_at =
+                    (fatal == Some(i)).then(|| winner.violations[i] + 1);
+                Self::render_violations(&mut buf, count, fatal_at)?;
+                write!(buf, "</td>")?;
+            }
+            write!(buf, "</tr>")?;
+        }
+        write!(buf, "</tbody></table>")?;
+        Ok(verbatim(&buf))
+    }
+
+    fn doc(&self) -> String {
+        "{# Optimality Theory tableau, constraints strictly ranked from left \
+         to right; the winner, fatal violations and shading are computed #}
+        tableau(
+            {# input form, linguinput codes allowed #}
+            in:string,
+            {# constraint names, from highest to lowest ranked #}
+            cons:array of string,
+            {# candidates, each one either:
+                - a string \"form|violations|...\", one violation count per
+                  constraint, empty meaning 0, e.g. \"ka|0|1\"
+                - an object with form and viol, the array of counts
+            #}
+            cands:array
+        ) -> String "
+            .to_owned()
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
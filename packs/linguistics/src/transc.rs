@@ -1,13 +1,38 @@
-use lin_ssg_core::{Arg, ArgError, ArgParser, Args, Function};
+use std::{collections::BTreeMap, convert::Infallible, sync::Arc};
+
+use lin_ssg_core::{
+    args,
+    escape_html,
+    strip_verbatim,
+    verbatim,
+    Arg,
+    Args,
+    Function,
+    PageCtx,
+    Tester,
+};
 use lin_ssg_linguinput::{
     Display,
     DisplayFormat,
     Encode,
     Encoder,
     EncodingError,
+    Syntax,
+    Table,
+    TableInitError,
+    DEFAULT_TABLE_NAME,
 };
+use serde::Deserialize;
 use thiserror::Error;
 
+use crate::{
+    bidi::{Direction, VERTICAL_CLASS},
+    ipa::{self, IpaError},
+    lang::{Languages, LanguagesError},
+};
+
+pub const RECONSTRUCTED_MARKER: char = '*';
+
 #[derive(Debug, Error)]
 pub enum TranscriptionError {
     #[error("Could not encode to unicode: {}", .0)]
@@ -16,9 +41,21 @@ pub enum TranscriptionError {
         #[source]
         EncodingError,
     ),
+    #[error(transparent)]
+    Ipa(#[from] IpaError),
+    #[error("Could not select linguinput table")]
+    Table(
+        #[from]
+        #[source]
+        TableInitError,
+    ),
+    #[error(transparent)]
+    Languages(#[from] LanguagesError),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize,
+)]
 pub enum TranscriptionType {
     GraphemicRaw,
     Graphemic,
@@ -27,6 +64,18 @@ pub enum TranscriptionType {
     Phonetic,
 }
 
+impl TranscriptionType {
+    pub fn class_name(self) -> &'static str {
+        match self {
+            Self::GraphemicRaw => "transc-graphemic-raw",
+            Self::Graphemic => "transc-graphemic",
+            Self::Morphophonemic => "transc-morphophonemic",
+            Self::Phonemic => "transc-phonemic",
+            Self::Phonetic => "transc-phonetic",
+        }
+    }
+}
+
 impl<'a> Arg<'a> for TranscriptionType {
     fn from_json_ref(json: &'a serde_json::Value) -> Option<Self> {
         Some(match <&str>::from_json_ref(json)? {
@@ -44,29 +93,89 @@ impl<'a> Arg<'a> for TranscriptionType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct TranscArgs<'a> {
-    input: &'a str,
-    lang: Option<&'a str>,
-    ty: TranscriptionType,
-    attested: bool,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InputSyntax(pub Syntax);
+
+impl<'a> Arg<'a> for InputSyntax {
+    fn from_json_ref(json: &'a serde_json::Value) -> Option<Self> {
+        Some(Self(match <&str>::from_json_ref(json)? {
+            "Braces" => Syntax::Braces,
+            "XSampa" => Syntax::XSampa,
+            _ => None?,
+        }))
+    }
+
+    fn json_type() -> String {
+        "input-syntax".to_owned()
+    }
 }
 
-impl<'a> Args<'a> for TranscArgs<'a> {
-    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
-        let input = args.retrive_arg("in")?;
-        let ty: TranscriptionType = args
-            .retrive_arg_with_default("ty", || {
-                TranscriptionType::GraphemicRaw
-            })?;
-        let lang = args.retrive_arg_with_default("lg", || None)?;
-        let attested = args.retrive_arg_with_default("att", || true)?;
-        Ok(Self { input, lang, ty, attested })
+args! {
+    positional ["in"];
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct TranscArgs<'a> {
+        /// input
+        input: &'a str => "in",
+        /// language code, if not agnostic; conventions registered for it in
+        /// languages.toml apply
+        lang: Option<&'a str> => "lg" = None,
+        /// transcription type: GraphemicRaw (default unless the language
+        /// sets one), Graphemic, Phonemic, Phonetic or Morphophonemic
+        ty: Option<TranscriptionType> = None,
+        /// attested (true, default) or reconstructed (false)?
+        attested: bool => "att" = true,
+        /// validate phonemic/phonetic transcriptions as IPA? default false
+        check: bool => "chk" = false,
+        /// name of the linguinput table, default "default" unless the
+        /// language sets one
+        table: Option<&'a str> => "tbl" = None,
+        /// input convention: Braces, codes between braces e.g. {e}
+        /// (default), or XSampa, plain X-SAMPA e.g. E
+        syntax: InputSyntax = InputSyntax(Syntax::Braces),
+        /// text direction: ltr, rtl or auto; graphemic transcriptions default
+        /// to the language's, the others to ltr
+        dir: Option<Direction> = None,
+        /// wrap in a styled span (true, default) or emit bare text (false)?
+        html: bool = true,
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct TranscFn;
+// The site's default table and its named ones, as configured on `LinSsg`.
+#[derive(Debug, Clone)]
+pub struct SiteTables {
+    default: Arc<Table>,
+    named: Arc<BTreeMap<String, Table>>,
+}
+
+impl SiteTables {
+    pub fn new(
+        default: Arc<Table>,
+        named: Arc<BTreeMap<String, Table>>,
+    ) -> Self {
+        Self { default, named }
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Table, TableInitError> {
+        if name == DEFAULT_TABLE_NAME {
+            return Ok(&self.default);
+        }
+        self.named
+            .get(name)
+            .ok_or_else(|| TableInitError::UnknownTable(name.to_owned()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TranscFn {
+    languages: Languages,
+    tables: SiteTables,
+}
+
+impl TranscFn {
+    pub fn new(languages: Languages, tables: SiteTables) -> Self {
+        Self { languages, tables }
+    }
+}
 
 impl Function for TranscFn {
     type Args<'a> = TranscArgs<'a>;
@@ -76,67 +185,138 @@ impl Function for TranscFn {
     fn call<'a>(
         &self,
         args: Self::Args<'a>,
+        _ctx: &PageCtx,
     ) -> Result<Self::Output, Self::Error> {
+        let language = match args.lang {
+            Some(code) => self.languages.get(code)?,
+            None => None,
+        };
+        let ty = args
+            .ty
+            .or(language.as_ref().and_then(|language| language.transcription))
+            .unwrap_or(TranscriptionType::GraphemicRaw);
+        let table = args
+            .table
+            .or(language
+                .as_ref()
+                .and_then(|language| language.table.as_deref()))
+            .unwrap_or(DEFAULT_TABLE_NAME);
+        let table = self.tables.get(table)?;
+        let mut form = String::new();
+        let mut encoder = Encoder::with_table(&mut form, table);
+        encoder.set_syntax(args.syntax.0);
+        Display(args.input).encode(DisplayFormat, &mut encoder)?;
+        encoder.finish()?;
+        if args.check
+            && matches!(
+                ty,
+                TranscriptionType::Phonemic | TranscriptionType::Phonetic
+            )
+        {
+            ipa::validate(&form)?;
+        }
+        let (open, close) = match ty {
+            TranscriptionType::GraphemicRaw => ("", ""),
+            TranscriptionType::Graphemic => ("⟨", "⟩"),
+            TranscriptionType::Morphophonemic => ("⫽", "⫽"),
+            TranscriptionType::Phonemic => ("/", "/"),
+            TranscriptionType::Phonetic => ("[", "]"),
+        };
         let mut buf = String::new();
-        let mut encoder = Encoder::new(&mut buf)?;
         if !args.attested {
-            encoder.push('*')?;
-        }
-        match args.ty {
-            TranscriptionType::GraphemicRaw => (),
-            TranscriptionType::Graphemic => {
-                encoder.push_str("{<}")?;
-            },
-            TranscriptionType::Morphophonemic => {
-                encoder.push_str("{//}")?;
-            },
-            TranscriptionType::Phonemic => {
-                encoder.push('/')?;
-            },
-            TranscriptionType::Phonetic => {
-                encoder.push('[')?;
-            },
+            buf.push(RECONSTRUCTED_MARKER);
         }
-        Display(args.input).encode(DisplayFormat, &mut encoder)?;
-        match args.ty {
-            TranscriptionType::GraphemicRaw => (),
-            TranscriptionType::Graphemic => {
-                encoder.push_str("{>}")?;
-            },
-            TranscriptionType::Morphophonemic => {
-                encoder.push_str("{//}")?;
-            },
-            TranscriptionType::Phonemic => {
-                encoder.push('/')?;
-            },
-            TranscriptionType::Phonetic => {
-                encoder.push(']')?;
-            },
+        buf.push_str(open);
+        buf.push_str(&form);
+        buf.push_str(close);
+        let mut html = escape_html(&buf);
+        if args.html {
+            let mut class = format!("transc {}", ty.class_name());
+            if !args.attested {
+                class.push_str(" transc-reconstructed");
+            }
+            let mut attributes = String::new();
+            if let Some(code) = args.lang {
+                let tag = language
+                    .as_ref()
+                    .map_or(code.to_owned(), |language| language.tag(code));
+                attributes
+                    .push_str(&format!(" lang=\"{}\"", escape_html(&tag)));
+            }
+            let graphemic = matches!(
+                ty,
+                TranscriptionType::GraphemicRaw | TranscriptionType::Graphemic
+            );
+            let language_dir =
+                language.as_ref().and_then(|language| language.dir);
+            // Phonological transcriptions are written in IPA, which runs
+            // left to right even for right-to-left languages.
+            let dir = args.dir.or(match language_dir {
+                Some(Direction::Rtl) if !graphemic => Some(Direction::Ltr),
+                dir if graphemic => dir,
+                _ => None,
+            });
+            if let Some(dir) = dir {
+                attributes.push_str(&format!(" dir=\"{dir}\""));
+            }
+            if let Some(language) = &language {
+                if let Some(font_class) = &language.class {
+                    class.push(' ');
+                    class.push_str(font_class);
+                }
+                if language.vertical && graphemic {
+                    class.push(' ');
+                    class.push_str(VERTICAL_CLASS);
+                }
+            }
+            html = format!(
+                "<span class=\"{}\"{attributes}>{html}</span>",
+                escape_html(&class)
+            );
         }
-        encoder.finish()?;
-        Ok(buf)
+        // Apostrophes and colons are IPA symbols here, so the typography pass
+        // must leave transcriptions untouched.
+        Ok(verbatim(&html))
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "{{# linguistic transcriptions with unicode input \
+             #}}\ntransc(\n{}\n) -> String",
+            TranscArgs::doc()
+        )
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AttestedTester;
+
+impl Tester for AttestedTester {
+    type Input<'a> = &'a str;
+    type Args<'a> = ();
+    type Error = Infallible;
+
+    fn test<'a>(
+        &self,
+        input: Self::Input<'a>,
+        _args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<bool, Self::Error> {
+        let form = strip_verbatim(input);
+        let form = form
+            .strip_prefix("<span")
+            .and_then(|span| span.split_once('>'))
+            .map_or(form, |(_, form)| form);
+        Ok(!form.starts_with(RECONSTRUCTED_MARKER))
     }
 
     fn doc(&self) -> String {
-        "{# linguistic transcriptions with unicode input #}
-        transc(
-            {# input #}
-            in:string,
-            {# language code, if not agnostic #}
-            lg:string?,
-            {# transcription type:
-                - GraphemicRaw / GraRaw  (default)
-                - Graphemic
-                - Phonemic
-                - Phonetic
-                - Morphophonemic / Morpho
-            #}
-            ty:string?,
-            {# attested (true) or reconstructed (false)?
-                default false
-            #}
-            att:bool?
-        ) -> String "
+        "{# whether a form is attested, i.e. not marked with * #}
+        form is attested "
             .to_owned()
     }
 }
@@ -0,0 +1,70 @@
+use thiserror::Error;
+
+pub const DEPTH_MARKER: char = '>';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("Node at depth {} has no parent at depth {}", .depth, .depth - 1)]
+pub struct DepthError {
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeNode<T> {
+    pub value: T,
+    pub children: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tree<T> {
+    nodes: Vec<TreeNode<T>>,
+    roots: Vec<usize>,
+    ancestors: Vec<usize>,
+}
+
+impl<T> Default for Tree<T> {
+    fn default() -> Self {
+        Self { nodes: Vec::new(), roots: Vec::new(), ancestors: Vec::new() }
+    }
+}
+
+impl<T> Tree<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn next_depth(&self) -> usize {
+        self.ancestors.len()
+    }
+
+    pub fn push(
+        &mut self,
+        depth: usize,
+        value: T,
+    ) -> Result<usize, DepthError> {
+        if depth > self.ancestors.len() {
+            Err(DepthError { depth })?;
+        }
+        let index = self.nodes.len();
+        self.ancestors.truncate(depth);
+        match self.ancestors.last() {
+            Some(&parent) => self.nodes[parent].children.push(index),
+            None => self.roots.push(index),
+        }
+        self.ancestors.push(index);
+        self.nodes.push(TreeNode { value, children: Vec::new() });
+        Ok(index)
+    }
+
+    pub fn roots(&self) -> &[usize] {
+        &self.roots
+    }
+
+    pub fn node(&self, index: usize) -> &TreeNode<T> {
+        &self.nodes[index]
+    }
+}
+
+pub fn split_depth(input: &str) -> (usize, &str) {
+    let trimmed = input.trim_start_matches(DEPTH_MARKER);
+    (input.len() - trimmed.len(), trimmed)
+}
@@ -0,0 +1,198 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use lin_ssg_core::{
+    args,
+    ArgError,
+    ArgParser,
+    Args,
+    Function,
+    PageCtx,
+    SiteFs,
+};
+use lin_ssg_linguinput::{encode_with, EncodingError, Table};
+use thiserror::Error;
+
+use crate::{
+    rules::RuleSet,
+    RuleSyntaxError,
+    RULES_EXTENSION,
+    TRANSLIT_EXTENSION,
+};
+
+#[derive(Debug, Error)]
+pub enum SoundChangeError {
+    #[error("Could not encode to unicode: {}", .0)]
+    Encoding(
+        #[from]
+        #[source]
+        EncodingError,
+    ),
+    #[error("Could not read rules file {}", .0.display())]
+    Io(PathBuf, #[source] io::Error),
+    #[error("Invalid rules file {}", .0.display())]
+    Syntax(PathBuf, #[source] RuleSyntaxError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SoundChangeArgs<'a> {
+    word: &'a str,
+    rules: &'a str,
+}
+
+impl<'a> Args<'a> for SoundChangeArgs<'a> {
+    const POSITIONAL: &'static [&'static str] = &["word"];
+
+    fn parse(args: &mut ArgParser<'a>) -> Result<Self, ArgError> {
+        let word = args.retrive_arg("word")?;
+        let rules = args.retrive_arg("rules")?;
+        Ok(Self { word, rules })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RuleCache {
+    fs: Arc<dyn SiteFs>,
+    table: Arc<Table>,
+    rules_dir: PathBuf,
+    extension: &'static str,
+    cache: Arc<RwLock<HashMap<String, Arc<RuleSet>>>>,
+}
+
+impl RuleCache {
+    fn new(
+        fs: Arc<dyn SiteFs>,
+        table: Arc<Table>,
+        rules_dir: PathBuf,
+        extension: &'static str,
+    ) -> Self {
+        Self { fs, table, rules_dir, extension, cache: Arc::default() }
+    }
+
+    fn encode(&self, input: &str) -> Result<String, EncodingError> {
+        encode_with(input, &self.table)
+    }
+
+    fn load(&self, name: &str) -> Result<Arc<RuleSet>, SoundChangeError> {
+        if let Some(rule_set) = self
+            .cache
+            .read()
+            .unwrap_or_else(|error| error.into_inner())
+            .get(name)
+        {
+            return Ok(rule_set.clone());
+        }
+        let mut path = self.rules_dir.join(name);
+        path.set_extension(self.extension);
+        let code = self
+            .fs
+            .read_to_string(&path)
+            .map_err(|error| SoundChangeError::Io(path.clone(), error))?;
+        let rule_set = RuleSet::parse(&self.encode(&code)?)
+            .map_err(|error| SoundChangeError::Syntax(path, error))?;
+        let rule_set = Arc::new(rule_set);
+        self.cache
+            .write()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(name.to_owned(), rule_set.clone());
+        Ok(rule_set)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SoundChangeFn {
+    rules: RuleCache,
+}
+
+impl SoundChangeFn {
+    pub fn new(
+        fs: Arc<dyn SiteFs>,
+        table: Arc<Table>,
+        rules_dir: PathBuf,
+    ) -> Self {
+        Self { rules: RuleCache::new(fs, table, rules_dir, RULES_EXTENSION) }
+    }
+}
+
+impl Function for SoundChangeFn {
+    type Args<'a> = SoundChangeArgs<'a>;
+    type Output = String;
+    type Error = SoundChangeError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let rule_set = self.rules.load(args.rules)?;
+        Ok(rule_set.apply(&self.rules.encode(args.word)?))
+    }
+
+    fn doc(&self) -> String {
+        "{# derive a form by applying a sound change rules file #}
+        soundchange(
+            {# proto-form, linguinput codes allowed #}
+            word:string,
+            {# rules file name, without extension, in the rules directory #}
+            rules:string
+        ) -> String "
+            .to_owned()
+    }
+}
+
+args! {
+    positional ["in"];
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct TranslitArgs<'a> {
+        /// text in the original script, linguinput codes allowed
+        input: &'a str => "in",
+        /// language code, naming the rules file in the transliteration
+        /// directory
+        lang: &'a str => "lg",
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TranslitFn {
+    rules: RuleCache,
+}
+
+impl TranslitFn {
+    pub fn new(
+        fs: Arc<dyn SiteFs>,
+        table: Arc<Table>,
+        translit_dir: PathBuf,
+    ) -> Self {
+        Self {
+            rules: RuleCache::new(fs, table, translit_dir, TRANSLIT_EXTENSION),
+        }
+    }
+}
+
+impl Function for TranslitFn {
+    type Args<'a> = TranslitArgs<'a>;
+    type Output = String;
+    type Error = SoundChangeError;
+
+    fn call<'a>(
+        &self,
+        args: Self::Args<'a>,
+        _ctx: &PageCtx,
+    ) -> Result<Self::Output, Self::Error> {
+        let rule_set = self.rules.load(args.lang)?;
+        Ok(rule_set.transliterate(&self.rules.encode(args.input)?))
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "{{# romanize text with a per-language rules file, in the sound \
+             change syntax but applied in a single pass where the first \
+             matching rule wins #}}\ntranslit(\n{}\n) -> String",
+            TranslitArgs::doc()
+        )
+    }
+}
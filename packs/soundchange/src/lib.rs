@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+
+use function::{SoundChangeFn, TranslitFn};
+use lin_ssg_core::{LinSsg, Pack};
+pub use rules::{RuleSet, RuleSyntaxError};
+
+mod function;
+mod rules;
+
+#[cfg(test)]
+mod test;
+
+pub const DEFAULT_RULES_DIR: &str = "soundchanges";
+pub const RULES_EXTENSION: &str = "sc";
+
+pub const DEFAULT_TRANSLIT_DIR: &str = "data/translit";
+pub const TRANSLIT_EXTENSION: &str = "tr";
+
+pub const PACK_NAME: &str = "soundchange";
+
+#[derive(Debug, Clone)]
+pub struct SoundChangePack {
+    rules_dir: PathBuf,
+    translit_dir: PathBuf,
+}
+
+impl SoundChangePack {
+    pub fn new(rules_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            rules_dir: rules_dir.into(),
+            translit_dir: PathBuf::from(DEFAULT_TRANSLIT_DIR),
+        }
+    }
+
+    pub fn with_translit_dir(
+        mut self,
+        translit_dir: impl Into<PathBuf>,
+    ) -> Self {
+        self.translit_dir = translit_dir.into();
+        self
+    }
+}
+
+impl Default for SoundChangePack {
+    fn default() -> Self {
+        Self::new(DEFAULT_RULES_DIR)
+    }
+}
+
+impl Pack for SoundChangePack {
+    fn name(&self) -> &str {
+        PACK_NAME
+    }
+
+    fn install(&self, ssg: &mut LinSsg) {
+        install_with(ssg, self.rules_dir.clone());
+        let fs = ssg.config().shared_fs();
+        let table = ssg.linguinput_table();
+        ssg.register_fn(
+            "translit",
+            TranslitFn::new(fs, table, self.translit_dir.clone()),
+        );
+    }
+
+    fn doc(&self) -> String {
+        format!(
+            "sound change rules applied to words, read from {}/*.{}, and \
+             transliteration rules, read from {}/*.{}",
+            self.rules_dir.display(),
+            RULES_EXTENSION,
+            self.translit_dir.display(),
+            TRANSLIT_EXTENSION
+        )
+    }
+}
+
+pub fn install(ssg: &mut LinSsg) {
+    install_with(ssg, DEFAULT_RULES_DIR);
+    let fs = ssg.config().shared_fs();
+    let table = ssg.linguinput_table();
+    ssg.register_fn(
+        "translit",
+        TranslitFn::new(fs, table, DEFAULT_TRANSLIT_DIR.into()),
+    );
+}
+
+pub fn install_with(ssg: &mut LinSsg, rules_dir: impl Into<PathBuf>) {
+    let fs = ssg.config().shared_fs();
+    let table = ssg.linguinput_table();
+    ssg.register_fn(
+        "soundchange",
+        SoundChangeFn::new(fs, table, rules_dir.into()),
+    );
+}
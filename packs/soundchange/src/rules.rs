@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+pub const COMMENT_START: &str = "//";
+pub const RULE_ARROW: char = '>';
+pub const ENV_SEPARATOR: char = '/';
+pub const ENV_FOCUS: char = '_';
+pub const WORD_BOUNDARY: char = '#';
+pub const EMPTY_MARKERS: &[&str] = &["0", "∅"];
+
+#[derive(Debug, Clone, Error)]
+pub enum RuleSyntaxError {
+    #[error("Line {}: missing '>' between target and replacement", .0)]
+    MissingArrow(usize),
+    #[error("Line {}: environment must contain exactly one '_'", .0)]
+    BadEnvironment(usize),
+    #[error("Line {}: target must not be empty", .0)]
+    EmptyTarget(usize),
+    #[error("Line {}: category {} is not defined", .0, .1)]
+    UnknownCategory(usize, char),
+    #[error(
+        "Line {}: categories {} and {} have different sizes",
+        .0,
+        .1,
+        .2
+    )]
+    CategorySizeMismatch(usize, char, char),
+    #[error("Line {}: replacement cannot use category {} here", .0, .1)]
+    BadReplacementCategory(usize, char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Boundary,
+    Category(char),
+    Literal(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Replacement {
+    Literal(String),
+    Category(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    target: Vec<Token>,
+    replacement: Replacement,
+    left: Vec<Token>,
+    right: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RuleSet {
+    categories: HashMap<char, Vec<String>>,
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn parse(code: &str) -> Result<Self, RuleSyntaxError> {
+        let mut this = Self::default();
+        for (i, line) in code.lines().enumerate() {
+            let line_number = i + 1;
+            let line = match line.find(COMMENT_START) {
+                Some(start) => &line[.. start],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some((name, members)) = this.parse_category(line) {
+                this.categories.insert(name, members);
+            } else {
+                let rule = this.parse_rule(line_number, line)?;
+                this.rules.push(rule);
+            }
+        }
+        Ok(this)
+    }
+
+    pub fn apply(&self, word: &str) -> String {
+        let mut current = word.to_owned();
+        for rule in &self.rules {
+            current = self.apply_rules(&[rule], &current);
+        }
+        current
+    }
+
+    pub fn transliterate(&self, text: &str) -> String {
+        let rules: Vec<_> = self.rules.iter().collect();
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+        while !rest.is_empty() {
+            let word_len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            output.push_str(&self.apply_rules(&rules, &rest[.. word_len]));
+            rest = &rest[word_len ..];
+            let space_len =
+                rest.find(|ch: char| !ch.is_whitespace()).unwrap_or(rest.len());
+            output.push_str(&rest[.. space_len]);
+            rest = &rest[space_len ..];
+        }
+        output
+    }
+
+    fn parse_category(&self, line: &str) -> Option<(char, Vec<String>)> {
+        let (name, members) = line.split_once('=')?;
+        let mut name_chars = name.trim().chars();
+        let name = name_chars.next()?;
+        if name_chars.next().is_some() || !name.is_ascii_uppercase() {
+            return None;
+        }
+        let members = members.split_whitespace().map(str::to_owned).collect();
+        Some((name, members))
+    }
+
+    fn parse_rule(
+        &self,
+        line_number: usize,
+        line: &str,
+    ) -> Result<Rule, RuleSyntaxError> {
+        let (change, environment) = match line.split_once(ENV_SEPARATOR) {
+            Some((change, environment)) => (change, Some(environment)),
+            None => (line, None),
+        };
+        let (target, replacement) = change
+            .split_once(RULE_ARROW)
+            .ok_or(RuleSyntaxError::MissingArrow(line_number))?;
+
+        let target = self.tokenize(line_number, target)?;
+        if target.is_empty() {
+            Err(RuleSyntaxError::EmptyTarget(line_number))?;
+        }
+        let replacement =
+            self.parse_replacement(line_number, &target, replacement.trim())?;
+
+        let (left, right) = match environment {
+            Some(environment) => {
+                let (left, right) = environment
+                    .split_once(ENV_FOCUS)
+                    .filter(|(_, right)| !right.contains(ENV_FOCUS))
+                    .ok_or(RuleSyntaxError::BadEnvironment(line_number))?;
+                (
+                    self.tokenize(line_number, left)?,
+                    self.tokenize(line_number, right)?,
+                )
+            },
+            None => (Vec::new(), Vec::new()),
+        };
+
+        Ok(Rule { target, replacement, left, right })
+    }
+
+    fn parse_replacement(
+        &self,
+        line_number: usize,
+        target: &[Token],
+        replacement: &str,
+    ) -> Result<Replacement, RuleSyntaxError> {
+        if EMPTY_MARKERS.contains(&replacement) {
+            return Ok(Replacement::Literal(String::new()));
+        }
+        let mut chars = replacement.chars();
+        if let (Some(name), None) = (chars.next(), chars.next()) {
+            if let Some(members) = self.categories.get(&name) {
+                let [Token::Category(target_name)] = target else {
+                    Err(RuleSyntaxError::BadReplacementCategory(
+                        line_number,
+                        name,
+                    ))?
+                };
+                if self.categories[target_name].len() != members.len() {
+                    Err(RuleSyntaxError::CategorySizeMismatch(
+                        line_number,
+                        *target_name,
+                        name,
+                    ))?;
+                }
+                return Ok(Replacement::Category(name));
+            }
+        }
+        Ok(Replacement::Literal(replacement.split_whitespace().collect()))
+    }
+
+    fn tokenize(
+        &self,
+        line_number: usize,
+        pattern: &str,
+    ) -> Result<Vec<Token>, RuleSyntaxError> {
+        let mut tokens = Vec::new();
+        for ch in pattern.chars().filter(|ch| !ch.is_whitespace()) {
+            if ch == WORD_BOUNDARY {
+                tokens.push(Token::Boundary);
+            } else if self.categories.contains_key(&ch) {
+                tokens.push(Token::Category(ch));
+            } else if ch.is_ascii_uppercase() {
+                Err(RuleSyntaxError::UnknownCategory(line_number, ch))?;
+            } else if let Some(Token::Literal(literal)) = tokens.last_mut() {
+                literal.push(ch);
+            } else {
+                tokens.push(Token::Literal(ch.to_string()));
+            }
+        }
+        Ok(tokens)
+    }
+
+    // At each position, the first rule that matches wins, and matching
+    // resumes after the replaced text, so rules never see each other's
+    // output.
+    fn apply_rules(&self, rules: &[&Rule], word: &str) -> String {
+        let mut output = String::with_capacity(word.len());
+        let mut position = 0;
+        while position < word.len() {
+            let matched = rules.iter().find_map(|rule| {
+                self.match_forward(&rule.target, word, position)
+                    .filter(|&(end, _)| {
+                        self.match_forward(&rule.right, word, end).is_some()
+                            && self.match_backward(&rule.left, word, position)
+                    })
+                    .map(|matched| (rule, matched))
+            });
+            match matched {
+                Some((rule, (end, member_index))) => {
+                    match &rule.replacement {
+                        Replacement::Literal(literal) => {
+                            output.push_str(literal)
+                        },
+                        Replacement::Category(name) => {
+                            let index = member_index.unwrap_or_default();
+                            output.push_str(&self.categories[name][index]);
+                        },
+                    }
+                    position = end;
+                },
+                None => {
+                    let ch = word[position ..].chars().next().unwrap_or('\0');
+                    output.push(ch);
+                    position += ch.len_utf8();
+                },
+            }
+        }
+        output
+    }
+
+    fn match_forward(
+        &self,
+        tokens: &[Token],
+        word: &str,
+        mut position: usize,
+    ) -> Option<(usize, Option<usize>)> {
+        let mut member_index = None;
+        for token in tokens {
+            match token {
+                Token::Boundary => {
+                    if position != word.len() {
+                        return None;
+                    }
+                },
+                Token::Literal(literal) => {
+                    if !word[position ..].starts_with(literal.as_str()) {
+                        return None;
+                    }
+                    position += literal.len();
+                },
+                Token::Category(name) => {
+                    let (index, member) = self.categories[name]
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, member)| {
+                            word[position ..].starts_with(member.as_str())
+                        })
+                        .max_by_key(|(_, member)| member.len())?;
+                    member_index.get_or_insert(index);
+                    position += member.len();
+                },
+            }
+        }
+        Some((position, member_index))
+    }
+
+    fn match_backward(
+        &self,
+        tokens: &[Token],
+        word: &str,
+        mut position: usize,
+    ) -> bool {
+        for token in tokens.iter().rev() {
+            match token {
+                Token::Boundary => {
+                    if position != 0 {
+                        return false;
+                    }
+                },
+                Token::Literal(literal) => {
+                    if !word[.. position].ends_with(literal.as_str()) {
+                        return false;
+                    }
+                    position -= literal.len();
+                },
+                Token::Category(name) => {
+                    let Some(member) = self.categories[name]
+                        .iter()
+                        .filter(|member| {
+                            word[.. position].ends_with(member.as_str())
+                        })
+                        .max_by_key(|member| member.len())
+                    else {
+                        return false;
+                    };
+                    position -= member.len();
+                },
+            }
+        }
+        true
+    }
+}
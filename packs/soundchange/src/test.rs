@@ -0,0 +1,50 @@
+use crate::RuleSet;
+
+#[test]
+fn unconditioned_change() {
+    let rules = RuleSet::parse("p > f").unwrap();
+    assert_eq!(rules.apply("pap"), "faf");
+}
+
+#[test]
+fn word_final_environment() {
+    let rules = RuleSet::parse("p > f / _#").unwrap();
+    assert_eq!(rules.apply("pap"), "paf");
+}
+
+#[test]
+fn category_mapping_between_vowels() {
+    let code = "
+        // Grimm-like lenition
+        V = a e i o u
+        S = p t k
+        F = f θ x
+        S > F / V_V
+    ";
+    let rules = RuleSet::parse(code).unwrap();
+    assert_eq!(rules.apply("pataka"), "paθaxa");
+}
+
+#[test]
+fn deletion_and_ordering() {
+    let rules = RuleSet::parse("h > 0 / #_\na > e").unwrap();
+    assert_eq!(rules.apply("hana"), "ene");
+}
+
+#[test]
+fn transliteration_in_a_single_pass() {
+    let code = "
+        V = а е и о у
+        е > ye / #_
+        е > ye / V_
+        е > e
+        щ > shch
+        ш > sh
+        с > s
+        а > a
+        о > o
+    ";
+    let rules = RuleSet::parse(code).unwrap();
+    assert_eq!(rules.transliterate("ещё щаше"), "yeshchё shchashe");
+    assert_eq!(rules.transliterate("оес"), "oyes");
+}